@@ -0,0 +1,29 @@
+//! Times running a programme padded with `nop`s, the case the `nop` fast path
+//! in [Instruction]'s execute is for.
+//!
+//! Run with `cargo bench --bench nops`.
+use std::time::Instant;
+
+use riskv::{csr::CSR32, instructions::Instruction, processor::Processor};
+
+/// The canonical encoding of `nop`, `addi x0, x0, 0`.
+const NOP: u32 = 0x0000_0013;
+
+/// The number of `nop`s in the programme.
+const NOPS: usize = 1_000_000;
+
+fn main() {
+    let mut processor = Processor::<i32, CSR32>::default();
+    processor.store_words(0, &vec![NOP; NOPS]);
+
+    let start = Instant::now();
+    processor.run::<Instruction>();
+    let elapsed = start.elapsed();
+    assert_eq!(processor.pc() as usize, NOPS * 4);
+    println!(
+        "{:>8}: {:?} ({:.2} ns/op)",
+        "nop",
+        elapsed,
+        elapsed.as_nanos() as f64 / NOPS as f64
+    );
+}
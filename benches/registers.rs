@@ -0,0 +1,99 @@
+//! Compares indexing the array backed [Registers] with the previous layout of
+//! one named field per register indexed through a `match`.
+//!
+//! Run with `cargo bench --bench registers`.
+use std::hint::black_box;
+use std::time::Instant;
+
+use riskv::registers::Registers;
+
+/// The number of register operations in each run.
+const OPERATIONS: usize = 10_000_000;
+
+/// Declares [NamedRegisters], a register file with one field per register.
+macro_rules! named_registers {
+    ($($index:literal => $name:ident),* $(,)?) => {
+        /// The previous layout of the register file, one field per register.
+        #[derive(Default)]
+        struct NamedRegisters {
+            /// Writes to the zero register are discarded into this field.
+            discarded: i32,
+            $(
+                #[doc = concat!("The register `x", stringify!($index), "`.")]
+                $name: i32,
+            )*
+        }
+
+        impl std::ops::Index<u8> for NamedRegisters {
+            type Output = i32;
+
+            #[inline]
+            fn index(&self, index: u8) -> &i32 {
+                match index {
+                    0 => &0,
+                    $($index => &self.$name,)*
+                    _ => panic!("Out of bounds"),
+                }
+            }
+        }
+
+        impl std::ops::IndexMut<u8> for NamedRegisters {
+            #[inline]
+            fn index_mut(&mut self, index: u8) -> &mut i32 {
+                match index {
+                    0 => {
+                        self.discarded = 0;
+                        &mut self.discarded
+                    }
+                    $($index => &mut self.$name,)*
+                    _ => panic!("Out of bounds"),
+                }
+            }
+        }
+    };
+}
+
+named_registers!(
+    1 => ra, 2 => sp, 3 => gp, 4 => tp, 5 => t0, 6 => t1, 7 => t2, 8 => s0,
+    9 => s1, 10 => a0, 11 => a1, 12 => a2, 13 => a3, 14 => a4, 15 => a5,
+    16 => a6, 17 => a7, 18 => s2, 19 => s3, 20 => s4, 21 => s5, 22 => s6,
+    23 => s7, 24 => s8, 25 => s9, 26 => s10, 27 => s11, 28 => t3, 29 => t4,
+    30 => t5, 31 => t6,
+);
+
+/// Pseudo-random `(rd, rs1, rs2)` operands, as decoded from a programme.
+fn operands() -> Vec<(u8, u8, u8)> {
+    let mut state = 0x2545_f491_u32;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        (state % 32) as u8
+    };
+    (0..1024).map(|_| (next(), next(), next())).collect()
+}
+
+/// Time [OPERATIONS] additions of the form `rd = rs1 + rs2`.
+fn run<R>(name: &str, mut registers: R, operands: &[(u8, u8, u8)])
+where
+    R: std::ops::IndexMut<u8, Output = i32>,
+{
+    let start = Instant::now();
+    for &(rd, rs1, rs2) in operands.iter().cycle().take(OPERATIONS) {
+        let (rd, rs1, rs2) = black_box((rd, rs1, rs2));
+        registers[rd] = registers[rs1].wrapping_add(registers[rs2]).wrapping_add(1);
+    }
+    let elapsed = start.elapsed();
+    black_box(&registers[10]);
+    println!(
+        "{name:>8}: {:?} ({:.2} ns/op)",
+        elapsed,
+        elapsed.as_nanos() as f64 / OPERATIONS as f64
+    );
+}
+
+fn main() {
+    let operands = operands();
+    run("array", Registers::<i32>::default(), &operands);
+    run("named", NamedRegisters::default(), &operands);
+}
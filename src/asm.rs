@@ -0,0 +1,1553 @@
+//! A two-pass assembler for RISC-V assembly.
+//!
+//! The assembler supports the instructions of [crate::instructions::Instruction],
+//! the common pseudoinstructions, labels, and the following directives:
+//!
+//! | Directive           | Description                                           |
+//! | ------------------- | ----------------------------------------------------- |
+//! | `.text`             | Assemble the following statements as code.            |
+//! | `.data`             | Assemble the following statements as data.            |
+//! | `.word w[, w...]`   | Emit each value as a 32-bit little endian word.       |
+//! | `.byte b[, b...]`   | Emit each value as a byte.                            |
+//! | `.org offset`       | Move the location counter to `offset` from the start  |
+//! |                     | of the current section.                               |
+//!
+//! Code and data are assembled into separate sections, each with its own
+//! location counter. The text section starts at the base address and the data
+//! section follows it, aligned to a word boundary.
+//!
+//! Comments start with `#` and run to the end of the line. Character literals,
+//! such as `'h'` or `'\n'`, can be used wherever a number is expected.
+//!
+//! The first pass assigns an address to every label, the second pass encodes
+//! each statement, so labels can be referenced before they are defined.
+//! Wherever an immediate is expected a label can be used in its place, in
+//! which case the address of the label is used. Branch and jump targets are
+//! labels, or numeric offsets relative to the instruction.
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+
+use crate::csr;
+use crate::instructions::Instruction;
+use crate::registers::Register;
+
+/// The size in bytes of an encoded instruction.
+const INSTRUCTION_SIZE: usize = 4;
+/// The alignment in bytes of the start of the data section.
+const DATA_ALIGNMENT: usize = 4;
+
+/// The sections into which statements are assembled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    /// The code of the programme.
+    Text,
+    /// The data of the programme.
+    Data,
+}
+
+/// The location counters of each section.
+#[derive(Debug, Clone, Copy)]
+struct Cursor {
+    /// The current section.
+    section: Section,
+    /// The location in the text section.
+    text: usize,
+    /// The location in the data section.
+    data: usize,
+}
+
+impl Cursor {
+    /// The location in the current section.
+    fn location(&mut self) -> &mut usize {
+        match self.section {
+            Section::Text => &mut self.text,
+            Section::Data => &mut self.data,
+        }
+    }
+}
+
+/// A contiguous run of assembled bytes to be loaded at `address`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    /// The address of the first byte of the segment.
+    pub address: usize,
+    /// The assembled bytes.
+    pub bytes: Vec<u8>,
+}
+
+/// An assembled programme, made up of the segments to load into memory.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Program {
+    /// The segments of the programme in the order they were assembled.
+    pub segments: Vec<Segment>,
+}
+
+impl Program {
+    /// Append the `bytes` at `address`, extending an existing segment if the
+    /// bytes immediately follow it.
+    fn emit(&mut self, address: usize, bytes: &[u8]) {
+        if bytes.is_empty() {
+            return;
+        }
+        match self
+            .segments
+            .iter_mut()
+            .find(|segment| segment.address + segment.bytes.len() == address)
+        {
+            Some(segment) => segment.bytes.extend_from_slice(bytes),
+            None => self.segments.push(Segment {
+                address,
+                bytes: bytes.to_vec(),
+            }),
+        }
+    }
+}
+
+/// The location in the source of the text which caused an [AssembleError].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    /// The line number, starting from `1`.
+    pub line: usize,
+    /// The column, in bytes, starting from `1`.
+    pub column: usize,
+}
+
+impl Span {
+    /// The span of `text`, which must be a slice of `source`.
+    fn of(source: &str, text: &str) -> Self {
+        let offset = text.as_ptr() as usize - source.as_ptr() as usize;
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: offset - line_start + 1,
+        }
+    }
+}
+
+/// An error raised while assembling.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssembleError {
+    /// An operand is missing between commas.
+    EmptyOperand {
+        /// The location of the missing operand.
+        span: Span,
+    },
+    /// An instruction or directive has the wrong number of operands.
+    OperandCount {
+        /// The location of the instruction or directive.
+        span: Span,
+        /// The number of operands expected.
+        expected: usize,
+        /// The number of operands found.
+        found: usize,
+    },
+    /// An operand is not a valid number or label.
+    InvalidImmediate {
+        /// The location of the operand.
+        span: Span,
+        /// The operand.
+        operand: String,
+    },
+    /// An immediate does not fit in the instruction or directive.
+    ImmediateOutOfRange {
+        /// The location of the immediate.
+        span: Span,
+        /// The value of the immediate.
+        value: i64,
+        /// The smallest valid value.
+        min: i64,
+        /// The largest valid value.
+        max: i64,
+    },
+    /// A memory operand is not of the form `offset(register)`.
+    InvalidMemoryOperand {
+        /// The location of the operand.
+        span: Span,
+        /// The operand.
+        operand: String,
+    },
+    /// A register name is not recognised.
+    UnknownRegister {
+        /// The location of the register.
+        span: Span,
+        /// The register name.
+        name: String,
+    },
+    /// A CSR name or address is not recognised.
+    UnknownCsr {
+        /// The location of the CSR.
+        span: Span,
+        /// The CSR name.
+        name: String,
+    },
+    /// A label is referenced but never defined.
+    UnknownLabel {
+        /// The location of the reference.
+        span: Span,
+        /// The label.
+        label: String,
+    },
+    /// A label is defined more than once.
+    DuplicateLabel {
+        /// The location of the second definition.
+        span: Span,
+        /// The label.
+        label: String,
+    },
+    /// An instruction mnemonic is not recognised.
+    UnknownInstruction {
+        /// The location of the mnemonic.
+        span: Span,
+        /// The mnemonic.
+        mnemonic: String,
+    },
+    /// A directive is not recognised.
+    UnknownDirective {
+        /// The location of the directive.
+        span: Span,
+        /// The directive.
+        directive: String,
+    },
+    /// An `.org` directive would move the location counter backwards.
+    OrgBackwards {
+        /// The location of the offset.
+        span: Span,
+        /// The offset from the start of the section.
+        offset: usize,
+    },
+    /// A branch or jump target is not a multiple of 2 bytes away.
+    MisalignedTarget {
+        /// The location of the target.
+        span: Span,
+        /// The offset to the target.
+        offset: i64,
+    },
+    /// A `fence` operand is not a set of the letters `iorw`, or `0`.
+    InvalidFenceSet {
+        /// The location of the operand.
+        span: Span,
+        /// The operand.
+        operand: String,
+    },
+}
+
+impl AssembleError {
+    /// The location in the source of the text which caused the error.
+    pub const fn span(&self) -> Span {
+        match self {
+            Self::EmptyOperand { span }
+            | Self::OperandCount { span, .. }
+            | Self::InvalidImmediate { span, .. }
+            | Self::ImmediateOutOfRange { span, .. }
+            | Self::InvalidMemoryOperand { span, .. }
+            | Self::UnknownRegister { span, .. }
+            | Self::UnknownCsr { span, .. }
+            | Self::UnknownLabel { span, .. }
+            | Self::DuplicateLabel { span, .. }
+            | Self::UnknownInstruction { span, .. }
+            | Self::UnknownDirective { span, .. }
+            | Self::OrgBackwards { span, .. }
+            | Self::MisalignedTarget { span, .. }
+            | Self::InvalidFenceSet { span, .. } => *span,
+        }
+    }
+}
+
+impl Display for AssembleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Span { line, column } = self.span();
+        f.write_fmt(format_args!("line {line}, column {column}: "))?;
+        match self {
+            Self::EmptyOperand { .. } => f.write_str("empty operand"),
+            Self::OperandCount {
+                expected, found, ..
+            } => f.write_fmt(format_args!(
+                "expected {expected} operand{}, found {found}",
+                if *expected == 1 { "" } else { "s" },
+            )),
+            Self::InvalidImmediate { operand, .. } => {
+                f.write_fmt(format_args!("invalid immediate '{operand}'"))
+            }
+            Self::ImmediateOutOfRange {
+                value, min, max, ..
+            } => f.write_fmt(format_args!(
+                "immediate {value} out of range [{min}, {max}]"
+            )),
+            Self::InvalidMemoryOperand { operand, .. } => f.write_fmt(format_args!(
+                "expected 'offset(register)', found '{operand}'"
+            )),
+            Self::UnknownRegister { name, .. } => {
+                f.write_fmt(format_args!("unknown register '{name}'"))
+            }
+            Self::UnknownCsr { name, .. } => f.write_fmt(format_args!("unknown CSR '{name}'")),
+            Self::UnknownLabel { label, .. } => {
+                f.write_fmt(format_args!("unknown label '{label}'"))
+            }
+            Self::DuplicateLabel { label, .. } => {
+                f.write_fmt(format_args!("label '{label}' is already defined"))
+            }
+            Self::UnknownInstruction { mnemonic, .. } => {
+                f.write_fmt(format_args!("unknown instruction '{mnemonic}'"))
+            }
+            Self::UnknownDirective { directive, .. } => {
+                f.write_fmt(format_args!("unknown directive '{directive}'"))
+            }
+            Self::OrgBackwards { offset, .. } => f.write_fmt(format_args!(
+                "'.org {offset}' moves the location counter backwards"
+            )),
+            Self::MisalignedTarget { offset, .. } => f.write_fmt(format_args!(
+                "target offset {offset} is not a multiple of 2"
+            )),
+            Self::InvalidFenceSet { operand, .. } => f.write_fmt(format_args!(
+                "expected a fence set of 'iorw', found '{operand}'"
+            )),
+        }
+    }
+}
+
+impl std::error::Error for AssembleError {}
+
+/// A single line of assembly.
+struct Statement<'a> {
+    /// The labels defined on this line.
+    labels: Vec<&'a str>,
+    /// The directive or instruction mnemonic, including the leading `.` for
+    /// directives, and its operands.
+    item: Option<(&'a str, Vec<&'a str>)>,
+}
+
+/// Assemble the `source` into a [Program] starting at the address `base`.
+pub fn assemble(source: &str, base: usize) -> Result<Program, AssembleError> {
+    let statements = source
+        .lines()
+        .map(|line| parse_line(source, line))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut assembler = Assembler {
+        source,
+        base,
+        data: base,
+        symbols: HashMap::new(),
+    };
+    assembler.layout(&statements)?;
+    assembler.emit(&statements)
+}
+
+/// Parse a single `line` of the `source` into the labels it defines and the
+/// directive or instruction it contains.
+fn parse_line<'a>(source: &str, line: &'a str) -> Result<Statement<'a>, AssembleError> {
+    let mut rest = strip_comment(line).trim();
+    let mut labels = Vec::new();
+    while let Some((label, remainder)) = rest.split_once(':') {
+        if !is_identifier(label.trim()) {
+            break;
+        }
+        labels.push(label.trim());
+        rest = remainder.trim();
+    }
+
+    let item = match rest.split_once(char::is_whitespace) {
+        _ if rest.is_empty() => None,
+        Some((mnemonic, operands)) => Some((mnemonic, split_operands(source, operands)?)),
+        None => Some((rest, Vec::new())),
+    };
+    Ok(Statement { labels, item })
+}
+
+/// Remove a trailing `#` comment from the `line`.
+fn strip_comment(line: &str) -> &str {
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '\'' => in_quotes = !in_quotes,
+            '#' if !in_quotes => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Split comma separated `operands` from the `source`, ignoring commas within
+/// quotes.
+fn split_operands<'a>(source: &str, operands: &'a str) -> Result<Vec<&'a str>, AssembleError> {
+    let mut split = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (i, c) in operands.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '\'' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                split.push(operands[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    split.push(operands[start..].trim());
+    match split.iter().find(|operand| operand.is_empty()) {
+        Some(empty) => Err(AssembleError::EmptyOperand {
+            span: Span::of(source, empty),
+        }),
+        None => Ok(split),
+    }
+}
+
+/// Returns `true` if `value` is a valid label name.
+fn is_identifier(value: &str) -> bool {
+    let mut chars = value.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '.')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Parse a numeric literal, in decimal, hexadecimal (`0x`), or binary (`0b`),
+/// or a character literal.
+fn parse_number(value: &str) -> Option<i64> {
+    if let Some(character) = value.strip_prefix('\'') {
+        return parse_char(character.strip_suffix('\'')?);
+    }
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value),
+    };
+    let magnitude = if let Some(hex) = digits.strip_prefix("0x").or(digits.strip_prefix("0X")) {
+        i64::from_str_radix(hex, 16).ok()?
+    } else if let Some(binary) = digits.strip_prefix("0b").or(digits.strip_prefix("0B")) {
+        i64::from_str_radix(binary, 2).ok()?
+    } else {
+        digits.parse::<i64>().ok()?
+    };
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+/// Parse the contents of a character literal, which is either a single ASCII
+/// character or one of the escapes `\0`, `\n`, `\r`, `\t`, `\\`, `\'`, or `\"`.
+fn parse_char(value: &str) -> Option<i64> {
+    let character = match value.as_bytes() {
+        [b'\\', escape] => match escape {
+            b'0' => 0,
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b't' => b'\t',
+            b'\\' | b'\'' | b'"' => *escape,
+            _ => return None,
+        },
+        [character] if character.is_ascii() && *character != b'\\' => *character,
+        _ => return None,
+    };
+    Some(character.into())
+}
+
+/// Parse a register by its ABI name, such as `a0`, or its number, such as
+/// `x10`.
+fn parse_register(value: &str) -> Option<Register> {
+    if value == "fp" {
+        return Some(Register::FP);
+    }
+    if let Some(number) = value.strip_prefix('x') {
+        // Reject leading zeros and signs, such as `x01` or `x+1`.
+        return number
+            .parse::<u8>()
+            .ok()
+            .filter(|register| *register < 32 && register.to_string() == number)
+            .map(Register::from);
+    }
+    (0..32)
+        .map(Register::from)
+        .find(|register| register.name() == value)
+}
+
+/// Parse a CSR by its name, such as `mstatus`, or its address.
+fn parse_csr(value: &str) -> Option<u16> {
+    csr::from_name(value).or_else(|| parse_number(value).and_then(|csr| u16::try_from(csr).ok()))
+}
+
+/// Split a `pc` relative `offset` into the upper immediate for `AUIPC` and
+/// the sign-extended lower 12 bits, accounting for the sign extension of
+/// the lower bits.
+fn split_offset(offset: i64) -> (i32, i16) {
+    let upper = (((offset + 0x800) >> 12) & 0xF_FFFF) as i32;
+    let lower = ((offset & 0xFFF) as i16) << 4 >> 4;
+    (upper, lower)
+}
+
+/// The state of the assembler shared between the passes.
+struct Assembler<'a> {
+    /// The source being assembled.
+    source: &'a str,
+    /// The address at which the programme, and its text section, starts.
+    base: usize,
+    /// The address at which the data section starts.
+    data: usize,
+    /// The addresses of the labels.
+    symbols: HashMap<&'a str, usize>,
+}
+
+impl<'a> Assembler<'a> {
+    /// The first pass, assigning an address to each label.
+    ///
+    /// Since the size of the text section is not known until the end of the
+    /// pass, data labels are laid out relative to the start of the data
+    /// section and relocated once the start of the data section is known.
+    fn layout(&mut self, statements: &[Statement<'a>]) -> Result<(), AssembleError> {
+        let mut cursor = Cursor {
+            section: Section::Text,
+            text: self.base,
+            data: 0,
+        };
+        let mut data_labels = Vec::new();
+        for statement in statements {
+            for label in &statement.labels {
+                if self.symbols.insert(label, *cursor.location()).is_some() {
+                    return Err(AssembleError::DuplicateLabel {
+                        span: self.span(label),
+                        label: label.to_string(),
+                    });
+                }
+                if cursor.section == Section::Data {
+                    data_labels.push(*label);
+                }
+            }
+            if let Some((mnemonic, operands)) = &statement.item {
+                self.advance(&mut cursor, 0, mnemonic, operands)?;
+            }
+        }
+
+        self.data = cursor.text.next_multiple_of(DATA_ALIGNMENT);
+        for label in data_labels {
+            if let Some(address) = self.symbols.get_mut(label) {
+                *address += self.data;
+            }
+        }
+        Ok(())
+    }
+
+    /// Move the `cursor` past the directive or instruction at its location,
+    /// where the data section starts at `data`.
+    fn advance(
+        &self,
+        cursor: &mut Cursor,
+        data: usize,
+        mnemonic: &str,
+        operands: &[&str],
+    ) -> Result<(), AssembleError> {
+        let size = match mnemonic {
+            ".text" | ".data" => {
+                let [] = self.operands(mnemonic, operands)?;
+                cursor.section = match mnemonic {
+                    ".text" => Section::Text,
+                    _ => Section::Data,
+                };
+                0
+            }
+            ".word" => 4 * operands.len(),
+            ".byte" => operands.len(),
+            ".org" => {
+                let start = match cursor.section {
+                    Section::Text => self.base,
+                    Section::Data => data,
+                };
+                let location = cursor.location();
+                *location = self.org(start, *location, mnemonic, operands)?;
+                return Ok(());
+            }
+            _ if mnemonic.starts_with('.') => {
+                return Err(AssembleError::UnknownDirective {
+                    span: self.span(mnemonic),
+                    directive: mnemonic.to_string(),
+                })
+            }
+            "li" => {
+                let [_, imm] = self.operands(mnemonic, operands)?;
+                INSTRUCTION_SIZE * Instruction::LI(Register::ZERO, self.li_immediate(imm)?).count()
+            }
+            "call" | "tail" | "la" => 2 * INSTRUCTION_SIZE,
+            _ => INSTRUCTION_SIZE,
+        };
+        *cursor.location() += size;
+        Ok(())
+    }
+
+    /// The location set by an `.org` directive in a section starting at
+    /// `start`.
+    ///
+    /// The location counter can only be moved forwards.
+    fn org(
+        &self,
+        start: usize,
+        location: usize,
+        mnemonic: &str,
+        operands: &[&str],
+    ) -> Result<usize, AssembleError> {
+        let [operand] = self.operands(mnemonic, operands)?;
+        let offset = self.in_range(operand, 0..=i32::MAX.into())? as usize;
+        let target = start + offset;
+        if target < location {
+            return Err(AssembleError::OrgBackwards {
+                span: self.span(operand),
+                offset,
+            });
+        }
+        Ok(target)
+    }
+
+    /// The second pass, encoding each statement.
+    fn emit(&self, statements: &[Statement<'a>]) -> Result<Program, AssembleError> {
+        let mut program = Program::default();
+        let mut cursor = Cursor {
+            section: Section::Text,
+            text: self.base,
+            data: self.data,
+        };
+        for statement in statements {
+            let Some((mnemonic, operands)) = &statement.item else {
+                continue;
+            };
+            let location = *cursor.location();
+            let bytes = self.bytes(location, mnemonic, operands)?;
+            program.emit(location, &bytes);
+            self.advance(&mut cursor, self.data, mnemonic, operands)?;
+        }
+        Ok(program)
+    }
+
+    /// The bytes emitted by the directive or instruction at `location`.
+    fn bytes(
+        &self,
+        location: usize,
+        mnemonic: &str,
+        operands: &[&str],
+    ) -> Result<Vec<u8>, AssembleError> {
+        match mnemonic {
+            ".text" | ".data" | ".org" => Ok(Vec::new()),
+            ".word" => operands
+                .iter()
+                .map(|operand| {
+                    let value = self.in_range(operand, i32::MIN.into()..=u32::MAX.into())?;
+                    Ok((value as u32).to_le_bytes())
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(|words| words.concat()),
+            ".byte" => operands
+                .iter()
+                .map(|operand| {
+                    let value = self.in_range(operand, i8::MIN.into()..=u8::MAX.into())?;
+                    Ok(value as u8)
+                })
+                .collect(),
+            _ => Ok(self
+                .instructions(location, mnemonic, operands)?
+                .into_iter()
+                .flat_map(|instruction| instruction.encode().to_le_bytes())
+                .collect()),
+        }
+    }
+
+    /// The location in the source of `text`, a slice of the source.
+    fn span(&self, text: &str) -> Span {
+        Span::of(self.source, text)
+    }
+
+    /// Destructure the `operands` of `mnemonic` into an array of exactly `N`
+    /// operands.
+    fn operands<'b, const N: usize>(
+        &self,
+        mnemonic: &str,
+        operands: &[&'b str],
+    ) -> Result<[&'b str; N], AssembleError> {
+        operands
+            .try_into()
+            .map_err(|_| AssembleError::OperandCount {
+                span: self.span(mnemonic),
+                expected: N,
+                found: operands.len(),
+            })
+    }
+
+    /// Parse a register operand.
+    fn register(&self, operand: &str) -> Result<Register, AssembleError> {
+        parse_register(operand).ok_or_else(|| AssembleError::UnknownRegister {
+            span: self.span(operand),
+            name: operand.to_string(),
+        })
+    }
+
+    /// Parse a CSR operand.
+    fn csr(&self, operand: &str) -> Result<u16, AssembleError> {
+        parse_csr(operand)
+            .filter(|csr| *csr < 4096)
+            .ok_or_else(|| AssembleError::UnknownCsr {
+                span: self.span(operand),
+                name: operand.to_string(),
+            })
+    }
+
+    /// Parse a `fence` predecessor or successor set, the letters of its
+    /// members `iorw` in that order, or `0` for the empty set.
+    fn fence_set(&self, operand: &str) -> Result<u8, AssembleError> {
+        let error = || AssembleError::InvalidFenceSet {
+            span: self.span(operand),
+            operand: operand.to_string(),
+        };
+        if operand == "0" {
+            return Ok(0);
+        }
+        let mut letters = operand.chars().peekable();
+        let mut set = 0;
+        for (bit, letter) in [(0b1000, 'i'), (0b0100, 'o'), (0b0010, 'r'), (0b0001, 'w')] {
+            if letters.next_if_eq(&letter).is_some() {
+                set |= bit;
+            }
+        }
+        match (set, letters.next()) {
+            (1.., None) => Ok(set),
+            _ => Err(error()),
+        }
+    }
+
+    /// Parse an immediate operand, either a numeric literal or a label.
+    fn immediate(&self, operand: &str) -> Result<i64, AssembleError> {
+        parse_number(operand)
+            .or_else(|| self.symbols.get(operand).map(|address| *address as i64))
+            .ok_or_else(|| AssembleError::InvalidImmediate {
+                span: self.span(operand),
+                operand: operand.to_string(),
+            })
+    }
+
+    /// Check the `value` of the `operand` is within the `range`.
+    fn check_range(
+        &self,
+        operand: &str,
+        value: i64,
+        range: RangeInclusive<i64>,
+    ) -> Result<i64, AssembleError> {
+        if range.contains(&value) {
+            Ok(value)
+        } else {
+            Err(AssembleError::ImmediateOutOfRange {
+                span: self.span(operand),
+                value,
+                min: *range.start(),
+                max: *range.end(),
+            })
+        }
+    }
+
+    /// Parse an immediate operand which must be within the `range`.
+    fn in_range(&self, operand: &str, range: RangeInclusive<i64>) -> Result<i64, AssembleError> {
+        self.check_range(operand, self.immediate(operand)?, range)
+    }
+
+    /// Parse an immediate operand which must fit in a signed immediate of
+    /// `bits` bits.
+    fn signed(&self, operand: &str, bits: u32) -> Result<i64, AssembleError> {
+        self.in_range(operand, -(1 << (bits - 1))..=(1 << (bits - 1)) - 1)
+    }
+
+    /// Parse an immediate operand which must fit in an unsigned immediate of
+    /// `bits` bits.
+    fn unsigned(&self, operand: &str, bits: u32) -> Result<i64, AssembleError> {
+        self.in_range(operand, 0..=(1 << bits) - 1)
+    }
+
+    /// Parse the immediate of a `li` pseudoinstruction, which may be any 32-bit
+    /// signed or unsigned value.
+    ///
+    /// Unlike other immediates, labels are not accepted since the number of
+    /// instructions `li` expands to depends on the value.
+    fn li_immediate(&self, operand: &str) -> Result<i32, AssembleError> {
+        let value = parse_number(operand).ok_or_else(|| AssembleError::InvalidImmediate {
+            span: self.span(operand),
+            operand: operand.to_string(),
+        })?;
+        Ok(self.check_range(operand, value, i32::MIN.into()..=u32::MAX.into())? as i32)
+    }
+
+    /// Parse a memory operand of the form `offset(register)`.
+    fn memory(&self, operand: &str) -> Result<(i16, Register), AssembleError> {
+        let (offset, register) = operand
+            .strip_suffix(')')
+            .and_then(|operand| operand.split_once('('))
+            .ok_or_else(|| AssembleError::InvalidMemoryOperand {
+                span: self.span(operand),
+                operand: operand.to_string(),
+            })?;
+        let offset = match offset.trim() {
+            "" => 0,
+            offset => self.signed(offset, 12)?,
+        };
+        Ok((offset as i16, self.register(register.trim())?))
+    }
+
+    /// Parse a branch or jump target, either a label or a numeric offset,
+    /// as an offset relative to `location` which fits in `bits` bits.
+    fn target(&self, location: usize, operand: &str, bits: u32) -> Result<i32, AssembleError> {
+        let offset = match self.symbols.get(operand) {
+            Some(address) => *address as i64 - location as i64,
+            None => parse_number(operand).ok_or_else(|| AssembleError::UnknownLabel {
+                span: self.span(operand),
+                label: operand.to_string(),
+            })?,
+        };
+        if offset % 2 != 0 {
+            return Err(AssembleError::MisalignedTarget {
+                span: self.span(operand),
+                offset,
+            });
+        }
+        let limit = 1 << (bits - 1);
+        Ok(self.check_range(operand, offset, -limit..=limit - 1)? as i32)
+    }
+
+    /// Assemble an instruction or pseudoinstruction at `location`.
+    fn instructions(
+        &self,
+        location: usize,
+        mnemonic: &str,
+        ops: &[&str],
+    ) -> Result<Vec<Instruction>, AssembleError> {
+        let instruction = match mnemonic {
+            "lui" | "auipc" => {
+                let [rd, imm] = self.operands(mnemonic, ops)?;
+                let rd = self.register(rd)?;
+                let value = self.in_range(imm, -(1 << 19)..=(1 << 20) - 1)?;
+                let imm = (value & 0xF_FFFF) as i32;
+                match mnemonic {
+                    "lui" => Instruction::LUI { rd, imm },
+                    _ => Instruction::AUIPC { rd, imm },
+                }
+            }
+            "addi" | "slti" | "sltiu" | "xori" | "ori" | "andi" => {
+                let [rd, rs1, imm] = self.operands(mnemonic, ops)?;
+                let (rd, rs1) = (self.register(rd)?, self.register(rs1)?);
+                let imm = self.signed(imm, 12)? as i16;
+                match mnemonic {
+                    "addi" => Instruction::ADDI { rd, rs1, imm },
+                    "slti" => Instruction::SLTI { rd, rs1, imm },
+                    "sltiu" => Instruction::SLTIU { rd, rs1, imm },
+                    "xori" => Instruction::XORI { rd, rs1, imm },
+                    "ori" => Instruction::ORI { rd, rs1, imm },
+                    _ => Instruction::ANDI { rd, rs1, imm },
+                }
+            }
+            "slli" | "srli" | "srai" => {
+                let [rd, rs1, shamt] = self.operands(mnemonic, ops)?;
+                let (rd, rs1) = (self.register(rd)?, self.register(rs1)?);
+                let shamt = self.unsigned(shamt, 5)? as u8;
+                match mnemonic {
+                    "slli" => Instruction::SLLI { rd, rs1, shamt },
+                    "srli" => Instruction::SRLI { rd, rs1, shamt },
+                    _ => Instruction::SRAI { rd, rs1, shamt },
+                }
+            }
+            "add" | "sub" | "sll" | "slt" | "sltu" | "xor" | "srl" | "sra" | "or" | "and" => {
+                let [rd, rs1, rs2] = self.operands(mnemonic, ops)?;
+                let (rd, rs1, rs2) = (self.register(rd)?, self.register(rs1)?, self.register(rs2)?);
+                match mnemonic {
+                    "add" => Instruction::ADD { rd, rs1, rs2 },
+                    "sub" => Instruction::SUB { rd, rs1, rs2 },
+                    "sll" => Instruction::SLL { rd, rs1, rs2 },
+                    "slt" => Instruction::SLT { rd, rs1, rs2 },
+                    "sltu" => Instruction::SLTU { rd, rs1, rs2 },
+                    "xor" => Instruction::XOR { rd, rs1, rs2 },
+                    "srl" => Instruction::SRL { rd, rs1, rs2 },
+                    "sra" => Instruction::SRA { rd, rs1, rs2 },
+                    "or" => Instruction::OR { rd, rs1, rs2 },
+                    _ => Instruction::AND { rd, rs1, rs2 },
+                }
+            }
+            "mul" | "mulh" | "mulhsu" | "mulhu" | "div" | "divu" | "rem" | "remu" => {
+                let [rd, rs1, rs2] = self.operands(mnemonic, ops)?;
+                let (rd, rs1, rs2) = (self.register(rd)?, self.register(rs1)?, self.register(rs2)?);
+                match mnemonic {
+                    "mul" => Instruction::MUL { rd, rs1, rs2 },
+                    "mulh" => Instruction::MULH { rd, rs1, rs2 },
+                    "mulhsu" => Instruction::MULHSU { rd, rs1, rs2 },
+                    "mulhu" => Instruction::MULHU { rd, rs1, rs2 },
+                    "div" => Instruction::DIV { rd, rs1, rs2 },
+                    "divu" => Instruction::DIVU { rd, rs1, rs2 },
+                    "rem" => Instruction::REM { rd, rs1, rs2 },
+                    _ => Instruction::REMU { rd, rs1, rs2 },
+                }
+            }
+            "lb" | "lh" | "lw" | "lbu" | "lhu" => {
+                let [rd, address] = self.operands(mnemonic, ops)?;
+                let rd = self.register(rd)?;
+                let (offset, rs1) = self.memory(address)?;
+                match mnemonic {
+                    "lb" => Instruction::LB { rd, rs1, offset },
+                    "lh" => Instruction::LH { rd, rs1, offset },
+                    "lw" => Instruction::LW { rd, rs1, offset },
+                    "lbu" => Instruction::LBU { rd, rs1, offset },
+                    _ => Instruction::LHU { rd, rs1, offset },
+                }
+            }
+            "sb" | "sh" | "sw" => {
+                let [rs2, address] = self.operands(mnemonic, ops)?;
+                let rs2 = self.register(rs2)?;
+                let (offset, rs1) = self.memory(address)?;
+                match mnemonic {
+                    "sb" => Instruction::SB { rs1, rs2, offset },
+                    "sh" => Instruction::SH { rs1, rs2, offset },
+                    _ => Instruction::SW { rs1, rs2, offset },
+                }
+            }
+            "csrrw" | "csrrs" | "csrrc" => {
+                let [rd, csr, rs1] = self.operands(mnemonic, ops)?;
+                let (rd, csr, rs1) = (self.register(rd)?, self.csr(csr)?, self.register(rs1)?);
+                match mnemonic {
+                    "csrrw" => Instruction::CSRRW { rd, rs1, csr },
+                    "csrrs" => Instruction::CSRRS { rd, rs1, csr },
+                    _ => Instruction::CSRRC { rd, rs1, csr },
+                }
+            }
+            "csrrwi" | "csrrsi" | "csrrci" => {
+                let [rd, csr, imm] = self.operands(mnemonic, ops)?;
+                let (rd, csr) = (self.register(rd)?, self.csr(csr)?);
+                let imm = self.unsigned(imm, 5)? as u8;
+                match mnemonic {
+                    "csrrwi" => Instruction::CSRRWI { rd, csr, imm },
+                    "csrrsi" => Instruction::CSRRSI { rd, csr, imm },
+                    _ => Instruction::CSRRCI { rd, csr, imm },
+                }
+            }
+            "jal" => match ops {
+                [target] => Instruction::JAL {
+                    rd: Register::RA,
+                    offset: self.target(location, target, 21)?,
+                },
+                _ => {
+                    let [rd, target] = self.operands(mnemonic, ops)?;
+                    Instruction::JAL {
+                        rd: self.register(rd)?,
+                        offset: self.target(location, target, 21)?,
+                    }
+                }
+            },
+            "jalr" => match ops {
+                [rs1] => Instruction::JALR {
+                    rd: Register::RA,
+                    rs1: self.register(rs1)?,
+                    offset: 0,
+                },
+                [rd, address] => {
+                    let (offset, rs1) = self.memory(address)?;
+                    Instruction::JALR {
+                        rd: self.register(rd)?,
+                        rs1,
+                        offset,
+                    }
+                }
+                _ => {
+                    let [rd, rs1, offset] = self.operands(mnemonic, ops)?;
+                    Instruction::JALR {
+                        rd: self.register(rd)?,
+                        rs1: self.register(rs1)?,
+                        offset: self.signed(offset, 12)? as i16,
+                    }
+                }
+            },
+            "beq" | "bne" | "blt" | "bge" | "bltu" | "bgeu" | "bgt" | "ble" | "bgtu" | "bleu" => {
+                let [rs1, rs2, target] = self.operands(mnemonic, ops)?;
+                let (rs1, rs2) = (self.register(rs1)?, self.register(rs2)?);
+                let offset = self.target(location, target, 13)? as i16;
+                return Ok(match mnemonic {
+                    "beq" => vec![Instruction::BEQ { rs1, rs2, offset }],
+                    "bne" => vec![Instruction::BNE { rs1, rs2, offset }],
+                    "blt" => vec![Instruction::BLT { rs1, rs2, offset }],
+                    "bge" => vec![Instruction::BGE { rs1, rs2, offset }],
+                    "bltu" => vec![Instruction::BLTU { rs1, rs2, offset }],
+                    "bgeu" => vec![Instruction::BGEU { rs1, rs2, offset }],
+                    "bgt" => Instruction::BGT(rs1, rs2, offset).collect(),
+                    "ble" => Instruction::BLE(rs1, rs2, offset).collect(),
+                    "bgtu" => Instruction::BGTU(rs1, rs2, offset).collect(),
+                    _ => Instruction::BLEU(rs1, rs2, offset).collect(),
+                });
+            }
+            "beqz" | "bnez" | "blez" | "bgez" | "bltz" | "bgtz" => {
+                let [rs, target] = self.operands(mnemonic, ops)?;
+                let rs = self.register(rs)?;
+                let offset = self.target(location, target, 13)? as i16;
+                return Ok(match mnemonic {
+                    "beqz" => Instruction::BEQZ(rs, offset),
+                    "bnez" => Instruction::BNEZ(rs, offset),
+                    "blez" => Instruction::BLEZ(rs, offset),
+                    "bgez" => Instruction::BGEZ(rs, offset),
+                    "bltz" => Instruction::BLTZ(rs, offset),
+                    _ => Instruction::BGTZ(rs, offset),
+                }
+                .collect());
+            }
+            "sfence.vma" => match ops {
+                [] => Instruction::SFENCE_VMA {
+                    rs1: Register::ZERO,
+                    rs2: Register::ZERO,
+                },
+                _ => {
+                    let [rs1, rs2] = self.operands(mnemonic, ops)?;
+                    Instruction::SFENCE_VMA {
+                        rs1: self.register(rs1)?,
+                        rs2: self.register(rs2)?,
+                    }
+                }
+            },
+            "fence" => match ops {
+                [] => Instruction::FENCE {
+                    pred: 0b1111,
+                    succ: 0b1111,
+                },
+                _ => {
+                    let [pred, succ] = self.operands(mnemonic, ops)?;
+                    Instruction::FENCE {
+                        pred: self.fence_set(pred)?,
+                        succ: self.fence_set(succ)?,
+                    }
+                }
+            },
+            "fence.tso" | "fence.i" | "ecall" | "ebreak" | "mret" | "sret" | "wfi" => {
+                let [] = self.operands(mnemonic, ops)?;
+                match mnemonic {
+                    "fence.tso" => Instruction::FENCE_TSO,
+                    "fence.i" => Instruction::FENCE_I,
+                    "ecall" => Instruction::ECALL,
+                    "ebreak" => Instruction::EBREAK,
+                    "mret" => Instruction::MRET,
+                    "sret" => Instruction::SRET,
+                    _ => Instruction::WFI,
+                }
+            }
+            _ => return self.pseudoinstructions(location, mnemonic, ops),
+        };
+        Ok(vec![instruction])
+    }
+
+    /// Assemble a pseudoinstruction at `location`.
+    fn pseudoinstructions(
+        &self,
+        location: usize,
+        mnemonic: &str,
+        ops: &[&str],
+    ) -> Result<Vec<Instruction>, AssembleError> {
+        let instructions = match mnemonic {
+            "nop" => {
+                let [] = self.operands(mnemonic, ops)?;
+                Instruction::NOP
+            }
+            "ret" => {
+                let [] = self.operands(mnemonic, ops)?;
+                Instruction::RET
+            }
+            "li" => {
+                let [rd, imm] = self.operands(mnemonic, ops)?;
+                Instruction::LI(self.register(rd)?, self.li_immediate(imm)?)
+            }
+            "mv" | "not" | "neg" | "seqz" | "snez" | "sltz" | "sgtz" => {
+                let [rd, rs] = self.operands(mnemonic, ops)?;
+                let (rd, rs) = (self.register(rd)?, self.register(rs)?);
+                match mnemonic {
+                    "mv" => Instruction::MOV(rd, rs),
+                    "not" => Instruction::NOT(rd, rs),
+                    "neg" => Instruction::NEG(rd, rs),
+                    "seqz" => Instruction::SEQZ(rd, rs),
+                    "snez" => Instruction::SNEZ(rd, rs),
+                    "sltz" => Instruction::SLTZ(rd, rs),
+                    _ => Instruction::SGLZ(rd, rs),
+                }
+            }
+            "j" => {
+                let [target] = self.operands(mnemonic, ops)?;
+                Instruction::J(self.target(location, target, 21)?)
+            }
+            "jr" => {
+                let [rs] = self.operands(mnemonic, ops)?;
+                Instruction::JR(self.register(rs)?)
+            }
+            "la" => {
+                let [rd, symbol] = self.operands(mnemonic, ops)?;
+                let rd = self.register(rd)?;
+                let address =
+                    *self
+                        .symbols
+                        .get(symbol)
+                        .ok_or_else(|| AssembleError::UnknownLabel {
+                            span: self.span(symbol),
+                            label: symbol.to_string(),
+                        })?;
+                let (upper, lower) = split_offset(address as i64 - location as i64);
+                return Ok(vec![
+                    Instruction::AUIPC { rd, imm: upper },
+                    Instruction::ADDI {
+                        rd,
+                        rs1: rd,
+                        imm: lower,
+                    },
+                ]);
+            }
+            "call" | "tail" => {
+                let [target] = self.operands(mnemonic, ops)?;
+                let offset = self.target(location, target, 32)?;
+                let (upper, lower) = split_offset(offset.into());
+                let (link, rd) = match mnemonic {
+                    "call" => (Register::RA, Register::RA),
+                    _ => (Register::T1, Register::ZERO),
+                };
+                return Ok(vec![
+                    Instruction::AUIPC {
+                        rd: link,
+                        imm: upper,
+                    },
+                    Instruction::JALR {
+                        rd,
+                        rs1: link,
+                        offset: lower,
+                    },
+                ]);
+            }
+            "csrr" => {
+                let [rd, csr] = self.operands(mnemonic, ops)?;
+                Instruction::CSRR(self.register(rd)?, self.csr(csr)?)
+            }
+            "rdcycle" | "rdcycleh" | "rdtime" | "rdtimeh" | "rdinstret" | "rdinstreth" => {
+                let [rd] = self.operands(mnemonic, ops)?;
+                let rd = self.register(rd)?;
+                match mnemonic {
+                    "rdcycle" => Instruction::RDCYCLE(rd),
+                    "rdcycleh" => Instruction::RDCYCLEH(rd),
+                    "rdtime" => Instruction::RDTIME(rd),
+                    "rdtimeh" => Instruction::RDTIMEH(rd),
+                    "rdinstret" => Instruction::RDINSTRET(rd),
+                    _ => Instruction::RDINSTRETH(rd),
+                }
+            }
+            "csrw" | "csrs" | "csrc" => {
+                let [csr, rs] = self.operands(mnemonic, ops)?;
+                let (csr, rs) = (self.csr(csr)?, self.register(rs)?);
+                match mnemonic {
+                    "csrw" => Instruction::CSRW(rs, csr),
+                    "csrs" => Instruction::CSRS(rs, csr),
+                    _ => Instruction::CSRC(rs, csr),
+                }
+            }
+            "csrwi" | "csrsi" | "csrci" => {
+                let [csr, imm] = self.operands(mnemonic, ops)?;
+                let (csr, imm) = (self.csr(csr)?, self.unsigned(imm, 5)? as u8);
+                match mnemonic {
+                    "csrwi" => Instruction::CSRWI(csr, imm),
+                    "csrsi" => Instruction::CSRSI(csr, imm),
+                    _ => Instruction::CSRCI(csr, imm),
+                }
+            }
+            _ => {
+                return Err(AssembleError::UnknownInstruction {
+                    span: self.span(mnemonic),
+                    mnemonic: mnemonic.to_string(),
+                })
+            }
+        };
+        Ok(instructions.collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{csr::CSR32, processor::Processor, test::macros::instructions};
+    use pretty_assertions::assert_eq;
+
+    /// Encode the `instructions` as little endian bytes.
+    fn encode(instructions: Vec<Instruction>) -> Vec<u8> {
+        instructions
+            .into_iter()
+            .flat_map(|instruction| instruction.encode().to_le_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn assemble_instructions() {
+        let program = assemble(
+            "
+            start:  addi a0, zero, 21   # a0 = 21
+                    slli x11, a0, 1
+                    sub a2, a1, a0
+                    lw t0, -4(sp)
+                    sw t0, 8(sp)
+                    csrrw zero, mtvec, a0
+                    lui a3, 0xFFFFF
+                    mret
+            ",
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(
+            program.segments,
+            vec![Segment {
+                address: 0,
+                bytes: encode(vec![
+                    Instruction::ADDI {
+                        rd: Register::A0,
+                        rs1: Register::ZERO,
+                        imm: 21
+                    },
+                    Instruction::SLLI {
+                        rd: Register::A1,
+                        rs1: Register::A0,
+                        shamt: 1
+                    },
+                    Instruction::SUB {
+                        rd: Register::A2,
+                        rs1: Register::A1,
+                        rs2: Register::A0
+                    },
+                    Instruction::LW {
+                        rd: Register::T0,
+                        rs1: Register::SP,
+                        offset: -4
+                    },
+                    Instruction::SW {
+                        rs1: Register::SP,
+                        rs2: Register::T0,
+                        offset: 8
+                    },
+                    Instruction::CSRRW {
+                        rd: Register::ZERO,
+                        rs1: Register::A0,
+                        csr: csr::MTVEC
+                    },
+                    Instruction::LUI {
+                        rd: Register::A3,
+                        imm: 0xFFFFF
+                    },
+                    Instruction::MRET,
+                ]),
+            }]
+        );
+    }
+
+    #[test]
+    fn assemble_labels() {
+        let program = assemble(
+            "
+            loop:
+                beqz a0, end
+                addi a0, a0, -1
+                j loop
+            end: ret
+            ",
+            0x100,
+        )
+        .unwrap();
+
+        assert_eq!(
+            program.segments,
+            vec![Segment {
+                address: 0x100,
+                bytes: encode(instructions![
+                    Instruction::BEQZ(Register::A0, 12),
+                    [Instruction::ADDI {
+                        rd: Register::A0,
+                        rs1: Register::A0,
+                        imm: -1
+                    }],
+                    Instruction::J(-8),
+                    Instruction::RET,
+                ]),
+            }]
+        );
+    }
+
+    #[test]
+    fn assemble_pseudoinstructions() {
+        let program = assemble(
+            "
+                li a0, 0x12345678
+                li a1, -1
+                mv a2, a0
+                call function
+                nop
+            function:
+                ret
+            ",
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(
+            program.segments,
+            vec![Segment {
+                address: 0,
+                bytes: encode(instructions![
+                    Instruction::LI(Register::A0, 0x12345678),
+                    Instruction::LI(Register::A1, -1),
+                    Instruction::MOV(Register::A2, Register::A0),
+                    [
+                        Instruction::AUIPC {
+                            rd: Register::RA,
+                            imm: 0
+                        },
+                        Instruction::JALR {
+                            rd: Register::RA,
+                            rs1: Register::RA,
+                            offset: 12
+                        },
+                    ],
+                    Instruction::NOP,
+                    Instruction::RET,
+                ]),
+            }]
+        );
+    }
+
+    #[test]
+    fn assemble_counter_pseudoinstructions() {
+        let program = assemble(
+            "
+                rdcycle a0
+                rdcycleh a1
+                rdtime a2
+                rdtimeh a3
+                rdinstret a4
+                rdinstreth a5
+            ",
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(
+            program.segments,
+            vec![Segment {
+                address: 0,
+                bytes: encode(instructions![
+                    Instruction::RDCYCLE(Register::A0),
+                    Instruction::RDCYCLEH(Register::A1),
+                    Instruction::RDTIME(Register::A2),
+                    Instruction::RDTIMEH(Register::A3),
+                    Instruction::RDINSTRET(Register::A4),
+                    Instruction::RDINSTRETH(Register::A5),
+                ]),
+            }]
+        );
+    }
+
+    #[test]
+    fn assemble_data_directives() {
+        let program = assemble(
+            "
+                .word 1, 0xFFFFFFFF
+                .byte 1, 255, -1
+            .org 0x10
+                .word end
+            end:
+            ",
+            4,
+        )
+        .unwrap();
+
+        assert_eq!(
+            program.segments,
+            vec![
+                Segment {
+                    address: 4,
+                    bytes: vec![1, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 1, 0xFF, 0xFF],
+                },
+                Segment {
+                    address: 0x14,
+                    bytes: vec![0x18, 0, 0, 0],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn assemble_data_section() {
+        let program = assemble(
+            "
+            .data
+            msg: .byte 'h', 'i', 0
+            .text
+                la a0, msg
+            .data
+            .org 8
+            value: .word msg
+            ",
+            0x100,
+        )
+        .unwrap();
+
+        assert_eq!(
+            program.segments,
+            vec![
+                Segment {
+                    address: 0x108,
+                    bytes: vec![b'h', b'i', 0],
+                },
+                Segment {
+                    address: 0x100,
+                    bytes: encode(vec![
+                        Instruction::AUIPC {
+                            rd: Register::A0,
+                            imm: 0
+                        },
+                        Instruction::ADDI {
+                            rd: Register::A0,
+                            rs1: Register::A0,
+                            imm: 8
+                        },
+                    ]),
+                },
+                Segment {
+                    address: 0x110,
+                    bytes: vec![0x08, 0x01, 0, 0],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn load_data_with_la() {
+        let program = assemble(
+            "
+                la a0, msg
+                lbu a1, 0(a0)
+                lbu a2, 1(a0)
+                lbu a3, 2(a0)
+            .data
+            msg: .byte 'h', 'i', 0
+            ",
+            0x1000,
+        )
+        .unwrap();
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.load_program(&program);
+        processor.run_from::<Instruction>(0x1000);
+
+        assert_eq!(processor.registers[Register::A0], 0x1014);
+        assert_eq!(processor.registers[Register::A1], 'h' as i32);
+        assert_eq!(processor.registers[Register::A2], 'i' as i32);
+        assert_eq!(processor.registers[Register::A3], 0);
+    }
+
+    #[test]
+    fn parse_char_literals() {
+        let operands = "',', '\\'', 1";
+        assert_eq!(parse_number("'a'"), Some(97));
+        assert_eq!(parse_number("'#'"), Some(35));
+        assert_eq!(parse_number("'\\n'"), Some(10));
+        assert_eq!(parse_number("'\\0'"), Some(0));
+        assert_eq!(parse_number("'\\''"), Some(39));
+        assert_eq!(parse_number("'\\q'"), None);
+        assert_eq!(parse_number("'ab'"), None);
+        assert_eq!(
+            split_operands(operands, operands),
+            Ok(vec!["','", "'\\''", "1"])
+        );
+        assert_eq!(strip_comment("li a0, '#' # a comment"), "li a0, '#' ");
+    }
+
+    #[test]
+    fn split_offset_carries_into_upper() {
+        assert_eq!(split_offset(0x800), (1, -0x800));
+        assert_eq!(split_offset(0x7FF), (0, 0x7FF));
+        assert_eq!(split_offset(-4), (0, -4));
+        assert_eq!(split_offset(-0x801), (0xFFFFF, 0x7FF));
+    }
+
+    #[test]
+    fn assemble_errors() {
+        for (source, line, column, message) in [
+            ("a:\na: nop", 2, 1, "label 'a' is already defined"),
+            ("j nowhere", 1, 3, "unknown label 'nowhere'"),
+            (
+                "beq a0, a1, 3",
+                1,
+                13,
+                "target offset 3 is not a multiple of 2",
+            ),
+            ("  add a0, a1", 1, 3, "expected 3 operands, found 2"),
+            ("add a0, , a1", 1, 8, "empty operand"),
+            ("frobnicate a0", 1, 1, "unknown instruction 'frobnicate'"),
+            (".align 2", 1, 1, "unknown directive '.align'"),
+            (
+                "nop\n.org 0",
+                2,
+                6,
+                "'.org 0' moves the location counter backwards",
+            ),
+            (
+                "lw a0, 4[sp]",
+                1,
+                8,
+                "expected 'offset(register)', found '4[sp]'",
+            ),
+            ("csrr a0, foo", 1, 10, "unknown CSR 'foo'"),
+            ("li a0, label", 1, 8, "invalid immediate 'label'"),
+            (".byte 256", 1, 7, "immediate 256 out of range [-128, 255]"),
+            (
+                "fence rw, wr",
+                1,
+                11,
+                "expected a fence set of 'iorw', found 'wr'",
+            ),
+            (
+                "fence x, w",
+                1,
+                7,
+                "expected a fence set of 'iorw', found 'x'",
+            ),
+        ] {
+            let error = assemble(source, 0).unwrap_err();
+            assert_eq!(error.span(), Span { line, column });
+            assert_eq!(
+                error.to_string(),
+                format!("line {line}, column {column}: {message}")
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_register_span() {
+        assert_eq!(
+            assemble("nop\n  addi a0, a9, 1", 0),
+            Err(AssembleError::UnknownRegister {
+                span: Span {
+                    line: 2,
+                    column: 12
+                },
+                name: "a9".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn immediate_out_of_range_span() {
+        assert_eq!(
+            assemble("loop:\n\taddi a0, a0, 2048 # too big", 0),
+            Err(AssembleError::ImmediateOutOfRange {
+                span: Span {
+                    line: 2,
+                    column: 15
+                },
+                value: 2048,
+                min: -2048,
+                max: 2047,
+            })
+        );
+        assert_eq!(
+            assemble("sw a0, -2049(sp)", 0),
+            Err(AssembleError::ImmediateOutOfRange {
+                span: Span { line: 1, column: 8 },
+                value: -2049,
+                min: -2048,
+                max: 2047,
+            })
+        );
+    }
+}
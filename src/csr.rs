@@ -6,9 +6,135 @@
 //! the rest is OS - implementations specific.
 use std::sync::atomic::{AtomicI32, AtomicI64, Ordering::SeqCst};
 
+use crate::instruction_set::Exception;
+
+mod mstatus;
+
+pub use mstatus::MStatus;
+
 /// According to the RISC-V specification, the number of control status registers.
 const CSR_SIZE: usize = 4096;
 
+/// Machine status register.
+pub const MSTATUS: u16 = 0x300;
+/// Machine exception delegation register.
+pub const MEDELEG: u16 = 0x302;
+/// Machine interrupt delegation register.
+pub const MIDELEG: u16 = 0x303;
+/// Machine trap-handler base address.
+pub const MTVEC: u16 = 0x305;
+/// Machine exception programme counter.
+pub const MEPC: u16 = 0x341;
+/// Machine trap cause.
+pub const MCAUSE: u16 = 0x342;
+/// Machine bad address or instruction.
+pub const MTVAL: u16 = 0x343;
+
+/// Debug trigger select register, choosing the trigger accessed through
+/// [TDATA1] and [TDATA2].
+pub const TSELECT: u16 = 0x7A0;
+/// First debug trigger data register, the type and configuration of the
+/// selected trigger.
+pub const TDATA1: u16 = 0x7A1;
+/// Second debug trigger data register, the address matched by the selected
+/// trigger.
+pub const TDATA2: u16 = 0x7A2;
+
+/// Supervisor status register.
+///
+/// This is a restricted view of [MSTATUS] exposing only the supervisor fields.
+pub const SSTATUS: u16 = 0x100;
+/// Supervisor trap-handler base address.
+pub const STVEC: u16 = 0x105;
+/// Supervisor scratch register.
+pub const SSCRATCH: u16 = 0x140;
+/// Supervisor exception programme counter.
+pub const SEPC: u16 = 0x141;
+/// Supervisor trap cause.
+pub const SCAUSE: u16 = 0x142;
+/// Supervisor bad address or instruction.
+pub const STVAL: u16 = 0x143;
+/// Supervisor address translation and protection register.
+pub const SATP: u16 = 0x180;
+
+/// Cycle counter for the `RDCYCLE` instruction.
+pub const CYCLE: u16 = 0xC00;
+/// Timer for the `RDTIME` instruction.
+pub const TIME: u16 = 0xC01;
+/// Instructions-retired counter for the `RDINSTRET` instruction.
+pub const INSTRET: u16 = 0xC02;
+/// Upper 32 bits of [CYCLE], RV32 only.
+pub const CYCLEH: u16 = 0xC80;
+/// Upper 32 bits of [TIME], RV32 only.
+pub const TIMEH: u16 = 0xC81;
+/// Upper 32 bits of [INSTRET], RV32 only.
+pub const INSTRETH: u16 = 0xC82;
+
+/// The assembly names of the CSRs.
+const NAMES: [(&str, u16); 23] = [
+    ("mstatus", MSTATUS),
+    ("medeleg", MEDELEG),
+    ("mideleg", MIDELEG),
+    ("mtvec", MTVEC),
+    ("mepc", MEPC),
+    ("mcause", MCAUSE),
+    ("mtval", MTVAL),
+    ("tselect", TSELECT),
+    ("tdata1", TDATA1),
+    ("tdata2", TDATA2),
+    ("sstatus", SSTATUS),
+    ("stvec", STVEC),
+    ("sscratch", SSCRATCH),
+    ("sepc", SEPC),
+    ("scause", SCAUSE),
+    ("stval", STVAL),
+    ("satp", SATP),
+    ("cycle", CYCLE),
+    ("time", TIME),
+    ("instret", INSTRET),
+    ("cycleh", CYCLEH),
+    ("timeh", TIMEH),
+    ("instreth", INSTRETH),
+];
+
+/// The assembly name of the CSR at `address`, for example `"mstatus"`.
+pub fn name(address: u16) -> Option<&'static str> {
+    NAMES
+        .iter()
+        .find(|(_, csr)| *csr == address)
+        .map(|(name, _)| *name)
+}
+
+/// The address of the CSR with the assembly `name`, for example [MSTATUS]
+/// for `"mstatus"`.
+pub fn from_name(name: &str) -> Option<u16> {
+    NAMES
+        .iter()
+        .find(|(csr_name, _)| *csr_name == name)
+        .map(|(_, csr)| *csr)
+}
+
+/// The offset from a counter CSR, such as [TIME], to the CSR holding its
+/// upper 32 bits on RV32, such as [TIMEH].
+const COUNTER_HIGH_OFFSET: u16 = TIMEH - TIME;
+
+/// The bits of [MSTATUS] visible through [SSTATUS], see [MStatus].
+const SSTATUS_MASK: i32 = MStatus::<CSR32>::SIE | MStatus::<CSR32>::SPIE | MStatus::<CSR32>::SPP;
+
+/// Check that `index` is the address of a CSR, that is less than `CSR_SIZE`.
+///
+/// Raises [Exception::UnimplementedInstruction], the illegal instruction
+/// exception, otherwise. Since there is no instruction to report, the
+/// exception's value is zero.
+#[inline]
+pub const fn check_address(index: u16) -> Result<(), Exception> {
+    if (index as usize) < CSR_SIZE {
+        Ok(())
+    } else {
+        Err(Exception::UnimplementedInstruction(0))
+    }
+}
+
 /// The control status registers.
 pub trait ControlStatusRegisters {
     /// The type of the processor's registers.
@@ -35,6 +161,78 @@ pub trait ControlStatusRegisters {
     ///
     /// Panics if index is out of bounds (>`CSR_SIZE`)
     fn clear_bits(&self, index: u16, value: Self::Register) -> Self::Register;
+    /// Reads the 64-bit value of a counter CSR, such as [TIME].
+    ///
+    /// On RV32 the upper 32 bits are read from the corresponding high CSR,
+    /// such as [TIMEH].
+    fn read_counter(&self, index: u16) -> u64
+    where
+        Self::Register: Into<i64>,
+    {
+        self.read_counter64(index, index + COUNTER_HIGH_OFFSET)
+    }
+    /// Writes the 64-bit value of a counter CSR, such as [TIME].
+    ///
+    /// On RV32 the upper 32 bits are written to the corresponding high CSR,
+    /// such as [TIMEH].
+    fn write_counter(&self, index: u16, value: u64)
+    where
+        Self::Register: TryFrom<i64>,
+    {
+        // The values written always fit the register, so the conversions
+        // cannot fail.
+        if size_of::<Self::Register>() >= size_of::<u64>() {
+            if let Ok(value) = Self::Register::try_from(value as i64) {
+                self.read_write(index, value);
+            }
+            return;
+        }
+        for (index, value) in [(index, value), (index + COUNTER_HIGH_OFFSET, value >> 32)] {
+            if let Ok(value) = Self::Register::try_from(value as u32 as i32 as i64) {
+                self.read_write(index, value);
+            }
+        }
+    }
+    /// Reads a consistent snapshot of the 64-bit counter held in the `low`
+    /// and `high` CSRs, such as [TIME] and [TIMEH].
+    ///
+    /// On RV32 the high CSR is read before and after the low CSR, retrying
+    /// if it changed, so a carry between the reads does not tear the value.
+    /// On wider targets only the `low` CSR is read.
+    fn read_counter64(&self, low: u16, high: u16) -> u64
+    where
+        Self::Register: Into<i64>,
+    {
+        if size_of::<Self::Register>() >= size_of::<u64>() {
+            return self.read(low).into() as u64;
+        }
+        // Re-read the upper half until it is unchanged, so a carry into it
+        // between the reads cannot tear the value.
+        loop {
+            let upper: i64 = self.read(high).into();
+            let lower: i64 = self.read(low).into();
+            if self.read(high).into() == upper {
+                return ((upper as u64) << 32) | (lower as u64 & u32::MAX as u64);
+            }
+        }
+    }
+    /// Reads the value of the CSR, or raises the illegal instruction
+    /// exception if `index` is out of bounds, see [check_address].
+    fn try_read(&self, index: u16) -> Result<Self::Register, Exception> {
+        check_address(index)?;
+        Ok(self.read(index))
+    }
+    /// Swaps the value for the value at `index`, or raises the illegal
+    /// instruction exception if `index` is out of bounds, see
+    /// [check_address].
+    fn try_read_write(
+        &self,
+        index: u16,
+        value: Self::Register,
+    ) -> Result<Self::Register, Exception> {
+        check_address(index)?;
+        Ok(self.read_write(index, value))
+    }
 }
 
 /// The 32-bit control status registers.
@@ -87,9 +285,9 @@ pub struct CSR64 {
 /// `ControlStatusRegisters` for the given struct.
 ///
 /// The struct must contain a single field called `registers` containing a
-/// boxed slice of some `Atomic` integer.
+/// boxed slice of the given `Atomic` integer type.
 macro_rules! implement_csr {
-    ($struct_name: ty, $register_type:ty) => {
+    ($struct_name: ty, $register_type:ty, $atomic_type:ty) => {
         impl $struct_name {
             fn new() -> Self {
                 let mut registers = Vec::default();
@@ -98,12 +296,40 @@ macro_rules! implement_csr {
                     registers: registers.into_boxed_slice(),
                 }
             }
+
+            /// Create the CSRs with the initial `values`, each a CSR index
+            /// and the value written to it, in order.
+            ///
+            /// Like writes by an instruction, a value written to a restricted
+            /// view such as [SSTATUS] only sets the visible bits.
+            ///
+            /// # Panics
+            ///
+            /// If an index is not a CSR address, see [check_address].
+            pub fn from_values(values: impl IntoIterator<Item = (u16, $register_type)>) -> Self {
+                let csrs = Self::new();
+                for (index, value) in values {
+                    csrs.read_write(index, value);
+                }
+                csrs
+            }
         }
         impl Default for $struct_name {
             fn default() -> Self {
                 Self::new()
             }
         }
+        impl Clone for $struct_name {
+            fn clone(&self) -> Self {
+                Self {
+                    registers: self
+                        .registers
+                        .iter()
+                        .map(|register| <$atomic_type>::new(register.load(SeqCst)))
+                        .collect(),
+                }
+            }
+        }
         impl core::fmt::Debug for $struct_name {
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                 f.write_fmt(format_args!(
@@ -120,23 +346,50 @@ macro_rules! implement_csr {
                 ))
             }
         }
+        impl $struct_name {
+            /// The register backing the CSR at `index` and the mask of the
+            /// bits visible through it.
+            ///
+            /// Most CSRs are backed by their own register, however some, such
+            /// as [SSTATUS], are restricted views of another. A single trigger
+            /// is implemented, so [TSELECT] has no writable bits and always
+            /// selects trigger 0.
+            #[inline]
+            fn backing_register(&self, index: u16) -> (&$atomic_type, $register_type) {
+                match index {
+                    SSTATUS => (
+                        &self.registers[MSTATUS as usize],
+                        SSTATUS_MASK as $register_type,
+                    ),
+                    TSELECT => (&self.registers[TSELECT as usize], 0),
+                    _ => (&self.registers[index as usize], -1),
+                }
+            }
+        }
         impl ControlStatusRegisters for $struct_name {
             type Register = $register_type;
 
             fn read(&self, index: u16) -> Self::Register {
-                self.registers[index as usize].load(SeqCst)
+                let (register, mask) = self.backing_register(index);
+                register.load(SeqCst) & mask
             }
 
             fn read_write(&self, index: u16, value: Self::Register) -> Self::Register {
-                self.registers[index as usize].swap(value, SeqCst)
+                let (register, mask) = self.backing_register(index);
+                let previous = register
+                    .fetch_update(SeqCst, SeqCst, |old| Some((old & !mask) | (value & mask)))
+                    .unwrap_or_else(|old| old);
+                previous & mask
             }
 
             fn set_bits(&self, index: u16, value: Self::Register) -> Self::Register {
-                self.registers[index as usize].fetch_or(value, SeqCst)
+                let (register, mask) = self.backing_register(index);
+                register.fetch_or(value & mask, SeqCst) & mask
             }
 
             fn clear_bits(&self, index: u16, value: Self::Register) -> Self::Register {
-                self.registers[index as usize].fetch_and(!value, SeqCst)
+                let (register, mask) = self.backing_register(index);
+                register.fetch_and(!(value & mask), SeqCst) & mask
             }
         }
     };
@@ -154,8 +407,8 @@ impl PartialEq for CSR32 {
     }
 }
 
-implement_csr!(CSR32, i32);
-implement_csr!(CSR64, i64);
+implement_csr!(CSR32, i32, AtomicI32);
+implement_csr!(CSR64, i64, AtomicI64);
 
 #[cfg(test)]
 mod test {
@@ -184,6 +437,18 @@ mod test {
         );
     }
 
+    #[test]
+    fn from_values() {
+        let csr_32 = CSR32::from_values([(MTVEC, 0x100), (SSCRATCH, -1)]);
+        assert_eq!(csr_32.read(MTVEC), 0x100);
+        assert_eq!(csr_32.read(SSCRATCH), -1);
+        assert_eq!(csr_32.read(MEPC), 0);
+
+        let csr_64 = CSR64::from_values([(MTVEC, 0x100), (SSCRATCH, i64::MIN)]);
+        assert_eq!(csr_64.read(MTVEC), 0x100);
+        assert_eq!(csr_64.read(SSCRATCH), i64::MIN);
+    }
+
     #[test]
     fn initial_value_zero() {
         let csr_32 = CSR32::default();
@@ -246,4 +511,76 @@ mod test {
         assert_eq!(csr_64.read_write(42, 100), 0);
         assert_eq!(csr_64.read_write(42, 50), 100);
     }
+
+    #[test]
+    fn counters() {
+        let csr_32 = CSR32::default();
+        csr_32.write_counter(TIME, 0x1_8000_0000);
+        assert_eq!(csr_32.read(TIME), i32::MIN);
+        assert_eq!(csr_32.read(TIMEH), 1);
+        assert_eq!(csr_32.read_counter(TIME), 0x1_8000_0000);
+
+        let csr_64 = CSR64::default();
+        csr_64.write_counter(TIME, 0x1_8000_0000);
+        assert_eq!(csr_64.read(TIME), 0x1_8000_0000);
+        assert_eq!(csr_64.read(TIMEH), 0);
+        assert_eq!(csr_64.read_counter(TIME), 0x1_8000_0000);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(name(MSTATUS), Some("mstatus"));
+        assert_eq!(name(0x7c0), None);
+        for (csr_name, csr) in NAMES {
+            assert_eq!(from_name(csr_name), Some(csr));
+        }
+        assert_eq!(from_name("mscratch"), None);
+    }
+
+    #[test]
+    fn out_of_range_address() {
+        let csr_32 = CSR32::default();
+        assert_eq!(csr_32.try_read_write(0xfff, 7), Ok(0));
+        assert_eq!(csr_32.try_read(0xfff), Ok(7));
+        assert_eq!(
+            csr_32.try_read(0x1000),
+            Err(Exception::UnimplementedInstruction(0))
+        );
+        assert_eq!(
+            csr_32.try_read_write(u16::MAX, 7),
+            Err(Exception::UnimplementedInstruction(0))
+        );
+    }
+
+    #[test]
+    fn read_counter64() {
+        let csr_32 = CSR32::default();
+        csr_32.read_write(TIME, -1);
+        csr_32.read_write(TIMEH, 0x1234);
+        assert_eq!(csr_32.read_counter64(TIME, TIMEH), 0x1234_ffff_ffff);
+        assert_eq!(
+            csr_32.read_counter64(TIME, TIMEH),
+            csr_32.read_counter(TIME)
+        );
+
+        let csr_64 = CSR64::default();
+        csr_64.read_write(TIME, 0x1234_ffff_ffff);
+        csr_64.read_write(TIMEH, 7);
+        assert_eq!(csr_64.read_counter64(TIME, TIMEH), 0x1234_ffff_ffff);
+    }
+
+    #[test]
+    fn sstatus_is_a_view_of_mstatus() {
+        let csr_32 = CSR32::default();
+        csr_32.read_write(MSTATUS, -1);
+        assert_eq!(csr_32.read(SSTATUS), SSTATUS_MASK);
+
+        assert_eq!(csr_32.read_write(SSTATUS, 0), SSTATUS_MASK);
+        assert_eq!(csr_32.read(MSTATUS), !SSTATUS_MASK);
+
+        csr_32.set_bits(SSTATUS, -1);
+        assert_eq!(csr_32.read(MSTATUS), -1);
+        csr_32.clear_bits(SSTATUS, MStatus::<CSR32>::SPP);
+        assert_eq!(csr_32.read(MSTATUS), !MStatus::<CSR32>::SPP);
+    }
 }
@@ -0,0 +1,195 @@
+//! Typed access to the fields of the `mstatus` control status register.
+use std::ops::BitAnd;
+
+use super::{ControlStatusRegisters, MSTATUS};
+
+/// A view of the `mstatus` register in some control status registers.
+///
+/// The machine status register keeps track of and controls the hart's current
+/// operating state. This provides access to its individual fields, reading and
+/// writing the backing CSR with the correct bit masks so other fields are left
+/// undisturbed.
+///
+/// | Bit(s) | Field  | Description                                  |
+/// | ------ | ------ | -------------------------------------------- |
+/// | 1      | `SIE`  | Supervisor interrupt enable                  |
+/// | 3      | `MIE`  | Machine interrupt enable                     |
+/// | 5      | `SPIE` | Supervisor interrupt enable prior to a trap  |
+/// | 7      | `MPIE` | Machine interrupt enable prior to a trap     |
+/// | 8      | `SPP`  | Supervisor previous privilege mode           |
+/// | 11-12  | `MPP`  | Machine previous privilege mode              |
+/// | 22     | `TSR`  | Trap `SRET` in supervisor mode               |
+pub struct MStatus<'a, C> {
+    /// The control status registers containing `mstatus`.
+    csrs: &'a C,
+}
+
+impl<'a, C> MStatus<'a, C>
+where
+    C: ControlStatusRegisters,
+    C::Register: From<i32> + BitAnd<Output = C::Register> + PartialEq + Copy,
+{
+    /// The supervisor interrupt enable bit.
+    pub const SIE: i32 = 1 << 1;
+    /// The machine interrupt enable bit.
+    pub const MIE: i32 = 1 << 3;
+    /// The supervisor interrupt enable bit prior to a trap.
+    pub const SPIE: i32 = 1 << 5;
+    /// The machine interrupt enable bit prior to a trap.
+    pub const MPIE: i32 = 1 << 7;
+    /// The supervisor previous privilege mode bit.
+    pub const SPP: i32 = 1 << 8;
+    /// The machine previous privilege mode bits.
+    pub const MPP: i32 = 0b11 << Self::MPP_SHIFT;
+    /// The trap `SRET` bit.
+    pub const TSR: i32 = 1 << 22;
+    /// The position of the least significant bit of the `MPP` field.
+    const MPP_SHIFT: i32 = 11;
+
+    /// Create a view of the `mstatus` register in `csrs`.
+    pub fn new(csrs: &'a C) -> Self {
+        Self { csrs }
+    }
+
+    /// Returns `true` if all the bits in `mask` are set.
+    #[inline]
+    fn get(&self, mask: i32) -> bool {
+        let mask = C::Register::from(mask);
+        self.csrs.read(MSTATUS) & mask == mask
+    }
+
+    /// Sets or clears the bits in `mask`.
+    #[inline]
+    fn set(&self, mask: i32, value: bool) {
+        if value {
+            self.csrs.set_bits(MSTATUS, mask.into());
+        } else {
+            self.csrs.clear_bits(MSTATUS, mask.into());
+        }
+    }
+
+    /// Supervisor interrupt enable.
+    pub fn sie(&self) -> bool {
+        self.get(Self::SIE)
+    }
+
+    /// Set supervisor interrupt enable.
+    pub fn set_sie(&self, value: bool) {
+        self.set(Self::SIE, value)
+    }
+
+    /// Machine interrupt enable.
+    pub fn mie(&self) -> bool {
+        self.get(Self::MIE)
+    }
+
+    /// Set machine interrupt enable.
+    pub fn set_mie(&self, value: bool) {
+        self.set(Self::MIE, value)
+    }
+
+    /// Supervisor interrupt enable prior to the current trap.
+    pub fn spie(&self) -> bool {
+        self.get(Self::SPIE)
+    }
+
+    /// Set supervisor interrupt enable prior to the current trap.
+    pub fn set_spie(&self, value: bool) {
+        self.set(Self::SPIE, value)
+    }
+
+    /// Machine interrupt enable prior to the current trap.
+    pub fn mpie(&self) -> bool {
+        self.get(Self::MPIE)
+    }
+
+    /// Set machine interrupt enable prior to the current trap.
+    pub fn set_mpie(&self, value: bool) {
+        self.set(Self::MPIE, value)
+    }
+
+    /// Supervisor previous privilege mode, `0` for user mode and `1` for
+    /// supervisor mode.
+    pub fn spp(&self) -> u8 {
+        self.get(Self::SPP).into()
+    }
+
+    /// Set supervisor previous privilege mode.
+    ///
+    /// Only the least significant bit of `value` is used.
+    pub fn set_spp(&self, value: u8) {
+        self.set(Self::SPP, value & 1 != 0)
+    }
+
+    /// Trap `SRET`, when set executing `SRET` in supervisor mode raises an
+    /// illegal instruction exception.
+    pub fn tsr(&self) -> bool {
+        self.get(Self::TSR)
+    }
+
+    /// Set trap `SRET`.
+    pub fn set_tsr(&self, value: bool) {
+        self.set(Self::TSR, value)
+    }
+
+    /// Machine previous privilege mode, `0` for user mode, `1` for supervisor
+    /// mode, and `3` for machine mode.
+    pub fn mpp(&self) -> u8 {
+        let low = self.get(1 << Self::MPP_SHIFT) as u8;
+        let high = self.get(1 << (Self::MPP_SHIFT + 1)) as u8;
+        (high << 1) | low
+    }
+
+    /// Set machine previous privilege mode.
+    ///
+    /// Only the two least significant bits of `value` are used.
+    pub fn set_mpp(&self, value: u8) {
+        self.set(1 << Self::MPP_SHIFT, value & 0b01 != 0);
+        self.set(1 << (Self::MPP_SHIFT + 1), value & 0b10 != 0);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::csr::{CSR32, CSR64};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn set_and_read_mie() {
+        let csrs = CSR32::default();
+        let mstatus = MStatus::new(&csrs);
+        assert!(!mstatus.mie());
+
+        mstatus.set_mie(true);
+        assert!(mstatus.mie());
+        assert_eq!(csrs.read(MSTATUS), 0b1000);
+
+        mstatus.set_mie(false);
+        assert!(!mstatus.mie());
+        assert_eq!(csrs.read(MSTATUS), 0);
+    }
+
+    #[test]
+    fn fields_do_not_disturb_each_other() {
+        let csrs = CSR64::default();
+        csrs.read_write(MSTATUS, 0b11 << 11 | 1 << 7 | 1 << 1);
+        let mstatus = MStatus::new(&csrs);
+
+        mstatus.set_mie(true);
+        assert!(mstatus.mie());
+        assert!(mstatus.mpie());
+        assert!(mstatus.sie());
+        assert!(!mstatus.spie());
+        assert_eq!(mstatus.mpp(), 3);
+
+        mstatus.set_mpp(1);
+        mstatus.set_spp(1);
+        assert_eq!(mstatus.mpp(), 1);
+        assert_eq!(mstatus.spp(), 1);
+        assert_eq!(
+            csrs.read(MSTATUS),
+            0b01 << 11 | 1 << 8 | 1 << 7 | 1 << 3 | 1 << 1
+        );
+    }
+}
@@ -0,0 +1,347 @@
+//! Memory-mapped devices.
+//!
+//! Each device wraps another [MemoryBus], usually [Memory], intercepting the
+//! accesses to its mapped address and forwarding every other access to the
+//! wrapped memory.
+use std::io::Write;
+
+use crate::instruction_set::Exception;
+use crate::memory::{Memory, MemoryBus};
+
+/// Implements [MemoryBus] for a device wrapping the `memory` field, calling
+/// the device's `on_store` method with each value stored to its `address`
+/// and forwarding every other access to the wrapped memory.
+macro_rules! memory_mapped_device {
+    ($device:ident, $on_store:ident) => {
+        impl<M: MemoryBus> MemoryBus for $device<M> {
+            fn load_byte_unsigned(&mut self, location: usize) -> u8 {
+                self.memory.load_byte_unsigned(location)
+            }
+
+            fn store_byte(&mut self, location: usize, value: i8) -> Result<(), Exception> {
+                if location == self.address {
+                    return self.$on_store(value.into());
+                }
+                self.memory.store_byte(location, value)
+            }
+
+            fn peek_word(&self, location: usize) -> u32 {
+                self.memory.peek_word(location)
+            }
+
+            fn write(&mut self, location: usize, bytes: &[u8]) {
+                self.memory.write(location, bytes)
+            }
+
+            fn load_byte(&mut self, location: usize) -> i8 {
+                self.memory.load_byte(location)
+            }
+
+            fn load_half(&mut self, location: usize) -> i16 {
+                self.memory.load_half(location)
+            }
+
+            fn load_word(&mut self, location: usize) -> i32 {
+                self.memory.load_word(location)
+            }
+
+            fn load_double(&mut self, location: usize) -> i64 {
+                self.memory.load_double(location)
+            }
+
+            fn load_half_unsigned(&mut self, location: usize) -> u16 {
+                self.memory.load_half_unsigned(location)
+            }
+
+            fn load_word_unsigned(&mut self, location: usize) -> u32 {
+                self.memory.load_word_unsigned(location)
+            }
+
+            fn store_half(&mut self, location: usize, value: i16) -> Result<(), Exception> {
+                if location == self.address {
+                    return self.$on_store(value.into());
+                }
+                self.memory.store_half(location, value)
+            }
+
+            fn store_word(&mut self, location: usize, value: i32) -> Result<(), Exception> {
+                if location == self.address {
+                    return self.$on_store(value.into());
+                }
+                self.memory.store_word(location, value)
+            }
+
+            fn store_double(&mut self, location: usize, value: i64) -> Result<(), Exception> {
+                if location == self.address {
+                    return self.$on_store(value);
+                }
+                self.memory.store_double(location, value)
+            }
+
+            fn fetch_parcel(&mut self, location: usize) -> Result<u16, Exception> {
+                self.memory.fetch_parcel(location)
+            }
+
+            fn fetch_instruction(&mut self, location: usize) -> Result<u32, Exception> {
+                self.memory.fetch_instruction(location)
+            }
+
+            fn check_readable(&self, location: usize, width: usize) -> Result<(), Exception> {
+                self.memory.check_readable(location, width)
+            }
+
+            fn reserve(&mut self, end: usize) {
+                self.memory.reserve(end)
+            }
+
+            fn take_exit_request(&mut self) -> Option<i32> {
+                self.exit_request()
+            }
+        }
+    };
+}
+
+/// A UART-style character output device.
+///
+/// The lowest byte of each store to the mapped address is appended to the
+/// output, rather than being stored in memory, which is the classic way for a
+/// test programme to print. Loads from the mapped address are forwarded to
+/// the wrapped memory.
+///
+/// # Example
+///
+/// ```
+/// use riskv::{csr::CSR32, devices::ConsoleDevice, memory::Memory, processor::Processor};
+/// use riskv::{instructions, instructions::Instruction, registers::Register};
+///
+/// let mut processor = Processor::<i32, CSR32, _>::from_parts(
+///     Default::default(),
+///     0,
+///     CSR32::default(),
+///     ConsoleDevice::new(Memory::default(), 0x1000),
+/// );
+/// processor.store_instructions(
+///     0,
+///     instructions![
+///         Instruction::LI(Register::T0, 0x1000),
+///         Instruction::LI(Register::A0, 'h' as i32),
+///         [Instruction::SB { rs1: Register::T0, rs2: Register::A0, offset: 0 }],
+///         Instruction::LI(Register::A0, 'i' as i32),
+///         [Instruction::SB { rs1: Register::T0, rs2: Register::A0, offset: 0 }],
+///     ],
+/// );
+/// processor.run::<Instruction>();
+///
+/// assert_eq!(processor.memory().output(), "hi");
+/// ```
+pub struct ConsoleDevice<M = Memory> {
+    /// The wrapped memory.
+    memory: M,
+    /// The address the device is mapped to.
+    address: usize,
+    /// The bytes written to the device.
+    output: Vec<u8>,
+    /// Where the bytes written to the device are echoed, if anywhere.
+    writer: Option<Box<dyn Write + Send>>,
+}
+
+impl<M> std::fmt::Debug for ConsoleDevice<M>
+where
+    M: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConsoleDevice")
+            .field("memory", &self.memory)
+            .field("address", &self.address)
+            .field("output", &String::from_utf8_lossy(&self.output))
+            .finish_non_exhaustive()
+    }
+}
+
+impl<M: MemoryBus> ConsoleDevice<M> {
+    /// Map a console device to the `address`, forwarding all other accesses
+    /// to the `memory`.
+    pub fn new(memory: M, address: usize) -> Self {
+        Self {
+            memory,
+            address,
+            output: Vec::new(),
+            writer: None,
+        }
+    }
+
+    /// Echo each byte written to the device to the `writer`, for example
+    /// [std::io::stdout], flushing it after each newline.
+    ///
+    /// Errors writing to the `writer` are ignored, the bytes are still
+    /// captured in the [ConsoleDevice::output].
+    pub fn with_writer(mut self, writer: impl Write + Send + 'static) -> Self {
+        self.writer = Some(Box::new(writer));
+        self
+    }
+
+    /// The text written to the device, up to the first byte which is not
+    /// valid UTF-8, see [ConsoleDevice::bytes].
+    pub fn output(&self) -> &str {
+        match std::str::from_utf8(&self.output) {
+            Ok(output) => output,
+            Err(error) => {
+                std::str::from_utf8(&self.output[..error.valid_up_to()]).unwrap_or_default()
+            }
+        }
+    }
+
+    /// The raw bytes written to the device.
+    pub fn bytes(&self) -> &[u8] {
+        &self.output
+    }
+
+    /// The wrapped memory.
+    pub fn memory(&self) -> &M {
+        &self.memory
+    }
+
+    /// Capture the lowest byte of the `value` stored to the device.
+    fn put(&mut self, value: i64) -> Result<(), Exception> {
+        let byte = value as u8;
+        self.output.push(byte);
+        if let Some(writer) = &mut self.writer {
+            let _ = writer.write_all(&[byte]);
+            if byte == b'\n' {
+                let _ = writer.flush();
+            }
+        }
+        Ok(())
+    }
+
+    /// The console never requests an exit, however the wrapped memory may.
+    fn exit_request(&mut self) -> Option<i32> {
+        self.memory.take_exit_request()
+    }
+}
+
+memory_mapped_device!(ConsoleDevice, put);
+
+/// A `tohost` device, the termination convention of the RISC-V unit tests,
+/// `riscv-tests`.
+///
+/// A test stores to its `tohost` symbol to finish, an odd value requesting
+/// the exit with the status code `value >> 1`, so `1` indicates the test
+/// passed. The processor stops with [ExecutionResult::Exited] after the
+/// store, see [MemoryBus::take_exit_request].
+///
+/// Stores of even values, which request system calls of a proxy kernel, and
+/// the `fromhost` channel are not supported, such stores are discarded.
+///
+/// [ExecutionResult::Exited]: crate::processor::ExecutionResult::Exited
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToHostDevice<M = Memory> {
+    /// The wrapped memory.
+    memory: M,
+    /// The address of the `tohost` symbol the device is mapped to.
+    address: usize,
+    /// The exit status requested by the last store, if not yet taken.
+    exit: Option<i32>,
+}
+
+impl<M: MemoryBus> ToHostDevice<M> {
+    /// Map a `tohost` device to the `address`, forwarding all other accesses
+    /// to the `memory`.
+    pub fn new(memory: M, address: usize) -> Self {
+        Self {
+            memory,
+            address,
+            exit: None,
+        }
+    }
+
+    /// The wrapped memory.
+    pub fn memory(&self) -> &M {
+        &self.memory
+    }
+
+    /// Record an exit request if the `value` stored to the device is odd.
+    fn record(&mut self, value: i64) -> Result<(), Exception> {
+        if value & 1 == 1 {
+            self.exit = Some((value >> 1) as i32);
+        }
+        Ok(())
+    }
+
+    /// Take the exit status of the last store, if any, otherwise that of the
+    /// wrapped memory.
+    fn exit_request(&mut self) -> Option<i32> {
+        self.exit.take().or_else(|| self.memory.take_exit_request())
+    }
+}
+
+memory_mapped_device!(ToHostDevice, record);
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// A writer whose bytes can be inspected after it is moved into a device.
+    #[derive(Default, Clone)]
+    struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn console_output() {
+        let writer = SharedWriter::default();
+        let mut console = ConsoleDevice::new(Memory::default(), 0x100).with_writer(writer.clone());
+
+        for byte in "hi".bytes() {
+            console.store_byte(0x100, byte as i8).unwrap();
+        }
+        // Only the lowest byte of a wider store is output.
+        console.store_word(0x100, 0x4321_0a21).unwrap();
+        // Stores elsewhere are forwarded to the memory.
+        console.store_word(0x104, 42).unwrap();
+
+        assert_eq!(console.output(), "hi!");
+        assert_eq!(writer.0.lock().unwrap().as_slice(), b"hi!");
+        assert_eq!(console.load_word(0x100), 0);
+        assert_eq!(console.load_word(0x104), 42);
+
+        // An incomplete UTF-8 character is not part of the output.
+        console.store_byte(0x100, 0xe2_u8 as i8).unwrap();
+        assert_eq!(console.output(), "hi!");
+        assert_eq!(console.bytes(), b"hi!\xe2");
+
+        // A console with a writer can be moved to another thread.
+        let console = std::thread::spawn(move || console).join().unwrap();
+        assert_eq!(console.output(), "hi!");
+    }
+
+    #[test]
+    fn tohost_exit_request() {
+        let mut tohost = ToHostDevice::new(Memory::default(), 0x200);
+        assert_eq!(tohost.take_exit_request(), None);
+
+        // Even values are not exit requests.
+        tohost.store_word(0x200, 2).unwrap();
+        assert_eq!(tohost.take_exit_request(), None);
+
+        tohost.store_word(0x200, 7).unwrap();
+        assert_eq!(tohost.take_exit_request(), Some(3));
+        assert_eq!(tohost.take_exit_request(), None);
+
+        tohost.store_word(0x204, 1).unwrap();
+        assert_eq!(tohost.take_exit_request(), None);
+        assert_eq!(tohost.load_word(0x204), 1);
+    }
+}
@@ -12,7 +12,13 @@
 //! implementations and extensions, this trait can be implemented.
 use std::fmt::Display;
 
-use crate::{csr::ControlStatusRegisters, processor::Processor};
+use crate::{
+    csr::{ControlStatusRegisters, CSR32},
+    isa::{Extension, Isa},
+    memory::MemoryBus,
+    privilege::Privilege,
+    processor::Processor,
+};
 
 /// A processor exception
 ///
@@ -29,22 +35,134 @@ pub enum Exception {
     /// machines that support extensions with `16`-bit aligned instructions,
     /// such as the compressed instruction set extension, C.
     MisalignedInstructionFetch,
+
+    /// Load address misaligned exception.
+    ///
+    /// Raised when a load is not naturally aligned and the processor traps
+    /// unaligned accesses, see [crate::processor::UnalignedPolicy]. Holds the
+    /// effective address of the load.
+    LoadAddressMisaligned(u32),
+
+    /// Store address misaligned exception.
+    ///
+    /// Raised when a store is not naturally aligned and the processor traps
+    /// unaligned accesses, see [crate::processor::UnalignedPolicy]. Holds the
+    /// effective address of the store.
+    StoreAddressMisaligned(u32),
+
+    /// Store access fault exception.
+    ///
+    /// Raised when a store targets a region of memory which is not writable,
+    /// see [crate::memory::Protection].
+    StoreAccessFault,
+
+    /// Breakpoint exception.
+    ///
+    /// Raised by `EBREAK`, see [crate::processor::EbreakPolicy].
+    Breakpoint,
+
+    /// Environment call exception.
+    ///
+    /// Raised by `ECALL`. Holds the privilege level the call was made from,
+    /// which determines the exception code.
+    EnvironmentCall(Privilege),
+
+    /// Instruction access fault exception.
+    ///
+    /// Raised when an instruction is fetched from a region of memory which is
+    /// not executable, see [crate::memory::Protection].
+    InstructionAccessFault,
+
+    /// Memory access fault exception.
+    ///
+    /// Raised when a load reads an address which is outside of the memory,
+    /// see [crate::memory::MemoryBus::check_readable], or a region of memory
+    /// which is not readable, see [crate::memory::Protection].
+    MemoryAccessFault,
+
+    /// Load page fault exception.
+    ///
+    /// Raised when a load address cannot be translated, see [crate::mmu].
+    LoadPageFault,
+
+    /// Store page fault exception.
+    ///
+    /// Raised when a store address cannot be translated, see [crate::mmu].
+    StorePageFault,
+
+    /// Instruction page fault exception.
+    ///
+    /// Raised when an instruction fetch address cannot be translated, see
+    /// [crate::mmu].
+    InstructionPageFault,
 }
 
-impl Display for Exception {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Exception {
+    /// The exception code written to the `mcause` or `scause` CSR when this
+    /// exception is trapped.
+    ///
+    /// _Note_: [Exception::MemoryAccessFault] is the load access fault, a
+    /// store raises [Exception::StoreAccessFault].
+    pub const fn code(&self) -> u32 {
+        match self {
+            Self::MisalignedInstructionFetch => 0,
+            Self::InstructionAccessFault => 1,
+            Self::UnimplementedInstruction(_) => 2,
+            Self::Breakpoint => 3,
+            Self::LoadAddressMisaligned(_) => 4,
+            Self::MemoryAccessFault => 5,
+            Self::StoreAddressMisaligned(_) => 6,
+            Self::StoreAccessFault => 7,
+            Self::EnvironmentCall(privilege) => 8 + *privilege as u32,
+            Self::InstructionPageFault => 12,
+            Self::LoadPageFault => 13,
+            Self::StorePageFault => 15,
+        }
+    }
+
+    /// The exception specific value written to the `mtval` or `stval` CSR
+    /// when this exception is trapped, for example the encoding of an
+    /// unrecognised instruction.
+    pub const fn value(&self) -> u32 {
         match self {
-            Self::UnimplementedInstruction(instuction) => f.write_fmt(format_args!(
-                "The given instruction is not yet implemented {:#034b}",
-                instuction.to_le()
-            )),
-            Self::MisalignedInstructionFetch => {
-                f.write_str("Attempted to fetch an instruction not aligned to a 32-bit boundary")
+            Self::UnimplementedInstruction(instruction) => *instruction,
+            Self::LoadAddressMisaligned(address) | Self::StoreAddressMisaligned(address) => {
+                *address
             }
+            _ => 0,
+        }
+    }
+
+    /// The name of the exception cause as given in the privileged
+    /// specification's table of `mcause` values.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::MisalignedInstructionFetch => "Instruction address misaligned",
+            Self::InstructionAccessFault => "Instruction access fault",
+            Self::UnimplementedInstruction(_) => "Illegal instruction",
+            Self::Breakpoint => "Breakpoint",
+            Self::LoadAddressMisaligned(_) => "Load address misaligned",
+            Self::MemoryAccessFault => "Load access fault",
+            Self::StoreAddressMisaligned(_) => "Store/AMO address misaligned",
+            Self::StoreAccessFault => "Store/AMO access fault",
+            Self::EnvironmentCall(Privilege::User) => "Environment call from U-mode",
+            Self::EnvironmentCall(Privilege::Supervisor) => "Environment call from S-mode",
+            Self::EnvironmentCall(Privilege::Machine) => "Environment call from M-mode",
+            Self::InstructionPageFault => "Instruction page fault",
+            Self::LoadPageFault => "Load page fault",
+            Self::StorePageFault => "Store/AMO page fault",
         }
     }
 }
 
+/// Renders the name of the exception cause as given in the privileged
+/// specification, for example `Illegal instruction`, see [Exception::name].
+impl Display for Exception {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
 /// The core behaviour of an instruction set.
 pub trait InstructionSet: Sized {
     /// The type of the processor's registers.
@@ -66,17 +184,54 @@ pub trait InstructionSet: Sized {
     /// Decode this 32-bit value as an instruction. TODO: handle larger instructions
     fn decode(raw_instruction: u32) -> Result<Self, Exception>;
 
+    /// Decode this 32-bit value as an instruction of the given `isa`.
+    ///
+    /// Defaults to [InstructionSet::decode]. Instruction sets with encodings
+    /// which depend on the register width, such as `C.JAL` which is only
+    /// defined for RV32, override this. The processor decodes the
+    /// instructions it fetches with this.
+    fn decode_for(raw_instruction: u32, _isa: &Isa) -> Result<Self, Exception> {
+        Self::decode(raw_instruction)
+    }
+
     /// Encode the instruction to bytes. TODO: handle larger instructions
     fn encode(self) -> u32;
 
     /// Run this instruction on the provided processor.
-    fn execute(
+    fn execute<M: MemoryBus>(
         self,
-        processor: &mut Processor<Self::RegisterType, Self::CSRType>,
+        processor: &mut Processor<Self::RegisterType, Self::CSRType, M>,
     ) -> Result<(), Exception>;
 
     /// Returns the size of this instruction in number of bytes
     fn instruction_size(&self) -> Self::RegisterType;
+
+    /// The assembly mnemonic of this instruction, for example `"addi"`.
+    ///
+    /// Defaults to `"unknown"`, so instruction sets which do not provide
+    /// mnemonics are profiled as a single entry, see
+    /// [Processor::run_with_profile].
+    fn mnemonic(&self) -> &'static str {
+        "unknown"
+    }
+
+    /// The extension which defines this instruction, used to reject
+    /// instructions of extensions which are not enabled, see
+    /// [crate::isa::Isa].
+    fn extension(&self) -> Extension {
+        Extension::I
+    }
+}
+
+/// An object safe counterpart of [InstructionSet::execute] for RV32
+/// processors.
+///
+/// This allows a programme mixing instructions of different types, such as
+/// [crate::instructions::Instruction] and custom instructions, to be held as
+/// a `Vec<Box<dyn Executable>>`.
+pub trait Executable {
+    /// Run this instruction on the provided processor.
+    fn execute(&self, processor: &mut Processor<i32, CSR32>) -> Result<(), Exception>;
 }
 
 #[cfg(test)]
@@ -87,9 +242,65 @@ mod test {
         let exception = Exception::UnimplementedInstruction(u32::from_le(
             0b_00001000_00000100_00000010_00000001,
         ));
-        assert_eq!(
-            exception.to_string(),
-            "The given instruction is not yet implemented 0b00001000000001000000001000000001"
-        )
+        assert_eq!(exception.to_string(), "Illegal instruction")
+    }
+
+    #[test]
+    fn test_exception_code_and_name() {
+        for (exception, code, name) in [
+            (
+                Exception::MisalignedInstructionFetch,
+                0,
+                "Instruction address misaligned",
+            ),
+            (
+                Exception::InstructionAccessFault,
+                1,
+                "Instruction access fault",
+            ),
+            (
+                Exception::UnimplementedInstruction(0),
+                2,
+                "Illegal instruction",
+            ),
+            (Exception::Breakpoint, 3, "Breakpoint"),
+            (
+                Exception::LoadAddressMisaligned(1),
+                4,
+                "Load address misaligned",
+            ),
+            (Exception::MemoryAccessFault, 5, "Load access fault"),
+            (
+                Exception::StoreAddressMisaligned(1),
+                6,
+                "Store/AMO address misaligned",
+            ),
+            (Exception::StoreAccessFault, 7, "Store/AMO access fault"),
+            (
+                Exception::EnvironmentCall(Privilege::User),
+                8,
+                "Environment call from U-mode",
+            ),
+            (
+                Exception::EnvironmentCall(Privilege::Supervisor),
+                9,
+                "Environment call from S-mode",
+            ),
+            (
+                Exception::EnvironmentCall(Privilege::Machine),
+                11,
+                "Environment call from M-mode",
+            ),
+            (
+                Exception::InstructionPageFault,
+                12,
+                "Instruction page fault",
+            ),
+            (Exception::LoadPageFault, 13, "Load page fault"),
+            (Exception::StorePageFault, 15, "Store/AMO page fault"),
+        ] {
+            assert_eq!(exception.code(), code, "{exception:?}");
+            assert_eq!(exception.to_string(), name, "{exception:?}");
+        }
     }
 }
@@ -0,0 +1,753 @@
+//! The compressed instruction set extension, C.
+//!
+//! Compressed instructions are 16-bit encodings of common instructions, each
+//! of which expands to a single base instruction, see [Instruction::expand].
+//!
+//! The lowest two bits of a compressed instruction select one of three
+//! quadrants, and the `funct3` field in the top three bits selects the
+//! instruction within the quadrant. The immediates are scaled, zero-extended,
+//! and scattered across the instruction to keep the register fields in fixed
+//! positions.
+//!
+//! | Format | Description                 |
+//! | ------ | --------------------------- |
+//! | CIW    | Wide immediate instruction  |
+//! | CI     | Immediate instruction       |
+//! | CSS    | Stack-relative store        |
+//! | CJ     | Jump instruction            |
+use crate::{instruction_set::Exception, registers::Register};
+
+use super::Instruction;
+
+/// The bit mask for the quadrant of a compressed instruction.
+const QUADRANT_MASK: u16 = 0b_000_00000000_000_11;
+/// The right shift to apply to extract the `funct3` field.
+const FUNCT3_RSHIFT: usize = 13;
+
+/// A 3-bit register field, `rd'` or `rs2'`, addressing one of the eight most
+/// frequently used registers, `x8` to `x15`.
+pub(super) struct CompressedRegister;
+
+impl CompressedRegister {
+    /// The first register addressable by a compressed register field.
+    const OFFSET: u8 = 8;
+    /// The bit mask of the field after shifting.
+    const MASK: u16 = 0b111;
+
+    /// Decode the register from the field at `rshift`.
+    #[inline]
+    const fn decode(value: u16, rshift: usize) -> Register {
+        Register::const_from(((value >> rshift) & Self::MASK) as u8 + Self::OFFSET)
+    }
+
+    /// Returns `true` if the `register` is one of `x8` to `x15`, which can be
+    /// encoded in a compressed register field.
+    #[inline]
+    pub(super) const fn contains(register: Register) -> bool {
+        (register as u8).wrapping_sub(Self::OFFSET) <= Self::MASK as u8
+    }
+
+    /// Encode the register into the field at `lshift`.
+    ///
+    /// _Note_: registers outside of `x8` to `x15` cannot be encoded, see
+    /// [Instruction::try_encode].
+    #[inline]
+    const fn encode(register: Register, lshift: usize) -> u16 {
+        debug_assert!(
+            Self::contains(register),
+            "compressed register fields can only encode x8 to x15"
+        );
+        ((register as u8).wrapping_sub(Self::OFFSET) as u16 & Self::MASK) << lshift
+    }
+}
+
+/// `CIW`-type instruction - Wide immediate instructions.
+pub(super) struct CIW;
+
+impl CIW {
+    /// The right shift to apply to extract the destination register `rd'`.
+    const RD_RSHIFT: usize = 2;
+
+    /// Decode the destination register `rd'`.
+    #[inline]
+    const fn decode_rd(value: u16) -> Register {
+        CompressedRegister::decode(value, Self::RD_RSHIFT)
+    }
+
+    /// Decode the zero-extended immediate of `C.ADDI4SPN`, scaled by 4.
+    ///
+    /// The bits of the instruction `[12:5]` hold `nzuimm[5:4|9:6|2|3]`.
+    #[inline]
+    const fn decode_addi4spn(value: u16) -> u16 {
+        ((value >> 7) & 0b1111) << 6
+            | ((value >> 11) & 0b11) << 4
+            | ((value >> 5) & 0b1) << 3
+            | ((value >> 6) & 0b1) << 2
+    }
+
+    /// Encode the destination register and immediate of `C.ADDI4SPN`.
+    #[inline]
+    pub(super) const fn encode_addi4spn(rd: Register, imm: u16) -> u16 {
+        CompressedRegister::encode(rd, Self::RD_RSHIFT)
+            | ((imm >> 6) & 0b1111) << 7
+            | ((imm >> 4) & 0b11) << 11
+            | ((imm >> 3) & 0b1) << 5
+            | ((imm >> 2) & 0b1) << 6
+    }
+}
+
+/// `CI`-type instruction - Immediate instructions.
+pub(super) struct CI;
+
+impl CI {
+    /// The bit mask for the destination register after shifting.
+    const RD_MASK: u16 = 0b11111;
+    /// The right shift to apply to extract the destination register.
+    const RD_RSHIFT: usize = 7;
+
+    /// Decode the destination register.
+    #[inline]
+    const fn decode_rd(value: u16) -> Register {
+        Register::const_from(((value >> Self::RD_RSHIFT) & Self::RD_MASK) as u8)
+    }
+
+    /// Decode the sign-extended immediate of `C.LI` and `C.LUI`.
+    ///
+    /// The bits of the instruction `[12|6:2]` hold `imm[5|4:0]`.
+    #[inline]
+    const fn decode_imm(value: u16) -> i8 {
+        let imm = ((value >> 12) & 0b1) << 5 | ((value >> 2) & 0b11111);
+        // Sign-extend from bit 5.
+        ((imm << 2) as i8) >> 2
+    }
+
+    /// Encode the destination register and immediate of `C.LI` and `C.LUI`.
+    #[inline]
+    pub(super) const fn encode_imm(rd: Register, imm: i8) -> u16 {
+        let imm = imm as u16;
+        (rd as u16) << Self::RD_RSHIFT | ((imm >> 5) & 0b1) << 12 | (imm & 0b11111) << 2
+    }
+
+    /// Decode the zero-extended offset of `C.LWSP`, scaled by 4.
+    ///
+    /// The bits of the instruction `[12|6:2]` hold `uimm[5|4:2|7:6]`.
+    #[inline]
+    const fn decode_lwsp(value: u16) -> u8 {
+        (((value >> 12) & 0b1) << 5 | ((value >> 4) & 0b111) << 2 | ((value >> 2) & 0b11) << 6)
+            as u8
+    }
+
+    /// Decode the sign-extended immediate of `C.ADDI16SP`, scaled by 16.
+    ///
+    /// The bits of the instruction `[12|6:2]` hold `nzimm[9|4|6|8:7|5]`.
+    #[inline]
+    const fn decode_addi16sp(value: u16) -> i16 {
+        let imm = ((value >> 12) & 0b1) << 9
+            | ((value >> 6) & 0b1) << 4
+            | ((value >> 5) & 0b1) << 6
+            | ((value >> 3) & 0b11) << 7
+            | ((value >> 2) & 0b1) << 5;
+        // Sign-extend from bit 9.
+        ((imm << 6) as i16) >> 6
+    }
+
+    /// Encode the immediate of `C.ADDI16SP`.
+    #[inline]
+    pub(super) const fn encode_addi16sp(imm: i16) -> u16 {
+        let imm = imm as u16;
+        ((imm >> 9) & 0b1) << 12
+            | ((imm >> 4) & 0b1) << 6
+            | ((imm >> 6) & 0b1) << 5
+            | ((imm >> 7) & 0b11) << 3
+            | ((imm >> 5) & 0b1) << 2
+    }
+
+    /// Encode the destination register and offset of `C.LWSP`.
+    #[inline]
+    pub(super) const fn encode_lwsp(rd: Register, offset: u8) -> u16 {
+        let offset = offset as u16;
+        (rd as u16) << Self::RD_RSHIFT
+            | ((offset >> 5) & 0b1) << 12
+            | ((offset >> 2) & 0b111) << 4
+            | ((offset >> 6) & 0b11) << 2
+    }
+}
+
+/// `CSS`-type instruction - Stack-relative store instructions.
+pub(super) struct CSS;
+
+impl CSS {
+    /// The bit mask for the source register after shifting.
+    const RS2_MASK: u16 = 0b11111;
+    /// The right shift to apply to extract the source register.
+    const RS2_RSHIFT: usize = 2;
+
+    /// Decode the source register `rs2`.
+    #[inline]
+    const fn decode_rs2(value: u16) -> Register {
+        Register::const_from(((value >> Self::RS2_RSHIFT) & Self::RS2_MASK) as u8)
+    }
+
+    /// Decode the zero-extended offset of `C.SWSP`, scaled by 4.
+    ///
+    /// The bits of the instruction `[12:7]` hold `uimm[5:2|7:6]`.
+    #[inline]
+    const fn decode_swsp(value: u16) -> u8 {
+        (((value >> 9) & 0b1111) << 2 | ((value >> 7) & 0b11) << 6) as u8
+    }
+
+    /// Encode the source register and offset of `C.SWSP`.
+    #[inline]
+    pub(super) const fn encode_swsp(rs2: Register, offset: u8) -> u16 {
+        let offset = offset as u16;
+        (rs2 as u16) << Self::RS2_RSHIFT
+            | ((offset >> 2) & 0b1111) << 9
+            | ((offset >> 6) & 0b11) << 7
+    }
+}
+
+/// `CJ`-type instruction - Jump instructions.
+pub(super) struct CJ;
+
+impl CJ {
+    /// Decode the sign-extended offset of `C.JAL`.
+    ///
+    /// The bits of the instruction `[12:2]` hold
+    /// `offset[11|4|9:8|10|6|7|3:1|5]`.
+    #[inline]
+    const fn decode(value: u16) -> i16 {
+        let offset = ((value >> 12) & 0b1) << 11
+            | ((value >> 11) & 0b1) << 4
+            | ((value >> 9) & 0b11) << 8
+            | ((value >> 8) & 0b1) << 10
+            | ((value >> 7) & 0b1) << 6
+            | ((value >> 6) & 0b1) << 7
+            | ((value >> 3) & 0b111) << 1
+            | ((value >> 2) & 0b1) << 5;
+        // Sign-extend from bit 11.
+        ((offset << 4) as i16) >> 4
+    }
+
+    /// Encode the offset of `C.JAL`.
+    #[inline]
+    pub(super) const fn encode(offset: i16) -> u16 {
+        let offset = offset as u16;
+        ((offset >> 11) & 0b1) << 12
+            | ((offset >> 4) & 0b1) << 11
+            | ((offset >> 8) & 0b11) << 9
+            | ((offset >> 10) & 0b1) << 8
+            | ((offset >> 6) & 0b1) << 7
+            | ((offset >> 7) & 0b1) << 6
+            | ((offset >> 1) & 0b111) << 3
+            | ((offset >> 5) & 0b1) << 2
+    }
+}
+
+impl Instruction {
+    /// Decode a 16-bit compressed instruction.
+    ///
+    /// The reserved encodings, such as a `C.ADDI4SPN` with a zero immediate
+    /// (including the all-zero parcel), or a `C.LUI` with a zero immediate or
+    /// `rd` of `x0`, raise [Exception::UnimplementedInstruction].
+    ///
+    /// The RV32 encodings are decoded, so `C.JAL` rather than `C.ADDIW`, see
+    /// [Instruction::decode_for].
+    #[inline]
+    pub(super) const fn decode_compressed(value: u16) -> Result<Self, Exception> {
+        let instruction = match (value & QUADRANT_MASK, value >> FUNCT3_RSHIFT) {
+            (0b00, 0b000) if CIW::decode_addi4spn(value) != 0 => Instruction::C_ADDI4SPN {
+                rd: CIW::decode_rd(value),
+                imm: CIW::decode_addi4spn(value),
+            },
+            (0b01, 0b001) => Instruction::C_JAL {
+                offset: CJ::decode(value),
+            },
+            (0b01, 0b010) => Instruction::C_LI {
+                rd: CI::decode_rd(value),
+                imm: CI::decode_imm(value),
+            },
+            (0b01, 0b011)
+                if matches!(CI::decode_rd(value), Register::SP)
+                    && CI::decode_addi16sp(value) != 0 =>
+            {
+                Instruction::C_ADDI16SP {
+                    imm: CI::decode_addi16sp(value),
+                }
+            }
+            (0b01, 0b011)
+                if !matches!(CI::decode_rd(value), Register::ZERO | Register::SP)
+                    && CI::decode_imm(value) != 0 =>
+            {
+                Instruction::C_LUI {
+                    rd: CI::decode_rd(value),
+                    imm: CI::decode_imm(value),
+                }
+            }
+            (0b10, 0b010) if !matches!(CI::decode_rd(value), Register::ZERO) => {
+                Instruction::C_LWSP {
+                    rd: CI::decode_rd(value),
+                    offset: CI::decode_lwsp(value),
+                }
+            }
+            (0b10, 0b110) => Instruction::C_SWSP {
+                rs2: CSS::decode_rs2(value),
+                offset: CSS::decode_swsp(value),
+            },
+            _ => return Err(Exception::UnimplementedInstruction(value as u32)),
+        };
+        Ok(instruction)
+    }
+
+    /// Returns `true` if this is a 16-bit compressed instruction.
+    #[inline]
+    pub const fn is_compressed(&self) -> bool {
+        matches!(
+            self,
+            Instruction::C_ADDI4SPN { .. }
+                | Instruction::C_JAL { .. }
+                | Instruction::C_LI { .. }
+                | Instruction::C_ADDI16SP { .. }
+                | Instruction::C_LUI { .. }
+                | Instruction::C_LWSP { .. }
+                | Instruction::C_SWSP { .. }
+        )
+    }
+
+    /// The length of the encoded instruction in bytes, by which the
+    /// programme counter advances unless the instruction changes control
+    /// flow.
+    #[inline]
+    pub const fn size_in_bytes(&self) -> usize {
+        if self.is_compressed() {
+            2
+        } else {
+            4
+        }
+    }
+
+    /// Expand a compressed instruction into the equivalent base instruction,
+    /// base instructions are returned unchanged.
+    ///
+    /// The behaviour of a compressed instruction is that of its expansion,
+    /// except the programme counter advances by 2 bytes rather than 4.
+    #[inline]
+    pub const fn expand(self) -> Self {
+        match self {
+            Instruction::C_ADDI4SPN { rd, imm } => Instruction::ADDI {
+                rd,
+                rs1: Register::SP,
+                imm: imm as i16,
+            },
+            Instruction::C_JAL { offset } => Instruction::JAL {
+                rd: Register::RA,
+                offset: offset as i32,
+            },
+            Instruction::C_LI { rd, imm } => Instruction::ADDI {
+                rd,
+                rs1: Register::ZERO,
+                imm: imm as i16,
+            },
+            Instruction::C_ADDI16SP { imm } => Instruction::ADDI {
+                rd: Register::SP,
+                rs1: Register::SP,
+                imm,
+            },
+            Instruction::C_LUI { rd, imm } => Instruction::LUI {
+                rd,
+                imm: imm as i32,
+            },
+            Instruction::C_LWSP { rd, offset } => Instruction::LW {
+                rd,
+                rs1: Register::SP,
+                offset: offset as i16,
+            },
+            Instruction::C_SWSP { rs2, offset } => Instruction::SW {
+                rs1: Register::SP,
+                rs2,
+                offset: offset as i16,
+            },
+            instruction => instruction,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn decode_addi4spn() {
+        // c.addi4spn a0, sp, 16
+        assert_eq!(
+            Instruction::decode_compressed(0x0808),
+            Ok(Instruction::C_ADDI4SPN {
+                rd: Register::A0,
+                imm: 16
+            })
+        );
+        // c.addi4spn s0, sp, 32
+        assert_eq!(
+            Instruction::decode_compressed(0x1000),
+            Ok(Instruction::C_ADDI4SPN {
+                rd: Register::S0,
+                imm: 32
+            })
+        );
+        // c.addi4spn a5, sp, 1020
+        assert_eq!(
+            Instruction::decode_compressed(0b_000_11111111_111_00),
+            Ok(Instruction::C_ADDI4SPN {
+                rd: Register::A5,
+                imm: 1020
+            })
+        );
+    }
+
+    #[test]
+    fn decode_jal() {
+        // c.jal 0
+        assert_eq!(
+            Instruction::decode_compressed(0x2001),
+            Ok(Instruction::C_JAL { offset: 0 })
+        );
+        // c.jal 8
+        assert_eq!(
+            Instruction::decode_compressed(0x2021),
+            Ok(Instruction::C_JAL { offset: 8 })
+        );
+        // c.jal -2
+        assert_eq!(
+            Instruction::decode_compressed(0b_001_11111111111_01),
+            Ok(Instruction::C_JAL { offset: -2 })
+        );
+        // c.jal 2046
+        assert_eq!(
+            Instruction::decode_compressed(0b_001_01111111111_01),
+            Ok(Instruction::C_JAL { offset: 2046 })
+        );
+    }
+
+    #[test]
+    fn decode_jal_rv64() {
+        use crate::isa::Isa;
+
+        let rv64: Isa = "rv64ic".parse().unwrap();
+        assert_eq!(
+            Instruction::decode_for(0x2021, &rv64),
+            Err(Exception::UnimplementedInstruction(0x2021))
+        );
+        // C.ADDI16SP has the same encoding on RV64.
+        assert_eq!(
+            Instruction::decode_for(0x7139, &rv64),
+            Ok(Instruction::C_ADDI16SP { imm: -64 })
+        );
+    }
+
+    #[test]
+    fn decode_li() {
+        // c.li a0, 5
+        assert_eq!(
+            Instruction::decode_compressed(0x4515),
+            Ok(Instruction::C_LI {
+                rd: Register::A0,
+                imm: 5
+            })
+        );
+        // c.li a0, -1
+        assert_eq!(
+            Instruction::decode_compressed(0x557d),
+            Ok(Instruction::C_LI {
+                rd: Register::A0,
+                imm: -1
+            })
+        );
+        // c.li t6, -32
+        assert_eq!(
+            Instruction::decode_compressed(0b_010_1_11111_00000_01),
+            Ok(Instruction::C_LI {
+                rd: Register::T6,
+                imm: -32
+            })
+        );
+        // c.li with rd = zero is a hint, and c.li sp is not reserved.
+        assert_eq!(
+            Instruction::decode_compressed(0x4015),
+            Ok(Instruction::C_LI {
+                rd: Register::ZERO,
+                imm: 5
+            })
+        );
+        assert_eq!(
+            Instruction::decode_compressed(0x4105),
+            Ok(Instruction::C_LI {
+                rd: Register::SP,
+                imm: 1
+            })
+        );
+    }
+
+    #[test]
+    fn decode_lui() {
+        // c.lui a0, 1
+        assert_eq!(
+            Instruction::decode_compressed(0x6505),
+            Ok(Instruction::C_LUI {
+                rd: Register::A0,
+                imm: 1
+            })
+        );
+        // c.lui a5, 0xfffff
+        assert_eq!(
+            Instruction::decode_compressed(0x77fd),
+            Ok(Instruction::C_LUI {
+                rd: Register::A5,
+                imm: -1
+            })
+        );
+        // c.lui ra, 0xfffe0
+        assert_eq!(
+            Instruction::decode_compressed(0b_011_1_00001_00000_01),
+            Ok(Instruction::C_LUI {
+                rd: Register::RA,
+                imm: -32
+            })
+        );
+    }
+
+    #[test]
+    fn decode_addi16sp() {
+        // c.addi16sp sp, -64
+        assert_eq!(
+            Instruction::decode_compressed(0x7139),
+            Ok(Instruction::C_ADDI16SP { imm: -64 })
+        );
+        // c.addi16sp sp, 64
+        assert_eq!(
+            Instruction::decode_compressed(0x6121),
+            Ok(Instruction::C_ADDI16SP { imm: 64 })
+        );
+        // c.addi16sp sp, -48
+        assert_eq!(
+            Instruction::decode_compressed(0x7179),
+            Ok(Instruction::C_ADDI16SP { imm: -48 })
+        );
+        // c.addi16sp sp, 496
+        assert_eq!(
+            Instruction::decode_compressed(0b_011_0_00010_11111_01),
+            Ok(Instruction::C_ADDI16SP { imm: 496 })
+        );
+        // c.addi16sp sp, -512
+        assert_eq!(
+            Instruction::decode_compressed(0b_011_1_00010_00000_01),
+            Ok(Instruction::C_ADDI16SP { imm: -512 })
+        );
+    }
+
+    #[test]
+    fn decode_lwsp() {
+        // c.lwsp ra, 12(sp)
+        assert_eq!(
+            Instruction::decode_compressed(0x40b2),
+            Ok(Instruction::C_LWSP {
+                rd: Register::RA,
+                offset: 12
+            })
+        );
+        // c.lwsp t6, 252(sp)
+        assert_eq!(
+            Instruction::decode_compressed(0b_010_1_11111_11111_10),
+            Ok(Instruction::C_LWSP {
+                rd: Register::T6,
+                offset: 252
+            })
+        );
+    }
+
+    #[test]
+    fn decode_swsp() {
+        // c.swsp ra, 12(sp)
+        assert_eq!(
+            Instruction::decode_compressed(0xc606),
+            Ok(Instruction::C_SWSP {
+                rs2: Register::RA,
+                offset: 12
+            })
+        );
+        // c.swsp t6, 252(sp)
+        assert_eq!(
+            Instruction::decode_compressed(0b_110_111111_11111_10),
+            Ok(Instruction::C_SWSP {
+                rs2: Register::T6,
+                offset: 252
+            })
+        );
+    }
+
+    #[test]
+    fn decode_reserved() {
+        // The all-zero parcel is a `c.addi4spn` with a zero immediate.
+        assert_eq!(
+            Instruction::decode_compressed(0x0000),
+            Err(Exception::UnimplementedInstruction(0x0000))
+        );
+        assert_eq!(
+            Instruction::decode_compressed(0x001c),
+            Err(Exception::UnimplementedInstruction(0x001c))
+        );
+        // c.addi16sp with a zero immediate.
+        assert_eq!(
+            Instruction::decode_compressed(0x6101),
+            Err(Exception::UnimplementedInstruction(0x6101))
+        );
+        // c.lui with a zero immediate.
+        assert_eq!(
+            Instruction::decode_compressed(0x6501),
+            Err(Exception::UnimplementedInstruction(0x6501))
+        );
+        // c.lui with rd = zero.
+        assert_eq!(
+            Instruction::decode_compressed(0x6005),
+            Err(Exception::UnimplementedInstruction(0x6005))
+        );
+        // c.lwsp with rd = zero.
+        assert_eq!(
+            Instruction::decode_compressed(0x4032),
+            Err(Exception::UnimplementedInstruction(0x4032))
+        );
+    }
+
+    #[test]
+    fn encode_round_trips() {
+        for instruction in [
+            Instruction::C_ADDI4SPN {
+                rd: Register::A0,
+                imm: 16,
+            },
+            Instruction::C_ADDI4SPN {
+                rd: Register::A5,
+                imm: 1020,
+            },
+            Instruction::C_JAL { offset: -2048 },
+            Instruction::C_JAL { offset: 1234 },
+            Instruction::C_LI {
+                rd: Register::A0,
+                imm: -32,
+            },
+            Instruction::C_LI {
+                rd: Register::T6,
+                imm: 31,
+            },
+            Instruction::C_ADDI16SP { imm: -512 },
+            Instruction::C_ADDI16SP { imm: 496 },
+            Instruction::C_LUI {
+                rd: Register::RA,
+                imm: -32,
+            },
+            Instruction::C_LUI {
+                rd: Register::T6,
+                imm: 31,
+            },
+            Instruction::C_LWSP {
+                rd: Register::RA,
+                offset: 12,
+            },
+            Instruction::C_LWSP {
+                rd: Register::T6,
+                offset: 252,
+            },
+            Instruction::C_SWSP {
+                rs2: Register::RA,
+                offset: 12,
+            },
+            Instruction::C_SWSP {
+                rs2: Register::ZERO,
+                offset: 196,
+            },
+        ] {
+            assert_eq!(
+                Instruction::decode_compressed(instruction.encode() as u16),
+                Ok(instruction)
+            );
+        }
+    }
+
+    #[test]
+    fn expand() {
+        assert_eq!(
+            Instruction::C_ADDI4SPN {
+                rd: Register::A0,
+                imm: 1020
+            }
+            .expand(),
+            Instruction::ADDI {
+                rd: Register::A0,
+                rs1: Register::SP,
+                imm: 1020
+            }
+        );
+        assert_eq!(
+            Instruction::C_LWSP {
+                rd: Register::RA,
+                offset: 252
+            }
+            .expand(),
+            Instruction::LW {
+                rd: Register::RA,
+                rs1: Register::SP,
+                offset: 252
+            }
+        );
+        assert_eq!(
+            Instruction::C_SWSP {
+                rs2: Register::RA,
+                offset: 12
+            }
+            .expand(),
+            Instruction::SW {
+                rs1: Register::SP,
+                rs2: Register::RA,
+                offset: 12
+            }
+        );
+        assert_eq!(
+            Instruction::C_JAL { offset: -8 }.expand(),
+            Instruction::JAL {
+                rd: Register::RA,
+                offset: -8
+            }
+        );
+        assert_eq!(
+            Instruction::C_ADDI16SP { imm: -64 }.expand(),
+            Instruction::ADDI {
+                rd: Register::SP,
+                rs1: Register::SP,
+                imm: -64
+            }
+        );
+        assert_eq!(
+            Instruction::C_LI {
+                rd: Register::A0,
+                imm: -1
+            }
+            .expand(),
+            Instruction::ADDI {
+                rd: Register::A0,
+                rs1: Register::ZERO,
+                imm: -1
+            }
+        );
+        assert_eq!(
+            Instruction::C_LUI {
+                rd: Register::A5,
+                imm: -1
+            }
+            .expand(),
+            Instruction::LUI {
+                rd: Register::A5,
+                imm: -1
+            }
+        );
+        assert_eq!(Instruction::MRET.expand(), Instruction::MRET);
+    }
+}
@@ -0,0 +1,453 @@
+//! Static control flow analysis of instructions, and the construction of
+//! branches and jumps from absolute target addresses.
+use std::fmt::Display;
+
+use crate::registers::Register;
+
+use super::{compressed::CompressedRegister, Instruction};
+
+/// The number of bits in the offset of a conditional branch.
+const BRANCH_OFFSET_BITS: u32 = 13;
+/// The number of bits in the offset of a `JAL`.
+const JUMP_OFFSET_BITS: u32 = 21;
+/// The number of bits in the offset of a `C.JAL`.
+const COMPRESSED_JUMP_OFFSET_BITS: u32 = 12;
+
+/// The error returned when a branch or jump cannot be encoded, see
+/// [Instruction::beq_to] and [Instruction::try_encode], or an instruction is
+/// not valid for the target, see [Instruction::validate].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The offset to the target is not a multiple of 2 bytes.
+    MisalignedTarget {
+        /// The offset from the instruction to the target.
+        offset: i64,
+    },
+    /// The offset to the target does not fit in the instruction.
+    TargetOutOfRange {
+        /// The offset from the instruction to the target.
+        offset: i64,
+        /// The smallest offset which can be encoded.
+        min: i64,
+        /// The largest offset which can be encoded.
+        max: i64,
+    },
+    /// The register cannot be encoded, such as `x16` to `x31` on RV32E, or a
+    /// register other than `x8` to `x15` in a compressed register field.
+    UnavailableRegister {
+        /// The unavailable register.
+        register: Register,
+    },
+}
+
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MisalignedTarget { offset } => {
+                write!(f, "target offset {offset} is not a multiple of 2")
+            }
+            Self::TargetOutOfRange { offset, min, max } => {
+                write!(f, "target offset {offset} is out of range {min}..={max}")
+            }
+            Self::UnavailableRegister { register } => {
+                write!(f, "register {} is not available", register.name())
+            }
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+/// The offset from `from_pc` to `target`, checking it is aligned and fits in
+/// a signed offset of `bits` bits.
+fn offset(from_pc: i32, target: i32, bits: u32) -> Result<i32, EncodeError> {
+    let offset = i64::from(target) - i64::from(from_pc);
+    let (min, max) = (-(1 << (bits - 1)), (1 << (bits - 1)) - 2);
+    if offset % 2 != 0 {
+        Err(EncodeError::MisalignedTarget { offset })
+    } else if !(min..=max).contains(&offset) {
+        Err(EncodeError::TargetOutOfRange { offset, min, max })
+    } else {
+        Ok(offset as i32)
+    }
+}
+
+/// A possible next programme counter after executing an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Successor {
+    /// Execution continues at the given address.
+    Address(i32),
+    /// Execution continues at an address which can only be determined at
+    /// runtime, for example the target of a `JALR`.
+    Indirect,
+}
+
+/// An iterator of up to 2 successors of an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Successors {
+    /// The iterator which will yield two successors.
+    Two(Successor, Successor),
+    /// The iterator which will yield one successor.
+    One(Successor),
+    /// The iterator which will yield no successors.
+    Zero,
+}
+
+impl Iterator for Successors {
+    type Item = Successor;
+    fn next(&mut self) -> Option<Successor> {
+        match *self {
+            Successors::Two(a, b) => {
+                *self = Successors::One(b);
+                Some(a)
+            }
+            Successors::One(a) => {
+                *self = Successors::Zero;
+                Some(a)
+            }
+            Successors::Zero => None,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let size = match self {
+            Successors::Two(..) => 2,
+            Successors::One(_) => 1,
+            Successors::Zero => 0,
+        };
+        (size, Some(size))
+    }
+}
+
+impl Instruction {
+    /// The possible values of the programme counter after executing this
+    /// instruction at `pc`, without executing it.
+    ///
+    /// - Conditional branches yield the branch target followed by the
+    ///   fall-through address.
+    /// - `JAL` yields its static target.
+    /// - `JALR` yields [Successor::Indirect] since its target depends on a
+    ///   register.
+    /// - All other instructions fall through to the next instruction.
+    pub fn successors(&self, pc: i32) -> Successors {
+        let size = if self.is_compressed() { 2 } else { 4 };
+        let fall_through = Successor::Address(pc.wrapping_add(size));
+        match self.expand() {
+            Instruction::JAL { offset, .. } => {
+                Successors::One(Successor::Address(pc.wrapping_add(offset)))
+            }
+            Instruction::JALR { .. } | Instruction::MRET | Instruction::SRET => {
+                Successors::One(Successor::Indirect)
+            }
+            Instruction::BEQ { offset, .. }
+            | Instruction::BNE { offset, .. }
+            | Instruction::BLT { offset, .. }
+            | Instruction::BGE { offset, .. }
+            | Instruction::BLTU { offset, .. }
+            | Instruction::BGEU { offset, .. } => Successors::Two(
+                Successor::Address(pc.wrapping_add(offset.into())),
+                fall_through,
+            ),
+            _ => Successors::One(fall_through),
+        }
+    }
+
+    /// A `BEQ` at `from_pc` branching to the absolute `target` address.
+    ///
+    /// Raises an [EncodeError] if the offset to the `target` is not a
+    /// multiple of 2 or does not fit in the 13-bit offset.
+    pub fn beq_to(
+        rs1: Register,
+        rs2: Register,
+        from_pc: i32,
+        target: i32,
+    ) -> Result<Instruction, EncodeError> {
+        let offset = offset(from_pc, target, BRANCH_OFFSET_BITS)? as i16;
+        Ok(Instruction::BEQ { rs1, rs2, offset })
+    }
+
+    /// A `BNE` at `from_pc` branching to the absolute `target` address, see
+    /// [Instruction::beq_to].
+    pub fn bne_to(
+        rs1: Register,
+        rs2: Register,
+        from_pc: i32,
+        target: i32,
+    ) -> Result<Instruction, EncodeError> {
+        let offset = offset(from_pc, target, BRANCH_OFFSET_BITS)? as i16;
+        Ok(Instruction::BNE { rs1, rs2, offset })
+    }
+
+    /// A `BLT` at `from_pc` branching to the absolute `target` address, see
+    /// [Instruction::beq_to].
+    pub fn blt_to(
+        rs1: Register,
+        rs2: Register,
+        from_pc: i32,
+        target: i32,
+    ) -> Result<Instruction, EncodeError> {
+        let offset = offset(from_pc, target, BRANCH_OFFSET_BITS)? as i16;
+        Ok(Instruction::BLT { rs1, rs2, offset })
+    }
+
+    /// A `BGE` at `from_pc` branching to the absolute `target` address, see
+    /// [Instruction::beq_to].
+    pub fn bge_to(
+        rs1: Register,
+        rs2: Register,
+        from_pc: i32,
+        target: i32,
+    ) -> Result<Instruction, EncodeError> {
+        let offset = offset(from_pc, target, BRANCH_OFFSET_BITS)? as i16;
+        Ok(Instruction::BGE { rs1, rs2, offset })
+    }
+
+    /// A `BLTU` at `from_pc` branching to the absolute `target` address, see
+    /// [Instruction::beq_to].
+    pub fn bltu_to(
+        rs1: Register,
+        rs2: Register,
+        from_pc: i32,
+        target: i32,
+    ) -> Result<Instruction, EncodeError> {
+        let offset = offset(from_pc, target, BRANCH_OFFSET_BITS)? as i16;
+        Ok(Instruction::BLTU { rs1, rs2, offset })
+    }
+
+    /// A `BGEU` at `from_pc` branching to the absolute `target` address, see
+    /// [Instruction::beq_to].
+    pub fn bgeu_to(
+        rs1: Register,
+        rs2: Register,
+        from_pc: i32,
+        target: i32,
+    ) -> Result<Instruction, EncodeError> {
+        let offset = offset(from_pc, target, BRANCH_OFFSET_BITS)? as i16;
+        Ok(Instruction::BGEU { rs1, rs2, offset })
+    }
+
+    /// A `JAL` at `from_pc` jumping to the absolute `target` address.
+    ///
+    /// Raises an [EncodeError] if the offset to the `target` is not a
+    /// multiple of 2 or does not fit in the 21-bit offset.
+    pub fn jal_to(rd: Register, from_pc: i32, target: i32) -> Result<Instruction, EncodeError> {
+        let offset = offset(from_pc, target, JUMP_OFFSET_BITS)?;
+        Ok(Instruction::JAL { rd, offset })
+    }
+
+    /// Encode this instruction as a little endian [u32], checking that the
+    /// offset of a branch or jump fits in the instruction rather than
+    /// silently truncating it.
+    ///
+    /// Raises an [EncodeError] if the offset of a conditional branch is not a
+    /// multiple of 2 or does not fit in 13 bits, or likewise for the 21-bit
+    /// offset of a `JAL`, or the 12-bit offset of a `C.JAL`. Raises
+    /// [EncodeError::UnavailableRegister] if a compressed register field
+    /// holds a register other than `x8` to `x15`.
+    pub fn try_encode(self) -> Result<u32, EncodeError> {
+        match self {
+            Instruction::C_ADDI4SPN { rd, .. } if !CompressedRegister::contains(rd) => {
+                return Err(EncodeError::UnavailableRegister { register: rd });
+            }
+            Instruction::BEQ { offset: imm, .. }
+            | Instruction::BNE { offset: imm, .. }
+            | Instruction::BLT { offset: imm, .. }
+            | Instruction::BGE { offset: imm, .. }
+            | Instruction::BLTU { offset: imm, .. }
+            | Instruction::BGEU { offset: imm, .. } => {
+                offset(0, imm.into(), BRANCH_OFFSET_BITS)?;
+            }
+            Instruction::JAL { offset: imm, .. } => {
+                offset(0, imm, JUMP_OFFSET_BITS)?;
+            }
+            Instruction::C_JAL { offset: imm } => {
+                offset(0, imm.into(), COMPRESSED_JUMP_OFFSET_BITS)?;
+            }
+            _ => {}
+        }
+        Ok(self.encode())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn try_encode_compressed_registers() {
+        let addi4spn = |rd| Instruction::C_ADDI4SPN { rd, imm: 16 };
+        assert_eq!(
+            addi4spn(Register::A0).try_encode(),
+            Ok(addi4spn(Register::A0).encode())
+        );
+        for rd in [Register::T2, Register::A6] {
+            assert_eq!(
+                addi4spn(rd).try_encode(),
+                Err(EncodeError::UnavailableRegister { register: rd })
+            );
+        }
+    }
+
+    #[test]
+    fn try_encode_branch_offsets() {
+        let branch = |offset| Instruction::BNE {
+            rs1: Register::A0,
+            rs2: Register::A1,
+            offset,
+        };
+        assert_eq!(branch(2000).try_encode(), Ok(branch(2000).encode()));
+        assert_eq!(
+            branch(5000).try_encode(),
+            Err(EncodeError::TargetOutOfRange {
+                offset: 5000,
+                min: -4096,
+                max: 4094,
+            })
+        );
+        assert_eq!(
+            branch(-3).try_encode(),
+            Err(EncodeError::MisalignedTarget { offset: -3 })
+        );
+    }
+
+    #[test]
+    fn try_encode_jump_offsets() {
+        let jal = |offset| Instruction::JAL {
+            rd: Register::RA,
+            offset,
+        };
+        assert_eq!(jal(-0x10_0000).try_encode(), Ok(jal(-0x10_0000).encode()));
+        assert_eq!(
+            jal(0x10_0000).try_encode(),
+            Err(EncodeError::TargetOutOfRange {
+                offset: 0x10_0000,
+                min: -0x10_0000,
+                max: 0xF_FFFE,
+            })
+        );
+        assert_eq!(
+            Instruction::C_JAL { offset: 2048 }.try_encode(),
+            Err(EncodeError::TargetOutOfRange {
+                offset: 2048,
+                min: -2048,
+                max: 2046,
+            })
+        );
+        // Other instructions are encoded unchanged.
+        assert_eq!(
+            Instruction::ECALL.try_encode(),
+            Ok(Instruction::ECALL.encode())
+        );
+    }
+
+    #[test]
+    fn successors_of_branch() {
+        let instruction = Instruction::BEQ {
+            rs1: Register::A0,
+            rs2: Register::A1,
+            offset: -8,
+        };
+        assert_eq!(
+            instruction.successors(100).collect::<Vec<_>>(),
+            vec![Successor::Address(92), Successor::Address(104)]
+        );
+    }
+
+    #[test]
+    fn successors_of_jal() {
+        let instruction = Instruction::JAL {
+            rd: Register::RA,
+            offset: 64,
+        };
+        assert_eq!(
+            instruction.successors(100).collect::<Vec<_>>(),
+            vec![Successor::Address(164)]
+        );
+    }
+
+    #[test]
+    fn successors_of_jalr() {
+        let instruction = Instruction::JALR {
+            rd: Register::ZERO,
+            rs1: Register::RA,
+            offset: 0,
+        };
+        assert_eq!(
+            instruction.successors(100).collect::<Vec<_>>(),
+            vec![Successor::Indirect]
+        );
+    }
+
+    #[test]
+    fn successors_of_fall_through() {
+        let instruction = Instruction::ADD {
+            rd: Register::A0,
+            rs1: Register::A1,
+            rs2: Register::A2,
+        };
+        assert_eq!(
+            instruction.successors(100).collect::<Vec<_>>(),
+            vec![Successor::Address(104)]
+        );
+    }
+
+    #[test]
+    fn branch_to_forward_and_backward_targets() {
+        assert_eq!(
+            Instruction::beq_to(Register::A0, Register::A1, 0x100, 0x110),
+            Ok(Instruction::BEQ {
+                rs1: Register::A0,
+                rs2: Register::A1,
+                offset: 16
+            })
+        );
+        assert_eq!(
+            Instruction::bgeu_to(Register::A0, Register::A1, 0x100, 0x100 - 4096),
+            Ok(Instruction::BGEU {
+                rs1: Register::A0,
+                rs2: Register::A1,
+                offset: -4096
+            })
+        );
+        assert_eq!(
+            Instruction::jal_to(Register::RA, 0x100, 0x0),
+            Ok(Instruction::JAL {
+                rd: Register::RA,
+                offset: -0x100
+            })
+        );
+        let branch = Instruction::bne_to(Register::A0, Register::ZERO, 100, 92).unwrap();
+        assert_eq!(branch.successors(100).next(), Some(Successor::Address(92)));
+    }
+
+    #[test]
+    fn branch_to_invalid_targets() {
+        assert_eq!(
+            Instruction::blt_to(Register::A0, Register::A1, 0, 4096),
+            Err(EncodeError::TargetOutOfRange {
+                offset: 4096,
+                min: -4096,
+                max: 4094
+            })
+        );
+        assert_eq!(
+            Instruction::jal_to(Register::RA, i32::MIN, i32::MAX),
+            Err(EncodeError::MisalignedTarget {
+                offset: u32::MAX as i64
+            })
+        );
+        assert_eq!(
+            Instruction::jal_to(Register::RA, 0, 1 << 20),
+            Err(EncodeError::TargetOutOfRange {
+                offset: 1 << 20,
+                min: -(1 << 20),
+                max: (1 << 20) - 2
+            })
+        );
+        assert_eq!(
+            Instruction::bltu_to(Register::A0, Register::A1, 0, 3),
+            Err(EncodeError::MisalignedTarget { offset: 3 })
+        );
+    }
+}
@@ -0,0 +1,378 @@
+//! Formatting instructions as assembly.
+//!
+//! Instructions are formatted in the style of `objdump`, with the operands
+//! separated by commas without spaces, for example `add a0,a1,a0`. Branch
+//! and jump targets are shown as offsets relative to the instruction, so the
+//! output can be assembled again by [crate::asm].
+use std::fmt::Display;
+
+use crate::{csr, instruction_set::Exception, memory::MemoryBus};
+
+use super::Instruction;
+
+/// Decode the words of `memory` from `start` up to `end`, yielding the
+/// address of each word with the instruction it decodes to.
+///
+/// Words which cannot be decoded yield the error rather than ending the
+/// iteration, so a disassembler can show them as a `.word` directive. The
+/// words are read without side effects, see [MemoryBus::peek_word].
+///
+/// _Note_: the address advances by 4 bytes at a time, so compressed
+/// instructions are not decoded separately, see
+/// [crate::processor::Processor::disassemble_text].
+///
+/// # Example
+///
+/// ```
+/// use riskv::{instructions::{disassemble, Instruction}, memory::{Memory, MemoryBus}};
+///
+/// let mut memory = Memory::default();
+/// memory.write(0, &0x00a5_8533_u32.to_le_bytes());
+///
+/// for (address, instruction) in disassemble(&memory, 0, 8) {
+///     match instruction {
+///         Ok(instruction) => println!("{address:x}: {instruction}"),
+///         Err(_) => println!("{address:x}: .word {:#010x}", memory.peek_word(address)),
+///     }
+/// }
+/// ```
+pub fn disassemble<M: MemoryBus>(
+    memory: &M,
+    start: usize,
+    end: usize,
+) -> impl Iterator<Item = (usize, Result<Instruction, Exception>)> + '_ {
+    (start..end)
+        .step_by(4)
+        .map(|address| (address, Instruction::decode(memory.peek_word(address))))
+}
+
+/// A CSR operand, formatted by name if it has one and by address otherwise.
+struct Csr(u16);
+
+impl Display for Csr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match csr::name(self.0) {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{:#x}", self.0),
+        }
+    }
+}
+
+/// A `FENCE` predecessor or successor set, formatted as the letters of its
+/// members in the order `iorw`, or `0` if it is empty.
+struct FenceSet(u8);
+
+impl Display for FenceSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0 & 0b1111 == 0 {
+            return f.write_str("0");
+        }
+        for (bit, letter) in [(0b1000, "i"), (0b0100, "o"), (0b0010, "r"), (0b0001, "w")] {
+            if self.0 & bit != 0 {
+                f.write_str(letter)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mnemonic = self.mnemonic();
+        match *self {
+            Instruction::LUI { rd, imm } | Instruction::AUIPC { rd, imm } => {
+                write!(f, "{mnemonic} {},{:#x}", rd.name(), imm & 0xf_ffff)
+            }
+            Instruction::ADDI { rd, rs1, imm }
+            | Instruction::SLTI { rd, rs1, imm }
+            | Instruction::SLTIU { rd, rs1, imm }
+            | Instruction::XORI { rd, rs1, imm }
+            | Instruction::ORI { rd, rs1, imm }
+            | Instruction::ANDI { rd, rs1, imm } => {
+                write!(f, "{mnemonic} {},{},{imm}", rd.name(), rs1.name())
+            }
+            Instruction::SLLI { rd, rs1, shamt }
+            | Instruction::SRLI { rd, rs1, shamt }
+            | Instruction::SRAI { rd, rs1, shamt } => {
+                write!(f, "{mnemonic} {},{},{shamt}", rd.name(), rs1.name())
+            }
+            Instruction::ADD { rd, rs1, rs2 }
+            | Instruction::SUB { rd, rs1, rs2 }
+            | Instruction::SLL { rd, rs1, rs2 }
+            | Instruction::SLT { rd, rs1, rs2 }
+            | Instruction::SLTU { rd, rs1, rs2 }
+            | Instruction::XOR { rd, rs1, rs2 }
+            | Instruction::SRL { rd, rs1, rs2 }
+            | Instruction::SRA { rd, rs1, rs2 }
+            | Instruction::OR { rd, rs1, rs2 }
+            | Instruction::AND { rd, rs1, rs2 }
+            | Instruction::MUL { rd, rs1, rs2 }
+            | Instruction::MULH { rd, rs1, rs2 }
+            | Instruction::MULHSU { rd, rs1, rs2 }
+            | Instruction::MULHU { rd, rs1, rs2 }
+            | Instruction::DIV { rd, rs1, rs2 }
+            | Instruction::DIVU { rd, rs1, rs2 }
+            | Instruction::REM { rd, rs1, rs2 }
+            | Instruction::REMU { rd, rs1, rs2 } => {
+                write!(f, "{mnemonic} {},{},{}", rd.name(), rs1.name(), rs2.name())
+            }
+            Instruction::LB { rd, rs1, offset }
+            | Instruction::LH { rd, rs1, offset }
+            | Instruction::LW { rd, rs1, offset }
+            | Instruction::LBU { rd, rs1, offset }
+            | Instruction::LHU { rd, rs1, offset }
+            | Instruction::JALR { rd, rs1, offset } => {
+                write!(f, "{mnemonic} {},{offset}({})", rd.name(), rs1.name())
+            }
+            Instruction::SB { rs1, rs2, offset }
+            | Instruction::SH { rs1, rs2, offset }
+            | Instruction::SW { rs1, rs2, offset } => {
+                write!(f, "{mnemonic} {},{offset}({})", rs2.name(), rs1.name())
+            }
+            Instruction::CSRRW { rd, rs1, csr }
+            | Instruction::CSRRS { rd, rs1, csr }
+            | Instruction::CSRRC { rd, rs1, csr } => {
+                write!(f, "{mnemonic} {},{},{}", rd.name(), Csr(csr), rs1.name())
+            }
+            Instruction::CSRRWI { rd, imm, csr }
+            | Instruction::CSRRSI { rd, imm, csr }
+            | Instruction::CSRRCI { rd, imm, csr } => {
+                write!(f, "{mnemonic} {},{},{imm}", rd.name(), Csr(csr))
+            }
+            Instruction::SFENCE_VMA { rs1, rs2 } => {
+                write!(f, "{mnemonic} {},{}", rs1.name(), rs2.name())
+            }
+            // A full fence is shown without its operands, as it is written.
+            Instruction::FENCE {
+                pred: 0b1111,
+                succ: 0b1111,
+            }
+            | Instruction::FENCE_TSO
+            | Instruction::FENCE_I
+            | Instruction::ECALL
+            | Instruction::EBREAK
+            | Instruction::MRET
+            | Instruction::SRET
+            | Instruction::WFI => f.write_str(mnemonic),
+            Instruction::FENCE { pred, succ } => {
+                write!(f, "{mnemonic} {},{}", FenceSet(pred), FenceSet(succ))
+            }
+            Instruction::JAL { rd, offset } => write!(f, "{mnemonic} {},{offset}", rd.name()),
+            Instruction::BEQ { rs1, rs2, offset }
+            | Instruction::BNE { rs1, rs2, offset }
+            | Instruction::BLT { rs1, rs2, offset }
+            | Instruction::BGE { rs1, rs2, offset }
+            | Instruction::BLTU { rs1, rs2, offset }
+            | Instruction::BGEU { rs1, rs2, offset } => {
+                write!(f, "{mnemonic} {},{},{offset}", rs1.name(), rs2.name())
+            }
+            Instruction::C_ADDI4SPN { rd, imm } => write!(f, "{mnemonic} {},sp,{imm}", rd.name()),
+            Instruction::C_JAL { offset } => write!(f, "{mnemonic} {offset}"),
+            Instruction::C_LI { rd, imm } => write!(f, "{mnemonic} {},{imm}", rd.name()),
+            Instruction::C_ADDI16SP { imm } => write!(f, "{mnemonic} sp,{imm}"),
+            Instruction::C_LUI { rd, imm } => {
+                write!(f, "{mnemonic} {},{:#x}", rd.name(), imm as i32 & 0xf_ffff)
+            }
+            Instruction::C_LWSP { rd, offset } => {
+                write!(f, "{mnemonic} {},{offset}(sp)", rd.name())
+            }
+            Instruction::C_SWSP { rs2, offset } => {
+                write!(f, "{mnemonic} {},{offset}(sp)", rs2.name())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::macros::*;
+    use crate::{asm, csr::CSR32, csr::MSTATUS, processor::Processor, registers::Register};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn disassemble_simple_run() {
+        let programme = instructions![
+            Instruction::LI(Register::A0, 21),
+            Instruction::LI(Register::A1, 21),
+            Instruction::ADD {
+                rd: Register::A2,
+                rs1: Register::A1,
+                rs2: Register::A0,
+            },
+        ];
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(0, programme.clone());
+        processor.store_words(12, &[u32::MAX]);
+
+        let disassembly: Vec<_> = disassemble(processor.memory(), 0, 16).collect();
+        assert_eq!(
+            disassembly,
+            vec![
+                (0, Ok(programme[0])),
+                (4, Ok(programme[1])),
+                (8, Ok(programme[2])),
+                (12, Err(Exception::UnimplementedInstruction(u32::MAX))),
+            ]
+        );
+    }
+
+    #[test]
+    fn system_instructions_round_trip() {
+        for assembly in [
+            "mret",
+            "sret",
+            "wfi",
+            "ecall",
+            "ebreak",
+            "fence",
+            "fence rw,w",
+            "fence.i",
+        ] {
+            let program = asm::assemble(assembly, 0).unwrap();
+            let word = u32::from_le_bytes(program.segments[0].bytes[..4].try_into().unwrap());
+            let instruction = Instruction::decode(word).unwrap();
+            assert_eq!(instruction.encode(), word, "{assembly}");
+            assert_eq!(instruction.to_string(), assembly);
+            assert_eq!(instruction.mnemonic(), assembly.split(' ').next().unwrap());
+        }
+    }
+
+    #[test]
+    fn display() {
+        for (instruction, assembly) in [
+            (
+                Instruction::ADD {
+                    rd: Register::A0,
+                    rs1: Register::A1,
+                    rs2: Register::A0,
+                },
+                "add a0,a1,a0",
+            ),
+            (
+                Instruction::ADDI {
+                    rd: Register::SP,
+                    rs1: Register::SP,
+                    imm: -16,
+                },
+                "addi sp,sp,-16",
+            ),
+            (
+                Instruction::LUI {
+                    rd: Register::A0,
+                    imm: 0x12345,
+                },
+                "lui a0,0x12345",
+            ),
+            (
+                Instruction::SW {
+                    rs1: Register::SP,
+                    rs2: Register::RA,
+                    offset: 12,
+                },
+                "sw ra,12(sp)",
+            ),
+            (
+                Instruction::JALR {
+                    rd: Register::ZERO,
+                    rs1: Register::RA,
+                    offset: 0,
+                },
+                "jalr zero,0(ra)",
+            ),
+            (
+                Instruction::BNE {
+                    rs1: Register::A0,
+                    rs2: Register::ZERO,
+                    offset: -8,
+                },
+                "bne a0,zero,-8",
+            ),
+            (
+                Instruction::CSRRS {
+                    rd: Register::T0,
+                    rs1: Register::ZERO,
+                    csr: MSTATUS,
+                },
+                "csrrs t0,mstatus,zero",
+            ),
+            (
+                Instruction::CSRRWI {
+                    rd: Register::ZERO,
+                    imm: 3,
+                    csr: 0x7c0,
+                },
+                "csrrwi zero,0x7c0,3",
+            ),
+            (Instruction::MRET, "mret"),
+            (
+                Instruction::C_LWSP {
+                    rd: Register::RA,
+                    offset: 12,
+                },
+                "c.lwsp ra,12(sp)",
+            ),
+            (
+                Instruction::C_LUI {
+                    rd: Register::A5,
+                    imm: -1,
+                },
+                "c.lui a5,0xfffff",
+            ),
+        ] {
+            assert_eq!(instruction.to_string(), assembly);
+        }
+    }
+
+    #[test]
+    fn display_assembles_to_instruction() {
+        for instruction in [
+            Instruction::AUIPC {
+                rd: Register::T1,
+                imm: 0xfffff,
+            },
+            Instruction::SRAI {
+                rd: Register::A0,
+                rs1: Register::A1,
+                shamt: 31,
+            },
+            Instruction::REMU {
+                rd: Register::S11,
+                rs1: Register::T6,
+                rs2: Register::GP,
+            },
+            Instruction::LBU {
+                rd: Register::A0,
+                rs1: Register::S0,
+                offset: -2048,
+            },
+            Instruction::JAL {
+                rd: Register::RA,
+                offset: -4,
+            },
+            Instruction::BGEU {
+                rs1: Register::A0,
+                rs2: Register::A1,
+                offset: 4094,
+            },
+            Instruction::CSRRC {
+                rd: Register::A0,
+                rs1: Register::A1,
+                csr: 0x7c0,
+            },
+            Instruction::SFENCE_VMA {
+                rs1: Register::A0,
+                rs2: Register::A1,
+            },
+            Instruction::ECALL,
+            Instruction::EBREAK,
+        ] {
+            let program = asm::assemble(&instruction.to_string(), 0).unwrap();
+            assert_eq!(
+                program.segments[0].bytes,
+                instruction.encode().to_le_bytes(),
+                "{instruction}"
+            );
+        }
+    }
+}
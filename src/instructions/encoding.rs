@@ -0,0 +1,313 @@
+//! Builders for encoding raw instructions of each of the instruction types.
+//!
+//! These are useful for encoding custom instructions, for example those in
+//! the `custom-0` to `custom-3` opcode spaces, which have no corresponding
+//! [Instruction] variant. The fields of the instruction are masked to their
+//! width, so out of range values do not overwrite the neighbouring fields.
+//!
+//! [defined_encodings] lists the encodings of an op code which decode as an
+//! [Instruction], for building test vectors.
+//!
+//! # Example
+//!
+//! ```
+//! use riskv::{instructions::encoding::encode_r, registers::Register};
+//!
+//! // A `custom-0` instruction, `rd = rs1 op rs2`.
+//! let instruction = encode_r(0b_0001011, 0b_001, 0b_0000000, Register::A0, Register::A1, Register::A2);
+//! assert_eq!(instruction, 0x00c5_950b);
+//! ```
+use crate::registers::Register;
+
+use super::{funct3::Funct3, funct7::Funct7, types, Instruction, OPP_MASK};
+
+/// A combination of `funct3` and `funct7` which decodes as an instruction,
+/// see [defined_encodings].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DefinedEncoding {
+    /// The `funct3` field, or `None` if the instruction is decoded whatever
+    /// its value, for example when it is part of an immediate.
+    pub funct3: Option<u8>,
+    /// The `funct7` field, or `None` if the instruction is decoded whatever
+    /// its value, for example when it is part of an immediate.
+    pub funct7: Option<u8>,
+    /// The mnemonic of the decoded instruction.
+    pub mnemonic: &'static str,
+}
+
+/// The combinations of `funct3` and `funct7` which decode as an instruction
+/// for the `opcode`, for systematically covering the decode space in
+/// conformance tests and fuzzers.
+///
+/// Each combination is decoded with the other fields zero, so instructions
+/// told apart by another field, such as `ebreak` from `ecall`, are not
+/// listed.
+///
+/// # Example
+///
+/// ```
+/// use riskv::instructions::encoding::{defined_encodings, DefinedEncoding};
+///
+/// let lui = defined_encodings(0b_0110111);
+/// assert_eq!(lui, [DefinedEncoding { funct3: None, funct7: None, mnemonic: "lui" }]);
+/// ```
+pub fn defined_encodings(opcode: u8) -> Vec<DefinedEncoding> {
+    let decode = |funct3: u8, funct7: u8| {
+        let word = encode_op_code(opcode) | Funct3::encode(funct3) | Funct7::encode(funct7);
+        match word & 0b11 {
+            0b11 => Instruction::decode(word).ok().map(|i| i.mnemonic()),
+            _ => None,
+        }
+    };
+    // The mnemonic if every value of funct7 decodes as the same instruction.
+    let ignores_funct7 = |funct3| {
+        let mnemonic = decode(funct3, 0)?;
+        (1..=0b_1111111)
+            .all(|funct7| decode(funct3, funct7) == Some(mnemonic))
+            .then_some(mnemonic)
+    };
+
+    let mut encodings = Vec::new();
+    for funct3 in 0..=0b_111 {
+        match ignores_funct7(funct3) {
+            Some(mnemonic) => encodings.push(DefinedEncoding {
+                funct3: Some(funct3),
+                funct7: None,
+                mnemonic,
+            }),
+            None => encodings.extend((0..=0b_1111111).filter_map(|funct7| {
+                Some(DefinedEncoding {
+                    funct3: Some(funct3),
+                    funct7: Some(funct7),
+                    mnemonic: decode(funct3, funct7)?,
+                })
+            })),
+        }
+    }
+    if encodings.len() == 8
+        && encodings
+            .iter()
+            .all(|encoding| encoding.funct7.is_none() && encoding.mnemonic == encodings[0].mnemonic)
+    {
+        encodings = vec![DefinedEncoding {
+            funct3: None,
+            ..encodings[0]
+        }];
+    }
+    encodings
+}
+
+/// Encode the 7-bit op code, the highest bit is discarded.
+#[inline]
+const fn encode_op_code(opcode: u8) -> u32 {
+    opcode as u32 & OPP_MASK
+}
+
+/// Encode an `R`-type instruction, `rd = rs1 op rs2`.
+#[inline]
+pub const fn encode_r(
+    opcode: u8,
+    funct3: u8,
+    funct7: u8,
+    rd: Register,
+    rs1: Register,
+    rs2: Register,
+) -> u32 {
+    encode_op_code(opcode)
+        | Funct3::encode(funct3)
+        | Funct7::encode(funct7)
+        | types::R::encode(rd, rs1, rs2)
+}
+
+/// Encode an `I`-type instruction with a 12-bit signed immediate.
+#[inline]
+pub const fn encode_i(opcode: u8, funct3: u8, rd: Register, rs1: Register, imm: i16) -> u32 {
+    encode_op_code(opcode) | Funct3::encode(funct3) | types::I::encode(rd, rs1, imm)
+}
+
+/// Encode an `S`-type instruction with a 12-bit signed immediate.
+#[inline]
+pub const fn encode_s(opcode: u8, funct3: u8, rs1: Register, rs2: Register, imm: i16) -> u32 {
+    encode_op_code(opcode) | Funct3::encode(funct3) | types::S::encode(rs1, rs2, imm)
+}
+
+/// Encode a `B`-type instruction with a 13-bit signed offset, the lowest bit
+/// of which is discarded.
+#[inline]
+pub const fn encode_b(opcode: u8, funct3: u8, rs1: Register, rs2: Register, offset: i16) -> u32 {
+    encode_op_code(opcode) | Funct3::encode(funct3) | types::B::encode(rs1, rs2, offset)
+}
+
+/// Encode a `U`-type instruction, only the upper 20 bits of the immediate are
+/// kept.
+#[inline]
+pub const fn encode_u(opcode: u8, rd: Register, imm: i32) -> u32 {
+    encode_op_code(opcode) | types::U::encode(rd, imm)
+}
+
+/// Encode a `J`-type instruction with a 21-bit signed offset, the lowest bit
+/// of which is discarded.
+#[inline]
+pub const fn encode_j(opcode: u8, rd: Register, offset: i32) -> u32 {
+    encode_op_code(opcode) | types::J::encode(rd, offset)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instructions::Instruction;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn encode_r_matches_instruction() {
+        let (rd, rs1, rs2) = (Register::A0, Register::A1, Register::T6);
+        assert_eq!(
+            encode_r(0b_0110011, 0b_000, 0b_0000000, rd, rs1, rs2),
+            Instruction::ADD { rd, rs1, rs2 }.encode()
+        );
+        assert_eq!(
+            encode_r(0b_0110011, 0b_000, 0b_0100000, rd, rs1, rs2),
+            Instruction::SUB { rd, rs1, rs2 }.encode()
+        );
+        assert_eq!(
+            encode_r(0b_0110011, 0b_100, 0b_0000001, rd, rs1, rs2),
+            Instruction::DIV { rd, rs1, rs2 }.encode()
+        );
+    }
+
+    #[test]
+    fn encode_i_matches_instruction() {
+        let (rd, rs1) = (Register::S0, Register::SP);
+        for imm in [0, 1, -1, 2047, -2048] {
+            assert_eq!(
+                encode_i(0b_0010011, 0b_000, rd, rs1, imm),
+                Instruction::ADDI { rd, rs1, imm }.encode()
+            );
+            assert_eq!(
+                encode_i(0b_0000011, 0b_010, rd, rs1, imm),
+                Instruction::LW {
+                    rd,
+                    rs1,
+                    offset: imm
+                }
+                .encode()
+            );
+        }
+    }
+
+    #[test]
+    fn encode_s_matches_instruction() {
+        let (rs1, rs2) = (Register::SP, Register::RA);
+        for offset in [0, 12, -4, 2047, -2048] {
+            assert_eq!(
+                encode_s(0b_0100011, 0b_010, rs1, rs2, offset),
+                Instruction::SW { rs1, rs2, offset }.encode()
+            );
+        }
+    }
+
+    #[test]
+    fn encode_b_matches_instruction() {
+        let (rs1, rs2) = (Register::A0, Register::A1);
+        for offset in [0, 8, -8, 4094, -4096] {
+            assert_eq!(
+                encode_b(0b_1100011, 0b_000, rs1, rs2, offset),
+                Instruction::BEQ { rs1, rs2, offset }.encode()
+            );
+        }
+    }
+
+    #[test]
+    fn encode_u_and_j_match_instruction() {
+        let rd = Register::T0;
+        assert_eq!(
+            encode_u(0b_0110111, rd, 0x12345 << 12),
+            Instruction::LUI {
+                rd,
+                imm: 0x12345 << 12
+            }
+            .encode()
+        );
+        for offset in [0, 2048, -2048, 0xffffe, -0x100000] {
+            assert_eq!(
+                encode_j(0b_1101111, rd, offset),
+                Instruction::JAL { rd, offset }.encode()
+            );
+        }
+    }
+
+    #[test]
+    fn defined_encodings_op_imm() {
+        let encoding = |funct3, funct7, mnemonic| DefinedEncoding {
+            funct3: Some(funct3),
+            funct7,
+            mnemonic,
+        };
+        // Bit 25 is part of the shift amount, reserved on RV32.
+        assert_eq!(
+            defined_encodings(0b_0010011),
+            [
+                encoding(0b_000, None, "addi"),
+                encoding(0b_001, Some(0b_0000000), "slli"),
+                encoding(0b_001, Some(0b_0000001), "slli"),
+                encoding(0b_010, None, "slti"),
+                encoding(0b_011, None, "sltiu"),
+                encoding(0b_100, None, "xori"),
+                encoding(0b_101, Some(0b_0000000), "srli"),
+                encoding(0b_101, Some(0b_0000001), "srli"),
+                encoding(0b_101, Some(0b_0100000), "srai"),
+                encoding(0b_101, Some(0b_0100001), "srai"),
+                encoding(0b_110, None, "ori"),
+                encoding(0b_111, None, "andi"),
+            ]
+        );
+    }
+
+    #[test]
+    fn shifts_with_a_disallowed_funct7_are_unimplemented() {
+        for (funct3, funct7) in [
+            (0b_001, 0b_0100000),
+            (0b_001, 0b_0000010),
+            (0b_101, 0b_0000010),
+        ] {
+            let word = encode_r(
+                0b_0010011,
+                funct3,
+                funct7,
+                Register::A0,
+                Register::A0,
+                Register::T0,
+            );
+            assert_eq!(
+                Instruction::decode(word),
+                Err(crate::instruction_set::Exception::UnimplementedInstruction(
+                    word
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn defined_encodings_op() {
+        let encodings = defined_encodings(0b_0110011);
+        assert_eq!(encodings.len(), 18);
+        assert!(encodings.iter().all(|encoding| encoding.funct7.is_some()));
+        assert!(defined_encodings(0b_0000000).is_empty());
+        assert!(defined_encodings(0b_0110010).is_empty());
+    }
+
+    #[test]
+    fn fields_are_masked() {
+        assert_eq!(
+            encode_r(
+                0xff,
+                0xff,
+                0xff,
+                Register::ZERO,
+                Register::ZERO,
+                Register::ZERO
+            ),
+            0xfe00_707f
+        );
+    }
+}
@@ -16,6 +16,13 @@ impl Funct3 {
     pub(super) const fn decode(value: u32) -> u8 {
         ((value & Self::MASK) >> Self::RSHIFT) as u8
     }
+
+    /// Encode the 3-bit `Funct3` value into an instruction, higher bits are
+    /// discarded.
+    #[inline]
+    pub(super) const fn encode(value: u8) -> u32 {
+        ((value as u32) << Self::RSHIFT) & Self::MASK
+    }
 }
 
 #[cfg(test)]
@@ -28,4 +35,10 @@ mod test {
         let instruction = u32::from_le(0b_0100100_01000_01000_101_00000_0010011);
         assert_eq!(Funct3::decode(instruction), 0b_101);
     }
+
+    #[test]
+    fn encode() {
+        let instruction = u32::from_le(0b_0000000_00000_00000_101_00000_0000000);
+        assert_eq!(Funct3::encode(0b_101), instruction);
+    }
 }
@@ -7,9 +7,6 @@ pub(super) struct Funct7;
 impl Funct7 {
     /// The bit mask for the relevant bits in the instruction.
     ///
-    /// _Note_: this is provided here as a reference since it is not actually
-    /// required for extracting this value from the instruction.
-    #[allow(unused)]
     const MASK: u32 = u32::from_le(0b_1111111_00000_00000_000_00000_0000000);
     /// The right shift to apply to the instruction to extract the CSR value.
     const RSHIFT: usize = 25;
@@ -19,6 +16,13 @@ impl Funct7 {
     pub(super) const fn decode(value: u32) -> u8 {
         (value >> Self::RSHIFT) as u8
     }
+
+    /// Encode the 7-bit `Funct7` value into an instruction, the highest bit
+    /// is discarded.
+    #[inline]
+    pub(super) const fn encode(value: u8) -> u32 {
+        ((value as u32) << Self::RSHIFT) & Self::MASK
+    }
 }
 
 #[cfg(test)]
@@ -31,4 +35,10 @@ mod test {
         let instruction = u32::from_le(0b_0100100_01000_01000_101_00000_0010011);
         assert_eq!(Funct7::decode(instruction), 0b_0100100);
     }
+
+    #[test]
+    fn encode() {
+        let instruction = u32::from_le(0b_0100100_00000_00000_000_00000_0000000);
+        assert_eq!(Funct7::encode(0b_0100100), instruction);
+    }
 }
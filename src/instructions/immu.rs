@@ -11,6 +11,8 @@ impl ImmU {
     /// required for extracting this value from the instruction.
     #[allow(unused)]
     pub(super) const MASK: u32 = u32::from_le(0b_1111111_11111_11111_111_00000_0000000);
+    /// The largest value representable in the 20-bit immediate field.
+    pub(super) const MAX: i32 = (1 << 20) - 1;
     /// The right shift to apply to the instruction to extract the immediate value.
     pub(super) const RSHIFT: usize = 12;
 
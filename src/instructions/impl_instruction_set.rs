@@ -1,9 +1,13 @@
 //! The implementation of [crate::instruction_set::InstructionSet] for
 //! [crate::instructions::Instruction].
-use crate::csr::{ControlStatusRegisters, CSR32};
-use crate::instruction_set::{Exception, InstructionSet};
-use crate::integer::{AsSigned, AsUnsigned};
-use crate::processor::Processor;
+use crate::csr::{ControlStatusRegisters, MStatus, CSR32, MEPC, SEPC};
+use crate::instruction_set::{Exception, Executable, InstructionSet};
+use crate::integer::{ge_unsigned, lt_unsigned, AsSigned, AsUnsigned};
+use crate::isa::{Extension, Isa};
+use crate::memory::{MemAccess, MemoryBus};
+use crate::mmu::{Access, PAGE_SIZE};
+use crate::privilege::Privilege;
+use crate::processor::{EbreakPolicy, Processor, UnalignedPolicy};
 use crate::registers::Register;
 
 use super::Instruction;
@@ -11,6 +15,120 @@ use super::Instruction;
 impl Instruction {
     /// A bit mask apply to a register before being used as the shift amount.
     const SHIFT_MASK: i32 = 0b_00000000_00000000_00000000_00011111;
+
+    /// Compute the physical address of a load or store of `N` bytes from the
+    /// effective address `rs1 + offset`.
+    ///
+    /// If the effective address is not aligned to `N` bytes and the processor
+    /// traps unaligned accesses, see [UnalignedPolicy], raises
+    /// [Exception::LoadAddressMisaligned] or [Exception::StoreAddressMisaligned].
+    /// These are also raised for an unaligned access which crosses into a
+    /// page that is not mapped to the following physical page, since the
+    /// access cannot be performed on contiguous physical memory, leaving a
+    /// trap handler to emulate it.
+    ///
+    /// An effective address which wraps around the address space, for example
+    /// a base of zero with a negative offset, raises
+    /// [Exception::MemoryAccessFault] for loads or
+    /// [Exception::StoreAccessFault] for stores, whichever [MemoryBus] backs
+    /// the processor.
+    ///
+    /// Whether the physical address is within the memory is left to the
+    /// [MemoryBus], for example [crate::memory::Memory] faults on accesses
+    /// beyond [crate::memory::Memory::MAX_LEN] rather than allocating them.
+    #[inline]
+    fn physical_address<const N: usize>(
+        processor: &mut Processor<i32, CSR32, impl MemoryBus>,
+        rs1: Register,
+        offset: i16,
+        access: Access,
+    ) -> Result<usize, Exception> {
+        let address = processor.registers[rs1]
+            .as_unsigned()
+            .checked_add_signed(offset.into())
+            .ok_or(match access {
+                Access::Store => Exception::StoreAccessFault,
+                _ => Exception::MemoryAccessFault,
+            })?;
+        let misaligned = match access {
+            Access::Store => Exception::StoreAddressMisaligned(address),
+            _ => Exception::LoadAddressMisaligned(address),
+        };
+        if processor.unaligned_policy == UnalignedPolicy::Trap
+            && !(address as usize).is_multiple_of(N)
+        {
+            return Err(misaligned);
+        }
+        let physical = processor.translate(address as usize, access)?;
+        let last = address.wrapping_add(N as u32 - 1);
+        if last as usize / PAGE_SIZE != address as usize / PAGE_SIZE {
+            // The second page is translated separately, it may not be mapped
+            // to the physical page following the first.
+            if processor.translate(last as usize, access)? != physical.wrapping_add(N - 1) {
+                return Err(misaligned);
+            }
+        }
+        Ok(physical)
+    }
+
+    /// Compute the physical address of a load of `N` bytes, see
+    /// [Instruction::physical_address].
+    ///
+    /// Raises [Exception::MemoryAccessFault] if the memory is not readable,
+    /// see [crate::memory::Protection].
+    #[inline]
+    fn load_address<const N: usize>(
+        processor: &mut Processor<i32, CSR32, impl MemoryBus>,
+        rs1: Register,
+        offset: i16,
+    ) -> Result<usize, Exception> {
+        let address = Self::physical_address::<N>(processor, rs1, offset, Access::Load)?;
+        processor.memory.check_readable(address, N)?;
+        Ok(address)
+    }
+
+    /// Check the shift amount of an immediate shift `instruction` fits in the
+    /// 32-bit registers.
+    ///
+    /// Raises [Exception::UnimplementedInstruction] if bit 5 of the shift
+    /// amount is set, which is reserved in RV32I.
+    #[inline]
+    fn check_shamt(instruction: Instruction, shamt: u8) -> Result<(), Exception> {
+        if shamt >= i32::BITS as u8 {
+            return Err(Exception::UnimplementedInstruction(instruction.encode()));
+        }
+        Ok(())
+    }
+
+    /// Check the `csr` written by `instruction` is not read-only.
+    ///
+    /// Raises [Exception::UnimplementedInstruction] if the top two bits of
+    /// the CSR address are set, which marks a read-only CSR such as `cycle`.
+    #[inline]
+    fn check_csr_writable(instruction: Instruction, csr: u16) -> Result<(), Exception> {
+        if csr >> 10 == 0b11 {
+            return Err(Exception::UnimplementedInstruction(instruction.encode()));
+        }
+        Ok(())
+    }
+
+    /// Report the `access` of `width` bytes of `value` at `address` to the
+    /// processor's memory tracer.
+    #[inline]
+    fn trace(
+        processor: &mut Processor<i32, CSR32, impl MemoryBus>,
+        access: Access,
+        address: usize,
+        width: usize,
+        value: u32,
+    ) {
+        processor.trace_memory(MemAccess {
+            address,
+            width,
+            access,
+            value,
+        });
+    }
 }
 
 impl InstructionSet for Instruction {
@@ -22,26 +140,55 @@ impl InstructionSet for Instruction {
         raw_instruction.try_into()
     }
 
+    #[inline]
+    fn decode_for(raw_instruction: u32, isa: &Isa) -> Result<Self, Exception> {
+        Instruction::decode_for(raw_instruction, isa)
+    }
+
     fn encode(self) -> u32 {
         self.encode()
     }
 
     #[inline]
     fn instruction_size(&self) -> Self::RegisterType {
-        4
+        self.size_in_bytes() as i32
+    }
+
+    #[inline]
+    fn mnemonic(&self) -> &'static str {
+        self.mnemonic()
+    }
+
+    #[inline]
+    fn extension(&self) -> Extension {
+        self.extension()
     }
 
-    fn execute(
+    fn execute<M: MemoryBus>(
         self,
-        processor: &mut Processor<Self::RegisterType, Self::CSRType>,
+        processor: &mut Processor<Self::RegisterType, Self::CSRType, M>,
     ) -> Result<(), Exception> {
         // By default, after this instruction, we will move to the next one. Instructions that do
         // something different e.g. JAL can set this variable to modify the pc.
-        let mut pc = processor.pc + self.instruction_size();
+        let mut pc = processor.pc.wrapping_add(self.instruction_size());
+
+        // The canonical `nop`, common as padding, only advances the pc.
+        if let Instruction::ADDI {
+            rd: Register::ZERO,
+            rs1: Register::ZERO,
+            imm: 0,
+        } = self
+        {
+            processor.pc = pc;
+            return Ok(());
+        }
 
-        match self {
+        // Compressed instructions behave exactly as their expansion.
+        match self.expand() {
             Instruction::LUI { rd, imm } => processor.registers[rd] = imm << 12,
-            Instruction::AUIPC { rd, imm } => processor.registers[rd] = processor.pc + (imm << 12),
+            Instruction::AUIPC { rd, imm } => {
+                processor.registers[rd] = processor.pc.wrapping_add(imm << 12)
+            }
             Instruction::ADDI { rd, rs1, imm } => {
                 processor.registers[rd] = processor.registers[rs1].wrapping_add(imm.into())
             }
@@ -49,9 +196,8 @@ impl InstructionSet for Instruction {
                 processor.registers[rd] = (processor.registers[rs1] < imm.into()).into()
             }
             Instruction::SLTIU { rd, rs1, imm } => {
-                processor.registers[rd] = (processor.registers[rs1].as_unsigned()
-                    < Self::RegisterType::from(imm).as_unsigned())
-                .into()
+                processor.registers[rd] =
+                    lt_unsigned(processor.registers[rs1], Self::RegisterType::from(imm)).into()
             }
             Instruction::XORI { rd, rs1, imm } => {
                 processor.registers[rd] = processor.registers[rs1] ^ Self::RegisterType::from(imm)
@@ -63,13 +209,16 @@ impl InstructionSet for Instruction {
                 processor.registers[rd] = processor.registers[rs1] & Self::RegisterType::from(imm)
             }
             Instruction::SLLI { rd, rs1, shamt } => {
+                Self::check_shamt(self, shamt)?;
                 processor.registers[rd] = processor.registers[rs1] << shamt
             }
             Instruction::SRLI { rd, rs1, shamt } => {
+                Self::check_shamt(self, shamt)?;
                 processor.registers[rd] =
                     (processor.registers[rs1].as_unsigned() >> shamt).as_signed()
             }
             Instruction::SRAI { rd, rs1, shamt } => {
+                Self::check_shamt(self, shamt)?;
                 processor.registers[rd] = processor.registers[rs1] >> shamt
             }
             Instruction::ADD { rd, rs1, rs2 } => {
@@ -89,9 +238,8 @@ impl InstructionSet for Instruction {
                     (processor.registers[rs1] < processor.registers[rs2]).into()
             }
             Instruction::SLTU { rd, rs1, rs2 } => {
-                processor.registers[rd] = (processor.registers[rs1].as_unsigned()
-                    < processor.registers[rs2].as_unsigned())
-                .into()
+                processor.registers[rd] =
+                    lt_unsigned(processor.registers[rs1], processor.registers[rs2]).into()
             }
             Instruction::XOR { rd, rs1, rs2 } => {
                 processor.registers[rd] = processor.registers[rs1] ^ processor.registers[rs2]
@@ -111,166 +259,350 @@ impl InstructionSet for Instruction {
             Instruction::AND { rd, rs1, rs2 } => {
                 processor.registers[rd] = processor.registers[rs1] & processor.registers[rs2]
             }
+            Instruction::MUL { rd, rs1, rs2 } => {
+                processor.registers[rd] =
+                    processor.registers[rs1].wrapping_mul(processor.registers[rs2])
+            }
+            Instruction::MULH { rd, rs1, rs2 } => {
+                let product = processor.registers[rs1] as i64 * processor.registers[rs2] as i64;
+                processor.registers[rd] = (product >> 32) as i32
+            }
+            Instruction::MULHSU { rd, rs1, rs2 } => {
+                let product =
+                    processor.registers[rs1] as i64 * processor.registers[rs2].as_unsigned() as i64;
+                processor.registers[rd] = (product >> 32) as i32
+            }
+            Instruction::MULHU { rd, rs1, rs2 } => {
+                let product = processor.registers[rs1].as_unsigned() as u64
+                    * processor.registers[rs2].as_unsigned() as u64;
+                processor.registers[rd] = (product >> 32) as i32
+            }
+            Instruction::DIV { rd, rs1, rs2 } => {
+                let (dividend, divisor) = (processor.registers[rs1], processor.registers[rs2]);
+                processor.registers[rd] = match divisor {
+                    0 => -1,
+                    // Wrapping division handles the overflow of `i32::MIN / -1`.
+                    _ => dividend.wrapping_div(divisor),
+                }
+            }
+            Instruction::DIVU { rd, rs1, rs2 } => {
+                let (dividend, divisor) = (
+                    processor.registers[rs1].as_unsigned(),
+                    processor.registers[rs2].as_unsigned(),
+                );
+                processor.registers[rd] = match divisor {
+                    0 => u32::MAX,
+                    _ => dividend / divisor,
+                }
+                .as_signed()
+            }
+            Instruction::REM { rd, rs1, rs2 } => {
+                let (dividend, divisor) = (processor.registers[rs1], processor.registers[rs2]);
+                processor.registers[rd] = match divisor {
+                    0 => dividend,
+                    _ => dividend.wrapping_rem(divisor),
+                }
+            }
+            Instruction::REMU { rd, rs1, rs2 } => {
+                let (dividend, divisor) = (
+                    processor.registers[rs1].as_unsigned(),
+                    processor.registers[rs2].as_unsigned(),
+                );
+                processor.registers[rd] = match divisor {
+                    0 => dividend,
+                    _ => dividend % divisor,
+                }
+                .as_signed()
+            }
             Instruction::CSRRW {
                 rd,
                 rs1: Register::ZERO,
                 csr,
             } => processor.registers[rd] = processor.csrs.read(csr),
             Instruction::CSRRW { rd, rs1, csr } => {
+                Self::check_csr_writable(self, csr)?;
                 processor.registers[rd] = processor.csrs.read_write(csr, processor.registers[rs1])
             }
+            // Setting or clearing the bits of `x0` leaves every bit unchanged,
+            // so the CSR is only read and none of the side effects of a write
+            // occur.
+            Instruction::CSRRS {
+                rd,
+                rs1: Register::ZERO,
+                csr,
+            }
+            | Instruction::CSRRC {
+                rd,
+                rs1: Register::ZERO,
+                csr,
+            } => processor.registers[rd] = processor.csrs.read(csr),
             Instruction::CSRRS { rd, rs1, csr } => {
+                Self::check_csr_writable(self, csr)?;
                 processor.registers[rd] = processor.csrs.set_bits(csr, processor.registers[rs1])
             }
             Instruction::CSRRC { rd, rs1, csr } => {
+                Self::check_csr_writable(self, csr)?;
                 processor.registers[rd] = processor.csrs.clear_bits(csr, processor.registers[rs1])
             }
             Instruction::CSRRWI { rd, csr, imm } => {
+                Self::check_csr_writable(self, csr)?;
                 processor.registers[rd] = processor
                     .csrs
                     .read_write(csr, Self::RegisterType::from(imm))
             }
+            // A zero immediate leaves every bit unchanged, so the CSR is only
+            // read and none of the side effects of a write occur.
+            Instruction::CSRRSI { rd, csr, imm: 0 } | Instruction::CSRRCI { rd, csr, imm: 0 } => {
+                processor.registers[rd] = processor.csrs.read(csr)
+            }
             Instruction::CSRRSI { rd, csr, imm } => {
+                Self::check_csr_writable(self, csr)?;
                 processor.registers[rd] =
                     processor.csrs.set_bits(csr, Self::RegisterType::from(imm))
             }
             Instruction::CSRRCI { rd, csr, imm } => {
+                Self::check_csr_writable(self, csr)?;
                 processor.registers[rd] = processor
                     .csrs
                     .clear_bits(csr, Self::RegisterType::from(imm))
             }
+            // Address translations are not cached so there is nothing to flush.
+            // Accesses are performed in programme order by a single hart, and
+            // instructions are fetched from memory each step, so there is
+            // nothing to order.
+            Instruction::FENCE { .. } | Instruction::FENCE_TSO | Instruction::FENCE_I => {}
+            Instruction::SFENCE_VMA { .. } => {}
+            Instruction::ECALL => return Err(Exception::EnvironmentCall(processor.privilege)),
+            Instruction::EBREAK => {
+                if processor.ebreak_policy != EbreakPolicy::Ignore {
+                    return Err(Exception::Breakpoint);
+                }
+            }
+            // Interrupts are not supported, so there is nothing to wait for.
+            Instruction::WFI => {}
+            Instruction::MRET => {
+                if processor.privilege < Privilege::Machine {
+                    return Err(Exception::UnimplementedInstruction(self.encode()));
+                }
+                let mstatus = MStatus::new(&processor.csrs);
+                processor.privilege = Privilege::from_bits(mstatus.mpp());
+                mstatus.set_mie(mstatus.mpie());
+                mstatus.set_mpie(true);
+                mstatus.set_mpp(Privilege::User as u8);
+                pc = processor.csrs.read(MEPC);
+            }
+            Instruction::SRET => {
+                let mstatus = MStatus::new(&processor.csrs);
+                if processor.privilege == Privilege::User
+                    || (processor.privilege == Privilege::Supervisor && mstatus.tsr())
+                {
+                    return Err(Exception::UnimplementedInstruction(self.encode()));
+                }
+                processor.privilege = Privilege::from_bits(mstatus.spp());
+                mstatus.set_sie(mstatus.spie());
+                mstatus.set_spie(true);
+                mstatus.set_spp(Privilege::User as u8);
+                pc = processor.csrs.read(SEPC);
+            }
             Instruction::LB { rd, rs1, offset } => {
-                processor.registers[rd] = processor.memory.load_byte(
-                    processor.registers[rs1]
-                        .wrapping_add(offset.into())
-                        .as_unsigned() as usize,
-                ) as Self::RegisterType
+                let address = Self::load_address::<1>(processor, rs1, offset)?;
+                let value = processor.memory.load_byte(address);
+                Self::trace(
+                    processor,
+                    Access::Load,
+                    address,
+                    1,
+                    value.as_unsigned().into(),
+                );
+                processor.registers[rd] = value.into()
             }
             Instruction::LH { rd, rs1, offset } => {
-                processor.registers[rd] = processor.memory.load_half(
-                    processor.registers[rs1]
-                        .wrapping_add(offset.into())
-                        .as_unsigned() as usize,
-                ) as Self::RegisterType
+                let address = Self::load_address::<2>(processor, rs1, offset)?;
+                let value = processor.memory.load_half(address);
+                Self::trace(
+                    processor,
+                    Access::Load,
+                    address,
+                    2,
+                    value.as_unsigned().into(),
+                );
+                processor.registers[rd] = value.into()
             }
             Instruction::LW { rd, rs1, offset } => {
-                processor.registers[rd] = processor.memory.load_word(
-                    processor.registers[rs1]
-                        .wrapping_add(offset.into())
-                        .as_unsigned() as usize,
-                )
+                let address = Self::load_address::<4>(processor, rs1, offset)?;
+                let value = processor.memory.load_word(address);
+                Self::trace(processor, Access::Load, address, 4, value.as_unsigned());
+                processor.registers[rd] = value
             }
             Instruction::LBU { rd, rs1, offset } => {
-                processor.registers[rd] = processor.memory.load_byte(
-                    processor.registers[rs1]
-                        .wrapping_add(offset.into())
-                        .as_unsigned() as usize,
-                ) as u8 as Self::RegisterType
+                let address = Self::load_address::<1>(processor, rs1, offset)?;
+                let value = processor.memory.load_byte_unsigned(address);
+                Self::trace(processor, Access::Load, address, 1, value.into());
+                processor.registers[rd] = value.into()
             }
             Instruction::LHU { rd, rs1, offset } => {
-                processor.registers[rd] = processor.memory.load_half(
-                    processor.registers[rs1]
-                        .wrapping_add(offset.into())
-                        .as_unsigned() as usize,
-                ) as u16 as Self::RegisterType
+                let address = Self::load_address::<2>(processor, rs1, offset)?;
+                let value = processor.memory.load_half_unsigned(address);
+                Self::trace(processor, Access::Load, address, 2, value.into());
+                processor.registers[rd] = value.into()
+            }
+            Instruction::SB { rs1, rs2, offset } => {
+                let address = Self::physical_address::<1>(processor, rs1, offset, Access::Store)?;
+                let value = processor.registers[rs2] as i8;
+                processor.memory.store_byte(address, value)?;
+                Self::trace(
+                    processor,
+                    Access::Store,
+                    address,
+                    1,
+                    value.as_unsigned().into(),
+                );
+            }
+            Instruction::SH { rs1, rs2, offset } => {
+                let address = Self::physical_address::<2>(processor, rs1, offset, Access::Store)?;
+                let value = processor.registers[rs2] as i16;
+                processor.memory.store_half(address, value)?;
+                Self::trace(
+                    processor,
+                    Access::Store,
+                    address,
+                    2,
+                    value.as_unsigned().into(),
+                );
+            }
+            Instruction::SW { rs1, rs2, offset } => {
+                let address = Self::physical_address::<4>(processor, rs1, offset, Access::Store)?;
+                let value = processor.registers[rs2];
+                processor.memory.store_word(address, value)?;
+                Self::trace(processor, Access::Store, address, 4, value.as_unsigned());
             }
-            Instruction::SB { rs1, rs2, offset } => processor.memory.store_byte(
-                processor.registers[rs1]
-                    .wrapping_add(offset.into())
-                    .as_unsigned() as usize,
-                processor.registers[rs2] as i8,
-            ),
-            Instruction::SH { rs1, rs2, offset } => processor.memory.store_half(
-                processor.registers[rs1]
-                    .wrapping_add(offset.into())
-                    .as_unsigned() as usize,
-                processor.registers[rs2] as i16,
-            ),
-            Instruction::SW { rs1, rs2, offset } => processor.memory.store_word(
-                processor.registers[rs1]
-                    .wrapping_add(offset.into())
-                    .as_unsigned() as usize,
-                processor.registers[rs2],
-            ),
             Instruction::JAL { rd, offset } => {
-                let jump = processor.pc + offset;
-                if jump % 4 != 0 {
+                let jump = processor.pc.wrapping_add(offset);
+                if jump % processor.isa.instruction_alignment() as i32 != 0 {
                     return Err(Exception::MisalignedInstructionFetch);
                 }
+                if rd == Register::RA {
+                    processor.record_call(pc);
+                }
                 processor.registers[rd] = pc;
                 pc = jump;
             }
             Instruction::JALR { rd, rs1, offset } => {
-                let jump = (processor.registers[rs1] + offset as i32) & !1;
-                if jump % 4 != 0 {
+                let jump = processor.registers[rs1].wrapping_add(offset as i32) & !1;
+                if jump % processor.isa.instruction_alignment() as i32 != 0 {
                     return Err(Exception::MisalignedInstructionFetch);
                 }
+                if rd == Register::RA {
+                    processor.record_call(pc);
+                } else if rd == Register::ZERO && rs1 == Register::RA && offset == 0 {
+                    processor.record_return();
+                }
                 processor.registers[rd] = pc;
                 pc = jump;
             }
             Instruction::BEQ { rs1, rs2, offset } => {
                 if processor.registers[rs1] == processor.registers[rs2] {
-                    if offset % 4 != 0 {
+                    if offset as i32 % processor.isa.instruction_alignment() as i32 != 0 {
                         return Err(Exception::MisalignedInstructionFetch);
                     }
-                    pc = processor.pc + offset as i32;
+                    pc = processor.pc.wrapping_add(offset as i32);
                 }
             }
             Instruction::BNE { rs1, rs2, offset } => {
                 if processor.registers[rs1] != processor.registers[rs2] {
-                    if offset % 4 != 0 {
+                    if offset as i32 % processor.isa.instruction_alignment() as i32 != 0 {
                         return Err(Exception::MisalignedInstructionFetch);
                     }
-                    pc = processor.pc + offset as i32;
+                    pc = processor.pc.wrapping_add(offset as i32);
                 }
             }
             Instruction::BLT { rs1, rs2, offset } => {
                 if processor.registers[rs1] < processor.registers[rs2] {
-                    if offset % 4 != 0 {
+                    if offset as i32 % processor.isa.instruction_alignment() as i32 != 0 {
                         return Err(Exception::MisalignedInstructionFetch);
                     }
-                    pc = processor.pc + offset as i32;
+                    pc = processor.pc.wrapping_add(offset as i32);
                 }
             }
             Instruction::BGE { rs1, rs2, offset } => {
                 if processor.registers[rs1] >= processor.registers[rs2] {
-                    if offset % 4 != 0 {
+                    if offset as i32 % processor.isa.instruction_alignment() as i32 != 0 {
                         return Err(Exception::MisalignedInstructionFetch);
                     }
-                    pc = processor.pc + offset as i32;
+                    pc = processor.pc.wrapping_add(offset as i32);
                 }
             }
             Instruction::BLTU { rs1, rs2, offset } => {
-                if processor.registers[rs1].as_unsigned() < processor.registers[rs2].as_unsigned() {
-                    if offset % 4 != 0 {
+                if lt_unsigned(processor.registers[rs1], processor.registers[rs2]) {
+                    if offset as i32 % processor.isa.instruction_alignment() as i32 != 0 {
                         return Err(Exception::MisalignedInstructionFetch);
                     }
-                    pc = processor.pc + offset as i32;
+                    pc = processor.pc.wrapping_add(offset as i32);
                 }
             }
             Instruction::BGEU { rs1, rs2, offset } => {
-                if processor.registers[rs1].as_unsigned() >= processor.registers[rs2].as_unsigned()
-                {
-                    if offset % 4 != 0 {
+                if ge_unsigned(processor.registers[rs1], processor.registers[rs2]) {
+                    if offset as i32 % processor.isa.instruction_alignment() as i32 != 0 {
                         return Err(Exception::MisalignedInstructionFetch);
                     }
-                    pc = processor.pc + offset as i32;
+                    pc = processor.pc.wrapping_add(offset as i32);
                 }
             }
+            Instruction::C_ADDI4SPN { .. }
+            | Instruction::C_JAL { .. }
+            | Instruction::C_LI { .. }
+            | Instruction::C_ADDI16SP { .. }
+            | Instruction::C_LUI { .. }
+            | Instruction::C_LWSP { .. }
+            | Instruction::C_SWSP { .. } => unreachable!("compressed instructions are expanded"),
         }
         processor.pc = pc;
         Ok(())
     }
 }
 
+impl Executable for Instruction {
+    #[inline]
+    fn execute(&self, processor: &mut Processor<i32, CSR32>) -> Result<(), Exception> {
+        InstructionSet::execute(*self, processor)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::csr::SATP;
+    use crate::devices::ConsoleDevice;
     use crate::integer::i12;
-    use crate::registers::Register;
+    use crate::memory::Memory;
+    use crate::memory::{Protection, SparseMemory};
+    use crate::registers::{Register, Registers};
     use crate::test::macros::*;
     use pretty_assertions::assert_eq;
 
+    /// A supervisor mode processor on [SparseMemory] whose Sv32 page table,
+    /// rooted at physical page 1, maps the virtual pages from `0x0040_0000`
+    /// onwards to the physical pages `frames` for reading and writing.
+    fn paged_processor(frames: &[u32]) -> Processor<i32, CSR32, SparseMemory> {
+        let mut processor = Processor::from_parts(
+            Registers::default(),
+            0,
+            CSR32::default(),
+            SparseMemory::default(),
+        );
+        let pte = |ppn: u32, flags: u32| ((ppn << 10) | flags | 1) as i32;
+        processor.memory.store_word(0x1004, pte(2, 0)).unwrap();
+        for (index, frame) in frames.iter().enumerate() {
+            processor
+                .memory
+                .store_word(0x2000 + 4 * index, pte(*frame, 0b0110))
+                .unwrap();
+        }
+        processor.csrs.read_write(SATP, (1 << 31) | 1);
+        processor.privilege = Privilege::Supervisor;
+        processor
+    }
+
     #[test]
     fn execute_li() {
         for (i, pc_inc) in [
@@ -281,6 +613,9 @@ mod test {
             (i12::MAX as i32 + 1, 8),
             (i32::MAX, 8),
             (i32::MIN, 8),
+            (0x7FFF_F800, 8),
+            (0x8000_07FF_u32 as i32, 8),
+            (0xFFFF_FFFF_u32 as i32, 4),
         ] {
             test_execute!(
                 Instruction::LI(Register::T1, i),
@@ -717,6 +1052,29 @@ mod test {
         );
     }
 
+    #[test]
+    fn execute_sltu_with_zero_rs1() {
+        // `SNEZ rd, rs` is `SLTU rd, zero, rs`, relying on `0 <u rs` being `rs != 0`.
+        for (rs2, expected) in [
+            (0, 0),
+            (1, 1),
+            (i32::MAX, 1),
+            (i32::MIN, 1),
+            (u32::MAX as i32, 1),
+        ] {
+            test_execute!(
+                Instruction::SLTU{rd: Register::T4, rs1: Register::ZERO, rs2: Register::A4},
+                executed_on: {registers: {a4: rs2}},
+                results_in: {registers: {t4: expected, a4: rs2}, pc: 4},
+            );
+            test_execute!(
+                Instruction::SNEZ(Register::T4, Register::A4),
+                executed_on: {registers: {a4: rs2}},
+                results_in: {registers: {t4: expected, a4: rs2}, pc: 4},
+            );
+        }
+    }
+
     #[test]
     fn execute_sltu() {
         test_execute!(
@@ -871,6 +1229,123 @@ mod test {
         );
     }
 
+    #[test]
+    fn execute_mul() {
+        test_execute!(
+            Instruction::MUL{rd: Register::S0, rs1: Register::S1, rs2: Register::A1},
+            executed_on: {registers: {s1: 6, a1: -7}},
+            results_in: {registers: {s0: -42, s1: 6, a1: -7}, pc: 4},
+        );
+        test_execute!(
+            Instruction::MUL{rd: Register::S0, rs1: Register::S1, rs2: Register::A1},
+            executed_on: {registers: {s1: 0x10000, a1: 0x10001}},
+            results_in: {registers: {s0: 0x10000, s1: 0x10000, a1: 0x10001}, pc: 4},
+        );
+    }
+
+    #[test]
+    fn execute_mulh() {
+        test_execute!(
+            Instruction::MULH{rd: Register::S0, rs1: Register::S1, rs2: Register::A1},
+            executed_on: {registers: {s1: i32::MIN, a1: i32::MIN}},
+            results_in: {registers: {s0: 0x4000_0000, s1: i32::MIN, a1: i32::MIN}, pc: 4},
+        );
+        test_execute!(
+            Instruction::MULH{rd: Register::S0, rs1: Register::S1, rs2: Register::A1},
+            executed_on: {registers: {s1: -1, a1: 1}},
+            results_in: {registers: {s0: -1, s1: -1, a1: 1}, pc: 4},
+        );
+    }
+
+    #[test]
+    fn execute_mulhsu() {
+        test_execute!(
+            Instruction::MULHSU{rd: Register::S0, rs1: Register::S1, rs2: Register::A1},
+            executed_on: {registers: {s1: -1, a1: -1}},
+            results_in: {registers: {s0: -1, s1: -1, a1: -1}, pc: 4},
+        );
+        test_execute!(
+            Instruction::MULHSU{rd: Register::S0, rs1: Register::S1, rs2: Register::A1},
+            executed_on: {registers: {s1: 2, a1: i32::MIN}},
+            results_in: {registers: {s0: 1, s1: 2, a1: i32::MIN}, pc: 4},
+        );
+    }
+
+    #[test]
+    fn execute_mulhu() {
+        test_execute!(
+            Instruction::MULHU{rd: Register::S0, rs1: Register::S1, rs2: Register::A1},
+            executed_on: {registers: {s1: -1, a1: -1}},
+            results_in: {registers: {s0: -2, s1: -1, a1: -1}, pc: 4},
+        );
+    }
+
+    #[test]
+    fn execute_div() {
+        test_execute!(
+            Instruction::DIV{rd: Register::S0, rs1: Register::S1, rs2: Register::A1},
+            executed_on: {registers: {s1: -7, a1: 2}},
+            results_in: {registers: {s0: -3, s1: -7, a1: 2}, pc: 4},
+        );
+        test_execute!(
+            Instruction::DIV{rd: Register::S0, rs1: Register::S1, rs2: Register::A1},
+            executed_on: {registers: {s1: 42}},
+            results_in: {registers: {s0: -1, s1: 42}, pc: 4},
+        );
+        test_execute!(
+            Instruction::DIV{rd: Register::S0, rs1: Register::S1, rs2: Register::A1},
+            executed_on: {registers: {s1: i32::MIN, a1: -1}},
+            results_in: {registers: {s0: i32::MIN, s1: i32::MIN, a1: -1}, pc: 4},
+        );
+    }
+
+    #[test]
+    fn execute_divu() {
+        test_execute!(
+            Instruction::DIVU{rd: Register::S0, rs1: Register::S1, rs2: Register::A1},
+            executed_on: {registers: {s1: -1, a1: 2}},
+            results_in: {registers: {s0: i32::MAX, s1: -1, a1: 2}, pc: 4},
+        );
+        test_execute!(
+            Instruction::DIVU{rd: Register::S0, rs1: Register::S1, rs2: Register::A1},
+            executed_on: {registers: {s1: 42}},
+            results_in: {registers: {s0: -1, s1: 42}, pc: 4},
+        );
+    }
+
+    #[test]
+    fn execute_rem() {
+        test_execute!(
+            Instruction::REM{rd: Register::S0, rs1: Register::S1, rs2: Register::A1},
+            executed_on: {registers: {s1: -7, a1: 2}},
+            results_in: {registers: {s0: -1, s1: -7, a1: 2}, pc: 4},
+        );
+        test_execute!(
+            Instruction::REM{rd: Register::S0, rs1: Register::S1, rs2: Register::A1},
+            executed_on: {registers: {s1: 42}},
+            results_in: {registers: {s0: 42, s1: 42}, pc: 4},
+        );
+        test_execute!(
+            Instruction::REM{rd: Register::S0, rs1: Register::S1, rs2: Register::A1},
+            executed_on: {registers: {s1: i32::MIN, a1: -1}},
+            results_in: {registers: {s1: i32::MIN, a1: -1}, pc: 4},
+        );
+    }
+
+    #[test]
+    fn execute_remu() {
+        test_execute!(
+            Instruction::REMU{rd: Register::S0, rs1: Register::S1, rs2: Register::A1},
+            executed_on: {registers: {s1: -1, a1: 10}},
+            results_in: {registers: {s0: 5, s1: -1, a1: 10}, pc: 4},
+        );
+        test_execute!(
+            Instruction::REMU{rd: Register::S0, rs1: Register::S1, rs2: Register::A1},
+            executed_on: {registers: {s1: -42}},
+            results_in: {registers: {s0: -42, s1: -42}, pc: 4},
+        );
+    }
+
     #[test]
     fn execute_lb() {
         test_execute!(
@@ -964,6 +1439,20 @@ mod test {
         );
     }
 
+    #[test]
+    fn execute_stores_truncate_to_access_width() {
+        test_execute!(
+            Instruction::SB { rs1: Register::T1, rs2: Register::T3, offset: 0, },
+            executed_on: {registers: {t3: 0x1234_5678}, memory: {0: -1}},
+            results_in: {registers: {t3: 0x1234_5678}, memory: {0: 0xFFFF_FF78_u32 as i32}, pc: 4},
+        );
+        test_execute!(
+            Instruction::SH { rs1: Register::T1, rs2: Register::T3, offset: 0, },
+            executed_on: {registers: {t3: 0x1234_5678}, memory: {0: -1}},
+            results_in: {registers: {t3: 0x1234_5678}, memory: {0: 0xFFFF_5678_u32 as i32}, pc: 4},
+        );
+    }
+
     #[test]
     fn execute_sw() {
         test_execute!(
@@ -973,6 +1462,828 @@ mod test {
         );
     }
 
+    #[test]
+    fn execute_c_addi4spn() {
+        test_execute!(
+            Instruction::C_ADDI4SPN { rd: Register::A0, imm: 1020 },
+            executed_on: {registers: {sp: 4096}},
+            results_in: {registers: {sp: 4096, a0: 5116}, pc: 2},
+        );
+    }
+
+    #[test]
+    fn execute_c_jal() {
+        test_execute!(
+            Instruction::C_JAL { offset: -8 },
+            executed_on: {registers: {a0: 0}, pc: 8},
+            results_in: {registers: {ra: 10}, pc: 0},
+        );
+    }
+
+    #[test]
+    fn execute_c_li() {
+        test_execute!(
+            Instruction::C_LI { rd: Register::A0, imm: -32 },
+            executed_on: {registers: {a0: 7}},
+            results_in: {registers: {a0: -32}, pc: 2},
+        );
+    }
+
+    #[test]
+    fn execute_c_lui() {
+        test_execute!(
+            Instruction::C_LUI { rd: Register::A5, imm: -1 },
+            executed_on: {registers: {a5: 7}},
+            results_in: {registers: {a5: -4096}, pc: 2},
+        );
+    }
+
+    #[test]
+    fn execute_c_addi16sp() {
+        test_execute!(
+            Instruction::C_ADDI16SP { imm: -64 },
+            executed_on: {registers: {sp: 1024}},
+            results_in: {registers: {sp: 960}, pc: 2},
+        );
+    }
+
+    #[test]
+    fn execute_c_lwsp() {
+        test_execute!(
+            Instruction::C_LWSP { rd: Register::RA, offset: 252 },
+            executed_on: {registers: {sp: 4}, memory: {256: 12}},
+            results_in: {registers: {sp: 4, ra: 12}, memory: {256: 12}, pc: 2},
+        );
+    }
+
+    #[test]
+    fn execute_c_swsp() {
+        test_execute!(
+            Instruction::C_SWSP { rs2: Register::RA, offset: 12 },
+            executed_on: {registers: {sp: 4, ra: 42}},
+            results_in: {registers: {sp: 4, ra: 42}, memory: {16: 42}, pc: 2},
+        );
+    }
+
+    #[test]
+    fn execute_compressed_as_expansion() {
+        for instruction in [
+            Instruction::C_ADDI4SPN {
+                rd: Register::S1,
+                imm: 64,
+            },
+            Instruction::C_LWSP {
+                rd: Register::T0,
+                offset: 8,
+            },
+            Instruction::C_SWSP {
+                rs2: Register::A0,
+                offset: 128,
+            },
+            Instruction::C_ADDI16SP { imm: 496 },
+        ] {
+            let mut compressed = processor_state!(
+                registers: {sp: 1024, a0: -3}, memory: {1032: 7}
+            );
+            let mut expanded = processor_state!(
+                registers: {sp: 1024, a0: -3}, memory: {1032: 7}
+            );
+
+            instruction.execute(&mut compressed).unwrap();
+            instruction.expand().execute(&mut expanded).unwrap();
+
+            assert_eq!(compressed.pc, 2);
+            assert_eq!(expanded.pc, 4);
+            expanded.pc = 2;
+            assert_eq!(compressed, expanded, "{instruction:?}");
+        }
+    }
+
+    #[test]
+    fn execute_sfence_vma() {
+        test_execute!(
+            Instruction::SFENCE_VMA { rs1: Register::A0, rs2: Register::A1 },
+            executed_on: {registers: {a0: 64, a1: 1}, memory: {64: 42}},
+            results_in: {registers: {a0: 64, a1: 1}, memory: {64: 42}, pc: 4},
+        );
+    }
+
+    #[test]
+    fn execute_fence_and_wfi() {
+        test_execute!(
+            [
+                Instruction::FENCE { pred: 0b1111, succ: 0b1111 },
+                Instruction::FENCE_I,
+                Instruction::WFI,
+            ],
+            executed_on: {registers: {a0: 64}, memory: {64: 42}},
+            results_in: {registers: {a0: 64}, memory: {64: 42}, pc: 12},
+        );
+    }
+
+    #[test]
+    fn execute_mixed_executables() {
+        /// A custom instruction doubling a register.
+        struct Double(Register);
+
+        impl Executable for Double {
+            fn execute(&self, processor: &mut Processor<i32, CSR32>) -> Result<(), Exception> {
+                processor.registers[self.0] *= 2;
+                processor.pc += 4;
+                Ok(())
+            }
+        }
+
+        let programme: Vec<Box<dyn Executable>> = vec![
+            Box::new(Instruction::ADDI {
+                rd: Register::A0,
+                rs1: Register::ZERO,
+                imm: 3,
+            }),
+            Box::new(Double(Register::A0)),
+            Box::new(Instruction::ADDI {
+                rd: Register::A0,
+                rs1: Register::A0,
+                imm: 1,
+            }),
+        ];
+        let mut processor = processor_state!({registers: {a0: 0}});
+        for instruction in &programme {
+            instruction.execute(&mut processor).unwrap();
+        }
+
+        assert_eq!(processor, processor_state!({registers: {a0: 7}, pc: 12}));
+    }
+
+    #[test]
+    fn execute_advances_by_instruction_size() {
+        use Register::*;
+        for instruction in [
+            Instruction::LUI { rd: A0, imm: 1 },
+            Instruction::AUIPC { rd: A0, imm: 1 },
+            Instruction::ADDI {
+                rd: A0,
+                rs1: A1,
+                imm: 1,
+            },
+            Instruction::SLTI {
+                rd: A0,
+                rs1: A1,
+                imm: 1,
+            },
+            Instruction::XORI {
+                rd: A0,
+                rs1: A1,
+                imm: 1,
+            },
+            Instruction::SLLI {
+                rd: A0,
+                rs1: A1,
+                shamt: 1,
+            },
+            Instruction::ADD {
+                rd: A0,
+                rs1: A1,
+                rs2: A2,
+            },
+            Instruction::SUB {
+                rd: A0,
+                rs1: A1,
+                rs2: A2,
+            },
+            Instruction::SRA {
+                rd: A0,
+                rs1: A1,
+                rs2: A2,
+            },
+            Instruction::MUL {
+                rd: A0,
+                rs1: A1,
+                rs2: A2,
+            },
+            Instruction::DIV {
+                rd: A0,
+                rs1: A1,
+                rs2: A2,
+            },
+            Instruction::REMU {
+                rd: A0,
+                rs1: A1,
+                rs2: A2,
+            },
+            Instruction::LB {
+                rd: A0,
+                rs1: SP,
+                offset: 0,
+            },
+            Instruction::LW {
+                rd: A0,
+                rs1: SP,
+                offset: 4,
+            },
+            Instruction::SH {
+                rs1: SP,
+                rs2: A1,
+                offset: 2,
+            },
+            Instruction::SW {
+                rs1: SP,
+                rs2: A1,
+                offset: 8,
+            },
+            Instruction::CSRRW {
+                rd: A0,
+                rs1: A1,
+                csr: 0x340,
+            },
+            Instruction::FENCE { pred: 0, succ: 0 },
+            Instruction::FENCE_I,
+            Instruction::BEQ {
+                rs1: A0,
+                rs2: A1,
+                offset: 64,
+            },
+            Instruction::BLT {
+                rs1: A1,
+                rs2: A0,
+                offset: 64,
+            },
+            Instruction::C_ADDI4SPN { rd: A0, imm: 4 },
+            Instruction::C_ADDI16SP { imm: 16 },
+            Instruction::C_LWSP { rd: A0, offset: 4 },
+            Instruction::C_SWSP { rs2: A1, offset: 4 },
+        ] {
+            let mut processor = Processor::<i32, CSR32>::default();
+            processor.store_words(0, &[0; 16]);
+            processor.registers[A0] = 1;
+            processor.registers[A1] = 2;
+            processor.registers[SP] = 16;
+            processor.pc = 8;
+
+            assert_eq!(instruction.execute(&mut processor), Ok(()));
+            assert_eq!(
+                processor.pc,
+                8 + instruction.size_in_bytes() as i32,
+                "{instruction}"
+            );
+        }
+    }
+
+    #[test]
+    fn execute_control_flow_sets_pc() {
+        use Register::*;
+        for (instruction, pc) in [
+            (Instruction::JAL { rd: RA, offset: -8 }, 0),
+            (
+                Instruction::JALR {
+                    rd: RA,
+                    rs1: A1,
+                    offset: 2,
+                },
+                4,
+            ),
+            (
+                Instruction::BEQ {
+                    rs1: A0,
+                    rs2: A0,
+                    offset: 16,
+                },
+                24,
+            ),
+            (
+                Instruction::BNE {
+                    rs1: A0,
+                    rs2: A1,
+                    offset: -4,
+                },
+                4,
+            ),
+            (
+                Instruction::BGEU {
+                    rs1: A1,
+                    rs2: A0,
+                    offset: 8,
+                },
+                16,
+            ),
+            (Instruction::C_JAL { offset: 8 }, 16),
+        ] {
+            let mut processor = Processor::<i32, CSR32>::default();
+            processor.registers[A0] = 1;
+            processor.registers[A1] = 2;
+            processor.pc = 8;
+
+            assert_eq!(instruction.execute(&mut processor), Ok(()));
+            assert_eq!(processor.pc, pc, "{instruction}");
+        }
+    }
+
+    #[test]
+    fn execute_nops() {
+        test_execute!(
+            [Instruction::NOP, Instruction::NOP, Instruction::NOP].into_iter().flatten(),
+            executed_on: {registers: {a0: 1, t1: -1}, memory: {64: 42}},
+            results_in: {registers: {a0: 1, t1: -1}, memory: {64: 42}, pc: 12},
+        );
+    }
+
+    #[test]
+    fn execute_lw_paged() {
+        // The root page table at page 1 points to a second level table at
+        // page 2, which maps the virtual page 0x0040_0000 to page 3.
+        test_execute!(
+            Instruction::LW { rd: Register::T3, rs1: Register::T1, offset: 4, },
+            executed_on: {
+                registers: {t1: 0x0040_0000},
+                memory: {0x1004: 0x801, 0x2000: 0xC07, 0x3004: 42},
+                csr: {0x180: 0x8000_0001_u32 as i32},
+                privilege: Privilege::Supervisor
+            },
+            results_in: {
+                registers: {t1: 0x0040_0000, t3: 42},
+                memory: {0x1004: 0x801, 0x2000: 0xC07, 0x3004: 42},
+                csr: {0x180: 0x8000_0001_u32 as i32},
+                pc: 4,
+                privilege: Privilege::Supervisor
+            },
+        );
+        test_execute!(
+            Instruction::LW { rd: Register::T3, rs1: Register::T1, offset: 4, },
+            executed_on: {
+                registers: {t1: 0x0040_1000},
+                memory: {0x1004: 0x801, 0x2000: 0xC07, 0x3004: 42},
+                csr: {0x180: 0x8000_0001_u32 as i32},
+                privilege: Privilege::Supervisor
+            },
+            throws: Exception::LoadPageFault,
+        );
+        // Machine mode addresses are not translated.
+        test_execute!(
+            Instruction::LW { rd: Register::T3, rs1: Register::T1, offset: 4, },
+            executed_on: {
+                registers: {t1: 0x3000},
+                memory: {0x1004: 0x801, 0x2000: 0xC07, 0x3004: 42},
+                csr: {0x180: 0x8000_0001_u32 as i32}
+            },
+            results_in: {
+                registers: {t1: 0x3000, t3: 42},
+                memory: {0x1004: 0x801, 0x2000: 0xC07, 0x3004: 42},
+                csr: {0x180: 0x8000_0001_u32 as i32},
+                pc: 4
+            },
+        );
+    }
+
+    #[test]
+    fn execute_mret() {
+        // mstatus has MPIE set and MPP = S.
+        test_execute!(
+            Instruction::MRET,
+            executed_on: {registers: {}, csr: {0x300: 0b01_000_1000_0000, 0x341: 64}, pc: 8},
+            results_in: {
+                registers: {},
+                csr: {0x300: 0b00_000_1000_1000, 0x341: 64},
+                pc: 64,
+                privilege: Privilege::Supervisor
+            },
+        );
+    }
+
+    #[test]
+    fn execute_mret_below_machine_mode() {
+        for privilege in [Privilege::User, Privilege::Supervisor] {
+            test_execute!(
+                Instruction::MRET,
+                executed_on: {
+                    registers: {},
+                    csr: {0x300: 0b11_000_1000_0000, 0x341: 64},
+                    pc: 8,
+                    privilege: privilege
+                },
+                throws: Exception::UnimplementedInstruction(Instruction::MRET.encode()),
+            );
+        }
+    }
+
+    #[test]
+    fn execute_sret_trapped() {
+        // SRET is illegal in user mode.
+        test_execute!(
+            Instruction::SRET,
+            executed_on: {
+                registers: {},
+                csr: {0x100: 0b1_0010_0000, 0x141: 64},
+                pc: 8,
+                privilege: Privilege::User
+            },
+            throws: Exception::UnimplementedInstruction(Instruction::SRET.encode()),
+        );
+        // mstatus has TSR set.
+        test_execute!(
+            Instruction::SRET,
+            executed_on: {
+                registers: {},
+                csr: {0x300: 0b100_0000_0000_0001_0010_0000, 0x141: 64},
+                pc: 8,
+                privilege: Privilege::Supervisor
+            },
+            throws: Exception::UnimplementedInstruction(Instruction::SRET.encode()),
+        );
+        // TSR does not trap SRET in machine mode.
+        test_execute!(
+            Instruction::SRET,
+            executed_on: {
+                registers: {},
+                csr: {0x300: 0b100_0000_0000_0000_0010_0000, 0x141: 64},
+                pc: 8,
+                privilege: Privilege::Machine
+            },
+            results_in: {
+                registers: {},
+                csr: {0x300: 0b100_0000_0000_0000_0010_0010, 0x141: 64},
+                pc: 64,
+                privilege: Privilege::User
+            },
+        );
+    }
+
+    #[test]
+    fn execute_sret() {
+        // sstatus has SPIE set and SPP = U.
+        test_execute!(
+            Instruction::SRET,
+            executed_on: {
+                registers: {},
+                csr: {0x100: 0b0_0010_0000, 0x141: 64},
+                pc: 8,
+                privilege: Privilege::Supervisor
+            },
+            results_in: {
+                registers: {},
+                csr: {0x100: 0b0_0010_0010, 0x141: 64},
+                pc: 64,
+                privilege: Privilege::User
+            },
+        );
+        // sstatus has SPP = S.
+        test_execute!(
+            Instruction::SRET,
+            executed_on: {
+                registers: {},
+                csr: {0x100: 0b1_0000_0000, 0x141: 64},
+                pc: 8,
+                privilege: Privilege::Supervisor
+            },
+            results_in: {
+                registers: {},
+                csr: {0x100: 0b0_0010_0000, 0x141: 64},
+                pc: 64,
+                privilege: Privilege::Supervisor
+            },
+        );
+    }
+
+    #[test]
+    fn execute_load_negative_address() {
+        test_execute!(
+            Instruction::LW { rd: Register::T3, rs1: Register::T1, offset: -4, },
+            executed_on: {registers: {t1: 0}},
+            throws: Exception::MemoryAccessFault,
+        );
+        test_execute!(
+            Instruction::LBU { rd: Register::T3, rs1: Register::T1, offset: 0, },
+            executed_on: {registers: {t1: i32::MIN}},
+            throws: Exception::MemoryAccessFault,
+        );
+    }
+
+    #[test]
+    fn execute_store_negative_address() {
+        test_execute!(
+            Instruction::SB { rs1: Register::T1, rs2: Register::T3, offset: -1, },
+            executed_on: {registers: {t1: 0, t3: 1}},
+            throws: Exception::StoreAccessFault,
+        );
+    }
+
+    #[test]
+    fn execute_at_high_address_on_sparse_memory() {
+        let mut processor = Processor::<i32, CSR32, _>::from_parts(
+            Registers::default(),
+            0,
+            CSR32::default(),
+            SparseMemory::default(),
+        );
+        processor.registers[Register::T1] = 0x8000_0000_u32 as i32;
+        processor.registers[Register::T3] = 42;
+
+        Instruction::SW {
+            rs1: Register::T1,
+            rs2: Register::T3,
+            offset: 0,
+        }
+        .execute(&mut processor)
+        .unwrap();
+        Instruction::LW {
+            rd: Register::T4,
+            rs1: Register::T1,
+            offset: 0,
+        }
+        .execute(&mut processor)
+        .unwrap();
+
+        assert_eq!(processor.registers[Register::T4], 42);
+        assert_eq!(processor.memory.load_word(0x8000_0000), 42);
+        assert_eq!(processor.memory.page_count(), 1);
+    }
+
+    #[test]
+    fn wrapping_effective_address_faults_on_sparse_memory() {
+        let mut processor = Processor::<i32, CSR32, _>::from_parts(
+            Registers::default(),
+            0,
+            CSR32::default(),
+            SparseMemory::default(),
+        );
+        processor.registers[Register::T1] = 0;
+        processor.registers[Register::T2] = -4;
+
+        let load = Instruction::LW {
+            rd: Register::T4,
+            rs1: Register::T1,
+            offset: -4,
+        }
+        .execute(&mut processor);
+        let store = Instruction::SW {
+            rs1: Register::T2,
+            rs2: Register::T3,
+            offset: 8,
+        }
+        .execute(&mut processor);
+
+        assert_eq!(load, Err(Exception::MemoryAccessFault));
+        assert_eq!(store, Err(Exception::StoreAccessFault));
+        assert_eq!(processor.memory.page_count(), 0);
+    }
+
+    #[test]
+    fn execute_unaligned_across_pages() {
+        let lw = Instruction::LW {
+            rd: Register::T4,
+            rs1: Register::T1,
+            offset: 0,
+        };
+        let sw = Instruction::SW {
+            rs1: Register::T1,
+            rs2: Register::T3,
+            offset: 0,
+        };
+
+        // The virtual pages are mapped to adjacent physical pages.
+        let mut processor = paged_processor(&[5, 6]);
+        processor.registers[Register::T1] = 0x0040_0ffe;
+        processor.registers[Register::T3] = 0x1234_5678;
+        sw.execute(&mut processor).unwrap();
+        lw.execute(&mut processor).unwrap();
+        assert_eq!(processor.registers[Register::T4], 0x1234_5678);
+        assert_eq!(processor.memory.load_half(0x5ffe), 0x5678);
+        assert_eq!(processor.memory.load_half(0x6000), 0x1234);
+
+        // The virtual pages are mapped to non-adjacent physical pages.
+        let mut processor = paged_processor(&[5, 3]);
+        processor.registers[Register::T1] = 0x0040_0ffe;
+        assert_eq!(
+            sw.execute(&mut processor),
+            Err(Exception::StoreAddressMisaligned(0x0040_0ffe))
+        );
+        assert_eq!(
+            lw.execute(&mut processor),
+            Err(Exception::LoadAddressMisaligned(0x0040_0ffe))
+        );
+        assert_eq!(processor.memory.load_word(0x5ffc), 0);
+        assert_eq!(processor.memory.load_word(0x6000), 0);
+
+        // The second virtual page is not mapped.
+        let mut processor = paged_processor(&[5]);
+        processor.registers[Register::T1] = 0x0040_0ffe;
+        assert_eq!(lw.execute(&mut processor), Err(Exception::LoadPageFault));
+    }
+
+    #[test]
+    fn execute_translated_to_high_physical_address() {
+        let mut processor = paged_processor(&[0x8_0000]);
+        processor.registers[Register::T1] = 0x0040_0010;
+        processor.registers[Register::T3] = 42;
+
+        Instruction::SW {
+            rs1: Register::T1,
+            rs2: Register::T3,
+            offset: 0,
+        }
+        .execute(&mut processor)
+        .unwrap();
+        Instruction::LW {
+            rd: Register::T4,
+            rs1: Register::T1,
+            offset: 0,
+        }
+        .execute(&mut processor)
+        .unwrap();
+
+        assert_eq!(processor.registers[Register::T4], 42);
+        assert_eq!(processor.memory.load_word(0x8000_0010), 42);
+    }
+
+    #[test]
+    fn execute_sw_protected() {
+        let mut processor = processor_state!(registers: {t1: 64, t3: 12});
+        processor.memory.protect(0..64, Protection::ReadOnly);
+
+        let result = Instruction::SW {
+            rs1: Register::T1,
+            rs2: Register::T3,
+            offset: -4,
+        }
+        .execute(&mut processor);
+        assert_eq!(result, Err(Exception::StoreAccessFault));
+        assert_eq!(processor.memory.load_word(60), 0);
+        assert_eq!(processor.pc, 0);
+
+        let result = Instruction::SW {
+            rs1: Register::T1,
+            rs2: Register::T3,
+            offset: 0,
+        }
+        .execute(&mut processor);
+        assert_eq!(result, Ok(()));
+        assert_eq!(processor.memory.load_word(64), 12);
+        assert_eq!(processor.pc, 4);
+    }
+
+    #[test]
+    fn execute_unaligned_emulate() {
+        let mut processor = processor_state!(
+            registers: {t1: 1}, memory: {0: 0x4433_2211, 4: 0x0066_5544}
+        );
+        processor.set_unaligned_policy(UnalignedPolicy::Emulate);
+
+        Instruction::LW {
+            rd: Register::T3,
+            rs1: Register::T1,
+            offset: 0,
+        }
+        .execute(&mut processor)
+        .unwrap();
+        assert_eq!(processor.registers[Register::T3], 0x4444_3322);
+
+        Instruction::SH {
+            rs1: Register::T1,
+            rs2: Register::T3,
+            offset: 2,
+        }
+        .execute(&mut processor)
+        .unwrap();
+        assert_eq!(processor.memory.load_word(0), 0x2233_2211);
+        assert_eq!(processor.memory.load_half(4), 0x5533);
+    }
+
+    #[test]
+    fn double_address_unaligned_trap() {
+        // 64-bit accesses share the alignment and readability checks of the
+        // narrower accesses.
+        let mut processor = processor_state!(registers: {t1: 1});
+        processor.set_unaligned_policy(UnalignedPolicy::Trap);
+
+        assert_eq!(
+            Instruction::load_address::<8>(&mut processor, Register::T1, 3),
+            Err(Exception::LoadAddressMisaligned(4))
+        );
+        assert_eq!(
+            Instruction::physical_address::<8>(&mut processor, Register::T1, 11, Access::Store),
+            Err(Exception::StoreAddressMisaligned(12))
+        );
+        assert_eq!(
+            Instruction::load_address::<8>(&mut processor, Register::T1, 7),
+            Ok(8)
+        );
+        processor.memory.protect(12..16, Protection::NoAccess);
+        assert_eq!(
+            Instruction::load_address::<8>(&mut processor, Register::T1, 7),
+            Err(Exception::MemoryAccessFault)
+        );
+    }
+
+    #[test]
+    fn execute_unaligned_trap() {
+        let mut processor = processor_state!(registers: {t1: 1}, memory: {0: 0x4433_2211});
+        processor.set_unaligned_policy(UnalignedPolicy::Trap);
+
+        let result = Instruction::LW {
+            rd: Register::T3,
+            rs1: Register::T1,
+            offset: 1,
+        }
+        .execute(&mut processor);
+        assert_eq!(result, Err(Exception::LoadAddressMisaligned(2)));
+        let result = Instruction::LHU {
+            rd: Register::T3,
+            rs1: Register::T1,
+            offset: 0,
+        }
+        .execute(&mut processor);
+        assert_eq!(result, Err(Exception::LoadAddressMisaligned(1)));
+        let result = Instruction::SW {
+            rs1: Register::T1,
+            rs2: Register::T1,
+            offset: 2,
+        }
+        .execute(&mut processor);
+        assert_eq!(result, Err(Exception::StoreAddressMisaligned(3)));
+        assert_eq!(processor.registers[Register::T3], 0);
+        assert_eq!(processor.pc, 0);
+
+        // Byte accesses and aligned accesses are unaffected.
+        Instruction::LBU {
+            rd: Register::T3,
+            rs1: Register::T1,
+            offset: 0,
+        }
+        .execute(&mut processor)
+        .unwrap();
+        assert_eq!(processor.registers[Register::T3], 0x22);
+        Instruction::LW {
+            rd: Register::T3,
+            rs1: Register::T1,
+            offset: -1,
+        }
+        .execute(&mut processor)
+        .unwrap();
+        assert_eq!(processor.registers[Register::T3], 0x4433_2211);
+    }
+
+    #[test]
+    fn execute_null_guard_page() {
+        let mut processor = processor_state!(registers: {t1: 0, t3: 12});
+        processor.memory.protect(0..4096, Protection::NoAccess);
+
+        let result = Instruction::SW {
+            rs1: Register::T1,
+            rs2: Register::T3,
+            offset: 0,
+        }
+        .execute(&mut processor);
+        assert_eq!(result, Err(Exception::StoreAccessFault));
+
+        let result = Instruction::LW {
+            rd: Register::T3,
+            rs1: Register::T1,
+            offset: 0,
+        }
+        .execute(&mut processor);
+        assert_eq!(result, Err(Exception::MemoryAccessFault));
+        assert_eq!(processor.registers[Register::T3], 12);
+        assert_eq!(processor.pc, 0);
+
+        let result = Instruction::SW {
+            rs1: Register::T1,
+            rs2: Register::T3,
+            offset: 2047,
+        }
+        .execute(&mut processor);
+        assert_eq!(result, Err(Exception::StoreAccessFault));
+        processor.registers[Register::T1] = 4096;
+        let result = Instruction::SW {
+            rs1: Register::T1,
+            rs2: Register::T3,
+            offset: 0,
+        }
+        .execute(&mut processor);
+        assert_eq!(result, Ok(()));
+        assert_eq!(processor.memory.load_word(4096), 12);
+    }
+
+    #[test]
+    fn execute_null_mmio() {
+        let mut processor = Processor::<i32, CSR32, _>::from_parts(
+            Registers::default(),
+            0,
+            CSR32::default(),
+            ConsoleDevice::new(Memory::default(), 0),
+        );
+        processor.registers[Register::T3] = '!' as i32;
+
+        Instruction::SW {
+            rs1: Register::ZERO,
+            rs2: Register::T3,
+            offset: 0,
+        }
+        .execute(&mut processor)
+        .unwrap();
+
+        assert_eq!(processor.memory.output(), "!");
+        assert_eq!(processor.memory.memory().peek_word(0), 0);
+    }
+
     #[test]
     fn execute_csrrw() {
         test_execute!(
@@ -987,6 +2298,39 @@ mod test {
         );
     }
 
+    #[test]
+    fn execute_ecall() {
+        test_execute!(
+            Instruction::ECALL,
+            executed_on: {registers: {a7: 93}, pc: 8},
+            throws: Exception::EnvironmentCall(Privilege::Machine),
+        );
+        test_execute!(
+            Instruction::ECALL,
+            executed_on: {registers: {a7: 93}, privilege: Privilege::User},
+            throws: Exception::EnvironmentCall(Privilege::User),
+        );
+    }
+
+    #[test]
+    fn execute_rdcycle_rdtime_rdinstret() {
+        test_execute!(
+            Instruction::RDCYCLE(Register::A0),
+            executed_on: {registers: {a0: 0}, csr: {0xC00: 12}},
+            results_in: {registers: {a0: 12}, csr: {0xC00: 12}, pc: 4},
+        );
+        test_execute!(
+            Instruction::RDTIMEH(Register::A0),
+            executed_on: {registers: {a0: 0}, csr: {0xC81: 3}},
+            results_in: {registers: {a0: 3}, csr: {0xC81: 3}, pc: 4},
+        );
+        test_execute!(
+            Instruction::RDINSTRET(Register::A0),
+            executed_on: {registers: {a0: 0}, csr: {0xC02: 7}},
+            results_in: {registers: {a0: 7}, csr: {0xC02: 7}, pc: 4},
+        );
+    }
+
     #[test]
     fn execute_csrr() {
         test_execute!(
@@ -1133,6 +2477,40 @@ mod test {
         );
     }
 
+    #[test]
+    fn execute_csr_immediate_zero_only_reads() {
+        // Writing the read-only `cycle` CSR faults, so it can only be read
+        // with a zero immediate.
+        test_execute!(
+            instructions![
+                Instruction::CSRRSI { rd: Register::A0, imm: 0, csr: 0xC00 },
+                Instruction::CSRRCI { rd: Register::A1, imm: 0, csr: 0xC00 },
+            ],
+            executed_on: {registers: {a0: 3}, csr: {0xC00: 12}},
+            results_in: {registers: {a0: 12, a1: 12}, csr: {0xC00: 12}, pc: 8},
+        );
+        let csrrsi = Instruction::CSRRSI {
+            rd: Register::A0,
+            imm: 1,
+            csr: 0xC00,
+        };
+        test_execute!(
+            csrrsi,
+            executed_on: {registers: {a0: 3}, csr: {0xC00: 12}},
+            throws: Exception::UnimplementedInstruction(csrrsi.encode()),
+        );
+        let csrrci = Instruction::CSRRCI {
+            rd: Register::A0,
+            imm: 4,
+            csr: 0xC00,
+        };
+        test_execute!(
+            csrrci,
+            executed_on: {registers: {a0: 3}, csr: {0xC00: 12}},
+            throws: Exception::UnimplementedInstruction(csrrci.encode()),
+        );
+    }
+
     #[test]
     fn execute_csrwi() {
         test_execute!(
@@ -1731,4 +3109,77 @@ mod test {
             results_in: {registers: {ra: 41, s3: -43}, pc: 8 },
         );
     }
+
+    /// The division instructions of the M extension never trap, instead the
+    /// results of dividing by zero and of the overflowing signed division are
+    /// defined by the specification.
+    mod division {
+        use super::*;
+        use crate::{csr::CSR32, processor::Processor};
+        use pretty_assertions::assert_eq;
+
+        /// Execute the `instruction` dividing `dividend` by `divisor`,
+        /// returning the result.
+        fn divide(
+            instruction: fn(Register, Register, Register) -> Instruction,
+            dividend: i32,
+            divisor: i32,
+        ) -> i32 {
+            let mut processor: Processor<i32, CSR32> =
+                processor_state!(registers: {a0: dividend, a1: divisor});
+            instruction(Register::A2, Register::A0, Register::A1)
+                .execute(&mut processor)
+                .unwrap();
+            processor.register(Register::A2)
+        }
+
+        /// Constructs a `DIV` instruction.
+        fn div(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+            Instruction::DIV { rd, rs1, rs2 }
+        }
+
+        /// Constructs a `DIVU` instruction.
+        fn divu(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+            Instruction::DIVU { rd, rs1, rs2 }
+        }
+
+        /// Constructs a `REM` instruction.
+        fn rem(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+            Instruction::REM { rd, rs1, rs2 }
+        }
+
+        /// Constructs a `REMU` instruction.
+        fn remu(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+            Instruction::REMU { rd, rs1, rs2 }
+        }
+
+        #[test]
+        fn divide_by_zero() {
+            for dividend in [0, 1, -1, 42, i32::MIN, i32::MAX] {
+                assert_eq!(divide(div, dividend, 0), -1, "div {dividend}");
+                assert_eq!(divide(divu, dividend, 0), -1, "divu {dividend}");
+                assert_eq!(divide(rem, dividend, 0), dividend, "rem {dividend}");
+                assert_eq!(divide(remu, dividend, 0), dividend, "remu {dividend}");
+            }
+        }
+
+        #[test]
+        fn signed_overflow() {
+            assert_eq!(divide(div, i32::MIN, -1), i32::MIN);
+            assert_eq!(divide(rem, i32::MIN, -1), 0);
+            // As unsigned values the divisor is larger than the dividend.
+            assert_eq!(divide(divu, i32::MIN, -1), 0);
+            assert_eq!(divide(remu, i32::MIN, -1), i32::MIN);
+        }
+
+        #[test]
+        fn rounds_towards_zero() {
+            assert_eq!(divide(div, -7, 2), -3);
+            assert_eq!(divide(rem, -7, 2), -1);
+            assert_eq!(divide(div, 7, -2), -3);
+            assert_eq!(divide(rem, 7, -2), 1);
+            assert_eq!(divide(div, -7, -2), 3);
+            assert_eq!(divide(rem, -7, -2), -1);
+        }
+    }
 }
@@ -7,8 +7,12 @@
 //! an encoded instruction.
 #![allow(clippy::unusual_byte_groupings, clippy::upper_case_acronyms)]
 mod bimm;
+mod compressed;
+mod control_flow;
 mod csr;
 mod csr_imm;
+mod disassemble;
+pub mod encoding;
 mod funct3;
 mod funct6;
 mod funct7;
@@ -17,6 +21,7 @@ mod immu;
 mod impl_instruction_set;
 mod jimm;
 mod pseudoinstructions;
+mod raw;
 mod rd;
 mod rs1;
 mod rs2;
@@ -24,20 +29,31 @@ mod shamt;
 mod simmi;
 mod types;
 
+pub use self::{
+    control_flow::{EncodeError, Successor, Successors},
+    disassemble::disassemble,
+    pseudoinstructions::PseudoinstructionMappingIter,
+    raw::RawInstruction,
+};
+
 use self::{
     bimm::BImm, csr::Csr, csr_imm::CsrImm, funct3::Funct3, funct6::Funct6, funct7::Funct7,
     immi::ImmI, immu::ImmU, jimm::JImm, rd::Rd, rs1::Rs1, rs2::Rs2, shamt::Shamt, simmi::SImmI,
 };
 
-use crate::{instruction_set::Exception, registers::Register};
+use crate::{
+    instruction_set::Exception,
+    isa::{Extension, Isa},
+    memory::MemoryBus,
+    registers::{Register, Registers},
+};
 
 /// An representation of different instructions.
 ///
 /// Would we like to work with the raw bytes of the instructions, or simply provide a mechanism to
 /// convert to the raw bytes.
-#[cfg_attr(test, derive(Debug, PartialEq, Eq))]
-#[derive(Clone, Copy)]
-pub(super) enum Instruction {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Instruction {
     /// # Load Upper Immediate
     ///
     /// Build 32-bit constants and uses the U-type format. LUI places the U-immediate value in the
@@ -361,6 +377,140 @@ pub(super) enum Instruction {
         rs2: Register,
     },
 
+    /// # Multiply
+    ///
+    /// Multiply registers rs1 and rs2 and place the lower 32 bits of the
+    /// product in rd.
+    ///
+    /// `rd = (rs1 * rs2)[31:0]`
+    MUL {
+        /// The destination register
+        rd: Register,
+        /// Source register 1.
+        rs1: Register,
+        /// Source register 2.
+        rs2: Register,
+    },
+
+    /// # Multiply High
+    ///
+    /// Multiply registers rs1 and rs2, using signed multiplication, and place
+    /// the upper 32 bits of the product in rd.
+    ///
+    /// `rd = (sext(rs1) * sext(rs2))[63:32]`
+    MULH {
+        /// The destination register
+        rd: Register,
+        /// Source register 1.
+        rs1: Register,
+        /// Source register 2.
+        rs2: Register,
+    },
+
+    /// # Multiply High Signed Unsigned
+    ///
+    /// Multiply the signed register rs1 by the unsigned register rs2 and place
+    /// the upper 32 bits of the product in rd.
+    ///
+    /// `rd = (sext(rs1) * zext(rs2))[63:32]`
+    MULHSU {
+        /// The destination register
+        rd: Register,
+        /// Source register 1.
+        rs1: Register,
+        /// Source register 2.
+        rs2: Register,
+    },
+
+    /// # Multiply High Unsigned
+    ///
+    /// Multiply registers rs1 and rs2, using unsigned multiplication, and place
+    /// the upper 32 bits of the product in rd.
+    ///
+    /// `rd = (zext(rs1) * zext(rs2))[63:32]`
+    MULHU {
+        /// The destination register
+        rd: Register,
+        /// Source register 1.
+        rs1: Register,
+        /// Source register 2.
+        rs2: Register,
+    },
+
+    /// # Divide
+    ///
+    /// Divide register rs1 by rs2, using signed division rounding towards
+    /// zero, and place the quotient in rd.
+    ///
+    /// `rd = rs1 / rs2`
+    ///
+    /// _Note_: division by zero does not trap, instead the quotient is `-1`.
+    /// The overflowing division of the most negative integer by `-1` results
+    /// in the most negative integer.
+    DIV {
+        /// The destination register
+        rd: Register,
+        /// Source register 1.
+        rs1: Register,
+        /// Source register 2.
+        rs2: Register,
+    },
+
+    /// # Divide Unsigned
+    ///
+    /// Divide register rs1 by rs2, using unsigned division, and place the
+    /// quotient in rd.
+    ///
+    /// `rd = rs1 /u rs2`
+    ///
+    /// _Note_: division by zero does not trap, instead the quotient has all
+    /// bits set.
+    DIVU {
+        /// The destination register
+        rd: Register,
+        /// Source register 1.
+        rs1: Register,
+        /// Source register 2.
+        rs2: Register,
+    },
+
+    /// # Remainder
+    ///
+    /// Place the remainder of the signed division of register rs1 by rs2 in rd.
+    /// The sign of the remainder is the sign of the dividend.
+    ///
+    /// `rd = rs1 % rs2`
+    ///
+    /// _Note_: division by zero does not trap, instead the remainder is the
+    /// dividend. The overflowing division of the most negative integer by
+    /// `-1` results in a remainder of zero.
+    REM {
+        /// The destination register
+        rd: Register,
+        /// Source register 1.
+        rs1: Register,
+        /// Source register 2.
+        rs2: Register,
+    },
+
+    /// # Remainder Unsigned
+    ///
+    /// Place the remainder of the unsigned division of register rs1 by rs2 in
+    /// rd.
+    ///
+    /// `rd = rs1 %u rs2`
+    ///
+    /// _Note_: division by zero does not trap, instead the remainder is the
+    /// dividend.
+    REMU {
+        /// The destination register
+        rd: Register,
+        /// Source register 1.
+        rs1: Register,
+        /// Source register 2.
+        rs2: Register,
+    },
+
     /// # Load Byte
     ///
     /// Loads a 8-bit value from memory and sign-extends this to XLEN bits before storing it in
@@ -580,6 +730,99 @@ pub(super) enum Instruction {
         imm: u8,
     },
 
+    /// # Fence
+    ///
+    /// Order the memory and I/O accesses in the predecessor set before those
+    /// in the successor set, as seen by other harts and devices. Each set is
+    /// 4 bits, device input `i`, device output `o`, memory reads `r`, and
+    /// memory writes `w`, from the highest bit.
+    ///
+    /// _Note_: There is a single hart whose accesses are performed in
+    /// programme order so this is a no-op.
+    FENCE {
+        /// The predecessor set, `iorw`.
+        pred: u8,
+        /// The successor set, `iorw`.
+        succ: u8,
+    },
+
+    /// # Total store ordering fence
+    ///
+    /// Order the memory reads and writes before the fence before those after
+    /// it, except for writes before it and reads after it. This is the fence
+    /// with the `fm` field set to `TSO`, and predecessor and successor sets
+    /// `rw`.
+    ///
+    /// _Note_: There is a single hart whose accesses are performed in
+    /// programme order so this is a no-op.
+    #[allow(non_camel_case_types)]
+    FENCE_TSO,
+
+    /// # Instruction fence
+    ///
+    /// Synchronise the instruction and data streams, so that stores are seen
+    /// by subsequent instruction fetches.
+    ///
+    /// _Note_: Instructions are fetched from memory each step so this is a
+    /// no-op.
+    #[allow(non_camel_case_types)]
+    FENCE_I,
+
+    /// # Supervisor memory-management fence
+    ///
+    /// Synchronise updates to in-memory memory-management data structures
+    /// with current execution. rs1 optionally selects a virtual address and
+    /// rs2 an address space to which the fence applies.
+    ///
+    /// _Note_: Address translations are not cached so this is a no-op.
+    #[allow(non_camel_case_types)]
+    SFENCE_VMA {
+        /// Source register 1, the virtual address.
+        rs1: Register,
+        /// Source register 2, the address space identifier.
+        rs2: Register,
+    },
+
+    /// # Environment call
+    ///
+    /// Make a request to the execution environment, raising
+    /// [Exception::EnvironmentCall]. By convention the request is selected by
+    /// `a7`, with `93` requesting the programme exit with the status in `a0`,
+    /// see [crate::processor::ExecutionResult::Exited].
+    ECALL,
+
+    /// # Environment breakpoint
+    ///
+    /// Return control to a debugging environment. What happens without a
+    /// debugger attached is configured by [crate::processor::EbreakPolicy].
+    EBREAK,
+
+    /// # Machine-mode trap return
+    ///
+    /// Return from a machine-mode trap handler. The programme counter is set
+    /// to `mepc`, the privilege level is restored from `mstatus.MPP`, and
+    /// `mstatus.MIE` is restored from `mstatus.MPIE`.
+    ///
+    /// `pc = mepc; privilege = MPP; MIE = MPIE; MPIE = 1; MPP = U`
+    MRET,
+
+    /// # Supervisor-mode trap return
+    ///
+    /// Return from a supervisor-mode trap handler. The programme counter is
+    /// set to `sepc`, the privilege level is restored from `sstatus.SPP`, and
+    /// `sstatus.SIE` is restored from `sstatus.SPIE`.
+    ///
+    /// `pc = sepc; privilege = SPP; SIE = SPIE; SPIE = 1; SPP = U`
+    SRET,
+
+    /// # Wait for interrupt
+    ///
+    /// Stall the hart until an interrupt might need servicing.
+    ///
+    /// _Note_: Interrupts are not yet supported, so this is a no-op, which
+    /// the specification permits.
+    WFI,
+
     /// # Jump and link
     ///
     /// Jump to address and place return address in rd.
@@ -756,15 +999,240 @@ pub(super) enum Instruction {
         /// `B`-immediate to the programme counter.
         offset: i16,
     },
+
+    /// # Compressed add immediate, scaled by 4, to stack pointer
+    ///
+    /// Add a zero-extended non-zero immediate, scaled by 4, to the stack
+    /// pointer and write the result to rd'. Used to generate pointers to
+    /// stack-allocated variables.
+    ///
+    /// `rd' = sp + nzuimm`, expanding to `addi rd', sp, nzuimm`
+    #[allow(non_camel_case_types)]
+    C_ADDI4SPN {
+        /// The destination register, one of `x8` to `x15`.
+        rd: Register,
+        /// The 10-bit non-zero immediate, a multiple of 4, zero-extended to
+        /// a [u16].
+        imm: u16,
+    },
+
+    /// # Compressed jump and link
+    ///
+    /// Jump to the programme counter plus a sign-extended offset and write
+    /// the address of the following instruction to `ra`.
+    ///
+    /// `ra = pc + 2; pc += offset`, expanding to `jal ra, offset`
+    ///
+    /// _Note_: this encoding is RV32 only, on RV64 it is `C.ADDIW`, see
+    /// [Instruction::decode_for].
+    #[allow(non_camel_case_types)]
+    C_JAL {
+        /// The 12-bit offset, a multiple of 2, sign-extended to an [i16].
+        offset: i16,
+    },
+
+    /// # Compressed load immediate
+    ///
+    /// Load a sign-extended 6-bit immediate into rd.
+    ///
+    /// `rd = imm`, expanding to `addi rd, zero, imm`
+    ///
+    /// _Note_: with `rd` `ZERO` (`x0`) this is a hint, which has no effect.
+    #[allow(non_camel_case_types)]
+    C_LI {
+        /// The destination register.
+        rd: Register,
+        /// The 6-bit immediate sign-extended to an [i8].
+        imm: i8,
+    },
+
+    /// # Compressed add immediate, scaled by 16, to stack pointer
+    ///
+    /// Add a sign-extended non-zero immediate, scaled by 16, to the stack
+    /// pointer. Used to adjust the stack pointer in procedure prologues and
+    /// epilogues.
+    ///
+    /// `sp += nzimm`, expanding to `addi sp, sp, nzimm`
+    #[allow(non_camel_case_types)]
+    C_ADDI16SP {
+        /// The 10-bit non-zero immediate, a multiple of 16, sign-extended to
+        /// an [i16].
+        imm: i16,
+    },
+
+    /// # Compressed load upper immediate
+    ///
+    /// Load a sign-extended non-zero 6-bit immediate into bits 17 to 12 of
+    /// rd, clearing the bottom 12 bits.
+    ///
+    /// `rd = nzimm << 12`, expanding to `lui rd, nzimm`
+    ///
+    /// _Note_: `rd` must not be `ZERO` (`x0`) or `SP` (`x2`), the encoding
+    /// with `rd` `SP` is [Instruction::C_ADDI16SP].
+    #[allow(non_camel_case_types)]
+    C_LUI {
+        /// The destination register.
+        rd: Register,
+        /// The 6-bit non-zero immediate sign-extended to an [i8].
+        imm: i8,
+    },
+
+    /// # Compressed load word, stack-pointer relative
+    ///
+    /// Load a 32-bit value from memory at the stack pointer plus a
+    /// zero-extended offset, scaled by 4, into rd.
+    ///
+    /// `rd = M[sp + uimm][31:0]`, expanding to `lw rd, uimm(sp)`
+    ///
+    /// _Note_: `rd` must not be `ZERO` (`x0`).
+    #[allow(non_camel_case_types)]
+    C_LWSP {
+        /// The destination register.
+        rd: Register,
+        /// The 8-bit offset, a multiple of 4, zero-extended to a [u8].
+        offset: u8,
+    },
+
+    /// # Compressed store word, stack-pointer relative
+    ///
+    /// Store the 32-bit value in rs2 to memory at the stack pointer plus a
+    /// zero-extended offset, scaled by 4.
+    ///
+    /// `M[sp + uimm] = rs2[31:0]`, expanding to `sw rs2, uimm(sp)`
+    #[allow(non_camel_case_types)]
+    C_SWSP {
+        /// The source register.
+        rs2: Register,
+        /// The 8-bit offset, a multiple of 4, zero-extended to a [u8].
+        offset: u8,
+    },
+}
+
+/// The mask of the lowest bits of the first parcel of an instruction which
+/// are all set for 32-bit instructions, and not all set for 16-bit compressed
+/// instructions.
+const LENGTH_MASK: u16 = 0b11;
+
+/// A decoded [Instruction] together with the word it was decoded from, see
+/// [Instruction::decode_with_raw].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodedInstruction {
+    /// The decoded instruction.
+    pub instruction: Instruction,
+    /// The word the instruction was decoded from.
+    ///
+    /// This may differ from the encoding of the instruction, since bits which
+    /// are ignored by the decoder, such as the upper half of a word holding a
+    /// compressed instruction, are not recovered by [Instruction::try_encode].
+    pub raw: u32,
 }
 
 impl Instruction {
+    /// The length in bytes of the instruction whose first 16-bit `parcel` is
+    /// given, 2 for a compressed instruction and 4 otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use riskv::instructions::Instruction;
+    ///
+    /// assert_eq!(Instruction::length_of(0x4501), 2); // c.li a0, 0
+    /// assert_eq!(Instruction::length_of(0x0513), 4); // addi a0, x0, 0
+    /// ```
+    #[inline]
+    pub const fn length_of(parcel: u16) -> usize {
+        if parcel & LENGTH_MASK != LENGTH_MASK {
+            2
+        } else {
+            4
+        }
+    }
+
+    /// Fetch and decode the instruction at `location` in `memory`, returning
+    /// the instruction and its length in bytes.
+    ///
+    /// The length is determined by the first 16-bit parcel, which is
+    /// fetched first so that a compressed instruction at the end of
+    /// executable memory can be decoded.
+    pub fn decode_at(
+        memory: &mut impl MemoryBus,
+        location: usize,
+    ) -> Result<(Self, usize), Exception> {
+        let parcel = memory.fetch_parcel(location)?;
+        if Self::length_of(parcel) == 2 {
+            return Ok((Self::decode_compressed(parcel)?, 2));
+        }
+        let raw_instruction = memory.fetch_instruction(location)?;
+        Ok((Self::decode(raw_instruction)?, 4))
+    }
+
+    /// Decode the `word` as an [Instruction] for a processor implementing the
+    /// `isa`.
+    ///
+    /// Some compressed encodings depend on the register width. On RV64
+    /// `C.JAL` is replaced by `C.ADDIW`, which is not implemented, so it
+    /// raises [Exception::UnimplementedInstruction].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use riskv::{instructions::Instruction, isa::Isa};
+    ///
+    /// let rv32: Isa = "rv32ic".parse().unwrap();
+    /// let rv64: Isa = "rv64ic".parse().unwrap();
+    /// assert_eq!(Instruction::decode_for(0x2001, &rv32), Ok(Instruction::C_JAL { offset: 0 }));
+    /// assert!(Instruction::decode_for(0x2001, &rv64).is_err());
+    /// ```
+    pub fn decode_for(word: u32, isa: &Isa) -> Result<Self, Exception> {
+        match Self::decode(word)? {
+            Instruction::C_JAL { .. } if isa.xlen() != 32 => {
+                Err(Exception::UnimplementedInstruction(word as u16 as u32))
+            }
+            instruction => Ok(instruction),
+        }
+    }
+
+    /// Decode the `word` as an [Instruction], preserving the `word` so that
+    /// the exact bits can be shown alongside the instruction.
+    pub const fn decode_with_raw(word: u32) -> Result<DecodedInstruction, Exception> {
+        match Self::decode(word) {
+            Ok(instruction) => Ok(DecodedInstruction {
+                instruction,
+                raw: word,
+            }),
+            Err(exception) => Err(exception),
+        }
+    }
+
+    /// Returns `true` if `word` is one of the encodings the specification
+    /// defines to be illegal, all zeros or all ones.
+    ///
+    /// These are what a programme counter which has run off into
+    /// uninitialised or erased memory usually fetches, so they are worth
+    /// singling out when reporting an illegal instruction.
+    pub const fn is_defined_illegal(word: u32) -> bool {
+        matches!(word, 0x0000_0000 | 0xffff_ffff)
+    }
+
     /// Decode a [u32] as an [Instruction].
     ///
     /// Instructions in RISC-V are encoded using little endian byte order.
     /// Therefore, to avoid unexpected results, ensure the [u32] is little endian.
+    ///
+    /// If the lowest bits mark a 16-bit compressed instruction, only the lower
+    /// half of `value` is decoded.
+    ///
+    /// The defined illegal encodings, see [Instruction::is_defined_illegal],
+    /// raise [Exception::UnimplementedInstruction], the illegal instruction
+    /// exception.
     #[inline]
     const fn decode(value: u32) -> Result<Self, Exception> {
+        if Self::is_defined_illegal(value) {
+            return Err(Exception::UnimplementedInstruction(value));
+        }
+        if Self::length_of(value as u16) == 2 {
+            return Self::decode_compressed(value as u16);
+        }
         let instruction = match Instruction::op_code(value) {
             0b_0110111 => Instruction::LUI {
                 rd: Rd::decode(value),
@@ -780,10 +1248,13 @@ impl Instruction {
                     rs1: Rs1::decode(value),
                     imm: ImmI::decode(value),
                 },
-                0b_001 => Instruction::SLLI {
-                    rd: Rd::decode(value),
-                    rs1: Rs1::decode(value),
-                    shamt: Shamt::decode(value),
+                0b_001 => match Funct6::decode(value) {
+                    0b_000000 => Instruction::SLLI {
+                        rd: Rd::decode(value),
+                        rs1: Rs1::decode(value),
+                        shamt: Shamt::decode(value),
+                    },
+                    _ => return Err(Exception::UnimplementedInstruction(value)),
                 },
                 0b_010 => Instruction::SLTI {
                     rd: Rd::decode(value),
@@ -876,6 +1347,46 @@ impl Instruction {
                     rs1: Rs1::decode(value),
                     rs2: Rs2::decode(value),
                 },
+                (0b_000, 0b_0000001) => Instruction::MUL {
+                    rd: Rd::decode(value),
+                    rs1: Rs1::decode(value),
+                    rs2: Rs2::decode(value),
+                },
+                (0b_001, 0b_0000001) => Instruction::MULH {
+                    rd: Rd::decode(value),
+                    rs1: Rs1::decode(value),
+                    rs2: Rs2::decode(value),
+                },
+                (0b_010, 0b_0000001) => Instruction::MULHSU {
+                    rd: Rd::decode(value),
+                    rs1: Rs1::decode(value),
+                    rs2: Rs2::decode(value),
+                },
+                (0b_011, 0b_0000001) => Instruction::MULHU {
+                    rd: Rd::decode(value),
+                    rs1: Rs1::decode(value),
+                    rs2: Rs2::decode(value),
+                },
+                (0b_100, 0b_0000001) => Instruction::DIV {
+                    rd: Rd::decode(value),
+                    rs1: Rs1::decode(value),
+                    rs2: Rs2::decode(value),
+                },
+                (0b_101, 0b_0000001) => Instruction::DIVU {
+                    rd: Rd::decode(value),
+                    rs1: Rs1::decode(value),
+                    rs2: Rs2::decode(value),
+                },
+                (0b_110, 0b_0000001) => Instruction::REM {
+                    rd: Rd::decode(value),
+                    rs1: Rs1::decode(value),
+                    rs2: Rs2::decode(value),
+                },
+                (0b_111, 0b_0000001) => Instruction::REMU {
+                    rd: Rd::decode(value),
+                    rs1: Rs1::decode(value),
+                    rs2: Rs2::decode(value),
+                },
                 _ => return Err(Exception::UnimplementedInstruction(value)),
             },
             0b_0000011 => match Funct3::decode(value) {
@@ -924,7 +1435,42 @@ impl Instruction {
                 },
                 _ => return Err(Exception::UnimplementedInstruction(value)),
             },
+            0b_0001111 => match Funct3::decode(value) {
+                // The `rs1` and `rd` fields are ignored. The only fence mode
+                // other than a normal fence is `FENCE.TSO`, the others are
+                // reserved.
+                0b_000 => match value >> FENCE_FM_RSHIFT {
+                    0b_0000 => Instruction::FENCE {
+                        pred: ((value >> FENCE_PRED_RSHIFT) & FENCE_SET_MASK) as u8,
+                        succ: ((value >> FENCE_SUCC_RSHIFT) & FENCE_SET_MASK) as u8,
+                    },
+                    0b_1000 if (value >> FENCE_SUCC_RSHIFT) & 0xff == 0b_0011_0011 => {
+                        Instruction::FENCE_TSO
+                    }
+                    _ => return Err(Exception::UnimplementedInstruction(value)),
+                },
+                0b_001 => Instruction::FENCE_I,
+                _ => return Err(Exception::UnimplementedInstruction(value)),
+            },
             0b_1110011 => match Funct3::decode(value) {
+                0b_000 => match Funct7::decode(value) {
+                    // The encodings with a non-zero `rd` are reserved.
+                    0b_0001001 if matches!(Rd::decode(value), Register::ZERO) => {
+                        Instruction::SFENCE_VMA {
+                            rs1: Rs1::decode(value),
+                            rs2: Rs2::decode(value),
+                        }
+                    }
+                    0b_0001001 => return Err(Exception::UnimplementedInstruction(value)),
+                    _ => match u32::from_le(value) {
+                        0b_0000000_00000_00000_000_00000_1110011 => Instruction::ECALL,
+                        0b_0000000_00001_00000_000_00000_1110011 => Instruction::EBREAK,
+                        0b_0011000_00010_00000_000_00000_1110011 => Instruction::MRET,
+                        0b_0001000_00010_00000_000_00000_1110011 => Instruction::SRET,
+                        0b_0001000_00101_00000_000_00000_1110011 => Instruction::WFI,
+                        _ => return Err(Exception::UnimplementedInstruction(value)),
+                    },
+                },
                 0b_001 => Instruction::CSRRW {
                     rd: Rd::decode(value),
                     rs1: Rs1::decode(value),
@@ -1093,6 +1639,38 @@ impl Instruction {
                 u32::from_le(0b_0000000_00000_00000_111_00000_0110011)
                     + types::R::encode(rd, rs1, rs2)
             }
+            Instruction::MUL { rd, rs1, rs2 } => {
+                u32::from_le(0b_0000001_00000_00000_000_00000_0110011)
+                    + types::R::encode(rd, rs1, rs2)
+            }
+            Instruction::MULH { rd, rs1, rs2 } => {
+                u32::from_le(0b_0000001_00000_00000_001_00000_0110011)
+                    + types::R::encode(rd, rs1, rs2)
+            }
+            Instruction::MULHSU { rd, rs1, rs2 } => {
+                u32::from_le(0b_0000001_00000_00000_010_00000_0110011)
+                    + types::R::encode(rd, rs1, rs2)
+            }
+            Instruction::MULHU { rd, rs1, rs2 } => {
+                u32::from_le(0b_0000001_00000_00000_011_00000_0110011)
+                    + types::R::encode(rd, rs1, rs2)
+            }
+            Instruction::DIV { rd, rs1, rs2 } => {
+                u32::from_le(0b_0000001_00000_00000_100_00000_0110011)
+                    + types::R::encode(rd, rs1, rs2)
+            }
+            Instruction::DIVU { rd, rs1, rs2 } => {
+                u32::from_le(0b_0000001_00000_00000_101_00000_0110011)
+                    + types::R::encode(rd, rs1, rs2)
+            }
+            Instruction::REM { rd, rs1, rs2 } => {
+                u32::from_le(0b_0000001_00000_00000_110_00000_0110011)
+                    + types::R::encode(rd, rs1, rs2)
+            }
+            Instruction::REMU { rd, rs1, rs2 } => {
+                u32::from_le(0b_0000001_00000_00000_111_00000_0110011)
+                    + types::R::encode(rd, rs1, rs2)
+            }
             Instruction::LB { rd, rs1, offset } => {
                 u32::from_le(0b_0000000_00000_00000_000_00000_0000011)
                     + types::I::encode(rd, rs1, offset)
@@ -1149,6 +1727,22 @@ impl Instruction {
                 u32::from_le(0b_0000000_00000_00000_111_00000_1110011)
                     + types::I::encode_csri(rd, imm, csr)
             }
+            Instruction::FENCE { pred, succ } => {
+                u32::from_le(0b_0000000_00000_00000_000_00000_0001111)
+                    + ((pred as u32 & FENCE_SET_MASK) << FENCE_PRED_RSHIFT)
+                    + ((succ as u32 & FENCE_SET_MASK) << FENCE_SUCC_RSHIFT)
+            }
+            Instruction::FENCE_TSO => u32::from_le(0b_1000_0011_0011_00000_000_00000_0001111),
+            Instruction::FENCE_I => u32::from_le(0b_0000000_00000_00000_001_00000_0001111),
+            Instruction::SFENCE_VMA { rs1, rs2 } => {
+                u32::from_le(0b_0001001_00000_00000_000_00000_1110011)
+                    + types::R::encode(Register::ZERO, rs1, rs2)
+            }
+            Instruction::ECALL => u32::from_le(0b_0000000_00000_00000_000_00000_1110011),
+            Instruction::EBREAK => u32::from_le(0b_0000000_00001_00000_000_00000_1110011),
+            Instruction::MRET => u32::from_le(0b_0011000_00010_00000_000_00000_1110011),
+            Instruction::SRET => u32::from_le(0b_0001000_00010_00000_000_00000_1110011),
+            Instruction::WFI => u32::from_le(0b_0001000_00101_00000_000_00000_1110011),
             Instruction::JAL { rd, offset } => {
                 u32::from_le(0b_0000000_00000_00000_000_00000_1101111)
                     + types::J::encode(rd, offset)
@@ -1181,10 +1775,421 @@ impl Instruction {
                 u32::from_le(0b_0000000_00000_00000_111_00000_1100011)
                     + types::B::encode(rs1, rs2, offset)
             }
-        }
-    }
-}
-
+            Instruction::C_ADDI4SPN { rd, imm } => compressed::CIW::encode_addi4spn(rd, imm) as u32,
+            Instruction::C_JAL { offset } => {
+                (0b_001_00000000000_01 + compressed::CJ::encode(offset)) as u32
+            }
+            Instruction::C_LI { rd, imm } => {
+                (0b_010_0_00000_00000_01 + compressed::CI::encode_imm(rd, imm)) as u32
+            }
+            Instruction::C_ADDI16SP { imm } => {
+                (0b_011_0_00010_00000_01 + compressed::CI::encode_addi16sp(imm)) as u32
+            }
+            Instruction::C_LUI { rd, imm } => {
+                (0b_011_0_00000_00000_01 + compressed::CI::encode_imm(rd, imm)) as u32
+            }
+            Instruction::C_LWSP { rd, offset } => {
+                (0b_010_0_00000_00000_10 + compressed::CI::encode_lwsp(rd, offset)) as u32
+            }
+            Instruction::C_SWSP { rs2, offset } => {
+                (0b_110_000000_00000_10 + compressed::CSS::encode_swsp(rs2, offset)) as u32
+            }
+        }
+    }
+}
+
+impl Instruction {
+    /// The canonical assembly mnemonic of this instruction, for example
+    /// `"addi"`.
+    #[inline]
+    pub const fn mnemonic(&self) -> &'static str {
+        match self {
+            Instruction::LUI { .. } => "lui",
+            Instruction::AUIPC { .. } => "auipc",
+            Instruction::ADDI { .. } => "addi",
+            Instruction::SLTI { .. } => "slti",
+            Instruction::SLTIU { .. } => "sltiu",
+            Instruction::XORI { .. } => "xori",
+            Instruction::ORI { .. } => "ori",
+            Instruction::ANDI { .. } => "andi",
+            Instruction::SLLI { .. } => "slli",
+            Instruction::SRLI { .. } => "srli",
+            Instruction::SRAI { .. } => "srai",
+            Instruction::ADD { .. } => "add",
+            Instruction::SUB { .. } => "sub",
+            Instruction::SLL { .. } => "sll",
+            Instruction::SLT { .. } => "slt",
+            Instruction::SLTU { .. } => "sltu",
+            Instruction::XOR { .. } => "xor",
+            Instruction::SRL { .. } => "srl",
+            Instruction::SRA { .. } => "sra",
+            Instruction::OR { .. } => "or",
+            Instruction::AND { .. } => "and",
+            Instruction::MUL { .. } => "mul",
+            Instruction::MULH { .. } => "mulh",
+            Instruction::MULHSU { .. } => "mulhsu",
+            Instruction::MULHU { .. } => "mulhu",
+            Instruction::DIV { .. } => "div",
+            Instruction::DIVU { .. } => "divu",
+            Instruction::REM { .. } => "rem",
+            Instruction::REMU { .. } => "remu",
+            Instruction::LB { .. } => "lb",
+            Instruction::LH { .. } => "lh",
+            Instruction::LW { .. } => "lw",
+            Instruction::LBU { .. } => "lbu",
+            Instruction::LHU { .. } => "lhu",
+            Instruction::SB { .. } => "sb",
+            Instruction::SH { .. } => "sh",
+            Instruction::SW { .. } => "sw",
+            Instruction::CSRRW { .. } => "csrrw",
+            Instruction::CSRRS { .. } => "csrrs",
+            Instruction::CSRRC { .. } => "csrrc",
+            Instruction::CSRRWI { .. } => "csrrwi",
+            Instruction::CSRRSI { .. } => "csrrsi",
+            Instruction::CSRRCI { .. } => "csrrci",
+            Instruction::FENCE { .. } => "fence",
+            Instruction::FENCE_TSO => "fence.tso",
+            Instruction::FENCE_I => "fence.i",
+            Instruction::SFENCE_VMA { .. } => "sfence.vma",
+            Instruction::ECALL => "ecall",
+            Instruction::EBREAK => "ebreak",
+            Instruction::MRET => "mret",
+            Instruction::SRET => "sret",
+            Instruction::WFI => "wfi",
+            Instruction::JAL { .. } => "jal",
+            Instruction::JALR { .. } => "jalr",
+            Instruction::BEQ { .. } => "beq",
+            Instruction::BNE { .. } => "bne",
+            Instruction::BLT { .. } => "blt",
+            Instruction::BGE { .. } => "bge",
+            Instruction::BLTU { .. } => "bltu",
+            Instruction::BGEU { .. } => "bgeu",
+            Instruction::C_ADDI4SPN { .. } => "c.addi4spn",
+            Instruction::C_JAL { .. } => "c.jal",
+            Instruction::C_LI { .. } => "c.li",
+            Instruction::C_ADDI16SP { .. } => "c.addi16sp",
+            Instruction::C_LUI { .. } => "c.lui",
+            Instruction::C_LWSP { .. } => "c.lwsp",
+            Instruction::C_SWSP { .. } => "c.swsp",
+        }
+    }
+
+    /// Returns `true` if this is a conditional branch, such as `beq`.
+    #[inline]
+    pub const fn is_branch(&self) -> bool {
+        matches!(
+            self.expand(),
+            Instruction::BEQ { .. }
+                | Instruction::BNE { .. }
+                | Instruction::BLT { .. }
+                | Instruction::BGE { .. }
+                | Instruction::BLTU { .. }
+                | Instruction::BGEU { .. }
+        )
+    }
+
+    /// Returns `true` if this is an unconditional jump, `jal` or `jalr`.
+    #[inline]
+    pub const fn is_jump(&self) -> bool {
+        matches!(
+            self.expand(),
+            Instruction::JAL { .. } | Instruction::JALR { .. }
+        )
+    }
+
+    /// Returns `true` if this instruction loads from memory.
+    #[inline]
+    pub const fn is_load(&self) -> bool {
+        matches!(
+            self.expand(),
+            Instruction::LB { .. }
+                | Instruction::LH { .. }
+                | Instruction::LW { .. }
+                | Instruction::LBU { .. }
+                | Instruction::LHU { .. }
+        )
+    }
+
+    /// Returns `true` if this instruction stores to memory.
+    #[inline]
+    pub const fn is_store(&self) -> bool {
+        matches!(
+            self.expand(),
+            Instruction::SB { .. } | Instruction::SH { .. } | Instruction::SW { .. }
+        )
+    }
+
+    /// The address a load or store will access given the current `registers`,
+    /// that is `rs1 + offset`, without executing it.
+    ///
+    /// Returns `None` if this instruction does not access memory. The address
+    /// is virtual, before any translation, see [crate::mmu], and wraps at the
+    /// width of the registers.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use riskv::{instructions::Instruction, registers::{Register, Registers}};
+    ///
+    /// let mut registers = Registers::<i32>::default();
+    /// registers[Register::A0] = 0x100;
+    ///
+    /// let lw = Instruction::LW { rd: Register::A1, rs1: Register::A0, offset: -4 };
+    /// assert_eq!(lw.effective_address(&registers), Some(0xFC));
+    /// ```
+    pub fn effective_address<R>(&self, registers: &Registers<R>) -> Option<R>
+    where
+        R: Copy + Into<i64> + TryFrom<i64>,
+    {
+        match self.expand() {
+            Instruction::LB { rs1, offset, .. }
+            | Instruction::LH { rs1, offset, .. }
+            | Instruction::LW { rs1, offset, .. }
+            | Instruction::LBU { rs1, offset, .. }
+            | Instruction::LHU { rs1, offset, .. }
+            | Instruction::SB { rs1, offset, .. }
+            | Instruction::SH { rs1, offset, .. }
+            | Instruction::SW { rs1, offset, .. } => {
+                // Sign-extend from the register width so the sum wraps.
+                let shift = 64 - 8 * std::mem::size_of::<R>() as u32;
+                let address = registers[rs1].into().wrapping_add(offset.into());
+                R::try_from(address << shift >> shift).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if this instruction accesses a CSR.
+    #[inline]
+    pub const fn is_csr(&self) -> bool {
+        matches!(
+            self.expand(),
+            Instruction::CSRRW { .. }
+                | Instruction::CSRRS { .. }
+                | Instruction::CSRRC { .. }
+                | Instruction::CSRRWI { .. }
+                | Instruction::CSRRSI { .. }
+                | Instruction::CSRRCI { .. }
+        )
+    }
+
+    /// Returns `true` if this is an integer computational instruction, that
+    /// is an arithmetic, logical, shift, or comparison instruction, including
+    /// `lui` and `auipc`.
+    #[inline]
+    pub const fn is_arithmetic(&self) -> bool {
+        matches!(
+            self.expand(),
+            Instruction::LUI { .. }
+                | Instruction::AUIPC { .. }
+                | Instruction::ADDI { .. }
+                | Instruction::SLTI { .. }
+                | Instruction::SLTIU { .. }
+                | Instruction::XORI { .. }
+                | Instruction::ORI { .. }
+                | Instruction::ANDI { .. }
+                | Instruction::SLLI { .. }
+                | Instruction::SRLI { .. }
+                | Instruction::SRAI { .. }
+                | Instruction::ADD { .. }
+                | Instruction::SUB { .. }
+                | Instruction::SLL { .. }
+                | Instruction::SLT { .. }
+                | Instruction::SLTU { .. }
+                | Instruction::XOR { .. }
+                | Instruction::SRL { .. }
+                | Instruction::SRA { .. }
+                | Instruction::OR { .. }
+                | Instruction::AND { .. }
+                | Instruction::MUL { .. }
+                | Instruction::MULH { .. }
+                | Instruction::MULHSU { .. }
+                | Instruction::MULHU { .. }
+                | Instruction::DIV { .. }
+                | Instruction::DIVU { .. }
+                | Instruction::REM { .. }
+                | Instruction::REMU { .. }
+        )
+    }
+
+    /// Returns `true` if this instruction is architecturally a no-op: its only
+    /// effect is to write `x0`, such as `add x0, a0, a1`.
+    ///
+    /// Loads into `x0` are not no-ops as they may still trap, nor are jumps
+    /// or CSR accesses which have side effects beyond their destination.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use riskv::{instructions::Instruction, registers::Register};
+    ///
+    /// let add = Instruction::ADD { rd: Register::ZERO, rs1: Register::A0, rs2: Register::A1 };
+    /// assert!(add.is_nop_effect());
+    /// let lw = Instruction::LW { rd: Register::ZERO, rs1: Register::A0, offset: 0 };
+    /// assert!(!lw.is_nop_effect());
+    /// ```
+    #[inline]
+    pub const fn is_nop_effect(&self) -> bool {
+        match self.expand() {
+            Instruction::LUI { rd, .. }
+            | Instruction::AUIPC { rd, .. }
+            | Instruction::ADDI { rd, .. }
+            | Instruction::SLTI { rd, .. }
+            | Instruction::SLTIU { rd, .. }
+            | Instruction::XORI { rd, .. }
+            | Instruction::ORI { rd, .. }
+            | Instruction::ANDI { rd, .. }
+            | Instruction::SLLI { rd, .. }
+            | Instruction::SRLI { rd, .. }
+            | Instruction::SRAI { rd, .. }
+            | Instruction::ADD { rd, .. }
+            | Instruction::SUB { rd, .. }
+            | Instruction::SLL { rd, .. }
+            | Instruction::SLT { rd, .. }
+            | Instruction::SLTU { rd, .. }
+            | Instruction::XOR { rd, .. }
+            | Instruction::SRL { rd, .. }
+            | Instruction::SRA { rd, .. }
+            | Instruction::OR { rd, .. }
+            | Instruction::AND { rd, .. }
+            | Instruction::MUL { rd, .. }
+            | Instruction::MULH { rd, .. }
+            | Instruction::MULHSU { rd, .. }
+            | Instruction::MULHU { rd, .. }
+            | Instruction::DIV { rd, .. }
+            | Instruction::DIVU { rd, .. }
+            | Instruction::REM { rd, .. }
+            | Instruction::REMU { rd, .. } => matches!(rd, Register::ZERO),
+            _ => false,
+        }
+    }
+
+    /// The registers this instruction reads or writes, padded with
+    /// [Register::ZERO].
+    const fn operands(&self) -> [Register; 3] {
+        use Register::ZERO;
+        match *self {
+            Instruction::LUI { rd, .. }
+            | Instruction::AUIPC { rd, .. }
+            | Instruction::JAL { rd, .. }
+            | Instruction::CSRRWI { rd, .. }
+            | Instruction::CSRRSI { rd, .. }
+            | Instruction::CSRRCI { rd, .. }
+            | Instruction::C_ADDI4SPN { rd, .. }
+            | Instruction::C_LI { rd, .. }
+            | Instruction::C_LUI { rd, .. }
+            | Instruction::C_LWSP { rd, .. } => [rd, ZERO, ZERO],
+            Instruction::C_JAL { .. } => [Register::RA, ZERO, ZERO],
+            Instruction::C_ADDI16SP { .. } => [Register::SP, Register::SP, ZERO],
+            Instruction::ADDI { rd, rs1, .. }
+            | Instruction::SLTI { rd, rs1, .. }
+            | Instruction::SLTIU { rd, rs1, .. }
+            | Instruction::XORI { rd, rs1, .. }
+            | Instruction::ORI { rd, rs1, .. }
+            | Instruction::ANDI { rd, rs1, .. }
+            | Instruction::SLLI { rd, rs1, .. }
+            | Instruction::SRLI { rd, rs1, .. }
+            | Instruction::SRAI { rd, rs1, .. }
+            | Instruction::LB { rd, rs1, .. }
+            | Instruction::LH { rd, rs1, .. }
+            | Instruction::LW { rd, rs1, .. }
+            | Instruction::LBU { rd, rs1, .. }
+            | Instruction::LHU { rd, rs1, .. }
+            | Instruction::CSRRW { rd, rs1, .. }
+            | Instruction::CSRRS { rd, rs1, .. }
+            | Instruction::CSRRC { rd, rs1, .. }
+            | Instruction::JALR { rd, rs1, .. } => [rd, rs1, ZERO],
+            Instruction::ADD { rd, rs1, rs2 }
+            | Instruction::SUB { rd, rs1, rs2 }
+            | Instruction::SLL { rd, rs1, rs2 }
+            | Instruction::SLT { rd, rs1, rs2 }
+            | Instruction::SLTU { rd, rs1, rs2 }
+            | Instruction::XOR { rd, rs1, rs2 }
+            | Instruction::SRL { rd, rs1, rs2 }
+            | Instruction::SRA { rd, rs1, rs2 }
+            | Instruction::OR { rd, rs1, rs2 }
+            | Instruction::AND { rd, rs1, rs2 }
+            | Instruction::MUL { rd, rs1, rs2 }
+            | Instruction::MULH { rd, rs1, rs2 }
+            | Instruction::MULHSU { rd, rs1, rs2 }
+            | Instruction::MULHU { rd, rs1, rs2 }
+            | Instruction::DIV { rd, rs1, rs2 }
+            | Instruction::DIVU { rd, rs1, rs2 }
+            | Instruction::REM { rd, rs1, rs2 }
+            | Instruction::REMU { rd, rs1, rs2 } => [rd, rs1, rs2],
+            Instruction::SB { rs1, rs2, .. }
+            | Instruction::SH { rs1, rs2, .. }
+            | Instruction::SW { rs1, rs2, .. }
+            | Instruction::SFENCE_VMA { rs1, rs2 }
+            | Instruction::BEQ { rs1, rs2, .. }
+            | Instruction::BNE { rs1, rs2, .. }
+            | Instruction::BLT { rs1, rs2, .. }
+            | Instruction::BGE { rs1, rs2, .. }
+            | Instruction::BLTU { rs1, rs2, .. }
+            | Instruction::BGEU { rs1, rs2, .. } => [rs1, rs2, ZERO],
+            Instruction::C_SWSP { rs2, .. } => [rs2, ZERO, ZERO],
+            Instruction::FENCE { .. }
+            | Instruction::FENCE_TSO
+            | Instruction::FENCE_I
+            | Instruction::ECALL
+            | Instruction::EBREAK
+            | Instruction::MRET
+            | Instruction::SRET
+            | Instruction::WFI => [ZERO; 3],
+        }
+    }
+
+    /// Check this instruction can be executed on a processor implementing
+    /// the `isa`.
+    ///
+    /// Raises [EncodeError::UnavailableRegister] if an operand is one of the
+    /// registers `x16` to `x31` which do not exist on RV32E.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use riskv::{instructions::Instruction, isa::Isa, registers::Register};
+    ///
+    /// let addi = Instruction::ADDI { rd: Register::S4, rs1: Register::A0, imm: 1 };
+    /// assert!(addi.validate(&"rv32i".parse::<Isa>().unwrap()).is_ok());
+    /// assert!(addi.validate(&"rv32e".parse::<Isa>().unwrap()).is_err());
+    /// ```
+    pub fn validate(&self, isa: &Isa) -> Result<(), EncodeError> {
+        if !isa.supports(Extension::E) {
+            return Ok(());
+        }
+        match self.operands().into_iter().find(|r| *r as u8 >= 16) {
+            Some(register) => Err(EncodeError::UnavailableRegister { register }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Instruction {
+    /// The extension of the instruction set which defines this instruction.
+    #[inline]
+    pub const fn extension(&self) -> Extension {
+        match self {
+            Instruction::MUL { .. }
+            | Instruction::MULH { .. }
+            | Instruction::MULHSU { .. }
+            | Instruction::MULHU { .. }
+            | Instruction::DIV { .. }
+            | Instruction::DIVU { .. }
+            | Instruction::REM { .. }
+            | Instruction::REMU { .. } => Extension::M,
+            Instruction::C_ADDI4SPN { .. }
+            | Instruction::C_JAL { .. }
+            | Instruction::C_LI { .. }
+            | Instruction::C_ADDI16SP { .. }
+            | Instruction::C_LUI { .. }
+            | Instruction::C_LWSP { .. }
+            | Instruction::C_SWSP { .. } => Extension::C,
+            _ => Extension::I,
+        }
+    }
+}
+
 impl TryFrom<u32> for Instruction {
     type Error = Exception;
     fn try_from(value: u32) -> Result<Self, Self::Error> {
@@ -1203,11 +2208,374 @@ impl Instruction {
 /// The bit mask to extract the instructions op code from a [u32].
 const OPP_MASK: u32 = u32::from_le(0b_0000000_00000_00000_000_00000_1111111);
 
+/// The bit mask of a `FENCE` predecessor or successor set after shifting.
+const FENCE_SET_MASK: u32 = 0b1111;
+/// The right shift to apply to extract the `FENCE` predecessor set.
+const FENCE_PRED_RSHIFT: u32 = 24;
+/// The right shift to apply to extract the `FENCE` successor set.
+const FENCE_SUCC_RSHIFT: u32 = 20;
+/// The right shift to apply to extract the `FENCE` fence mode, `fm`.
+const FENCE_FM_RSHIFT: u32 = 28;
+
 #[cfg(test)]
 mod test {
     use crate::{instructions::Instruction, registers::Register};
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn decode_at() {
+        use crate::{instruction_set::Exception, memory::Memory};
+
+        let mut memory = Memory::default();
+        let addi = Instruction::ADDI {
+            rd: Register::A0,
+            rs1: Register::A0,
+            imm: 1,
+        };
+        memory.store_word(4, addi.encode() as i32).unwrap();
+        // c.swsp ra, 12(sp)
+        memory.store_half(8, 0xc606_u16 as i16).unwrap();
+        // A compressed `c.nop`.
+        memory.store_half(10, 0x0001).unwrap();
+
+        assert_eq!(Instruction::decode_at(&mut memory, 4), Ok((addi, 4)));
+        assert_eq!(
+            Instruction::decode_at(&mut memory, 8),
+            Ok((
+                Instruction::C_SWSP {
+                    rs2: Register::RA,
+                    offset: 12
+                },
+                2
+            ))
+        );
+        assert_eq!(
+            Instruction::decode_at(&mut memory, 10),
+            Err(Exception::UnimplementedInstruction(0x0001))
+        );
+    }
+
+    #[test]
+    fn decode_with_raw() {
+        use crate::instruction_set::Exception;
+
+        // fence iorw, iorw with the ignored rd field set.
+        let word = Instruction::FENCE {
+            pred: 0b1111,
+            succ: 0b1111,
+        }
+        .encode()
+            | 0b_11111 << 7;
+        // c.swsp ra, 12(sp) with garbage in the upper half of the word.
+        let compressed = 0xdead_c606;
+
+        for word in [word, compressed] {
+            let decoded = Instruction::decode_with_raw(word).unwrap();
+            assert_eq!(decoded.raw, word);
+            assert_ne!(decoded.instruction.encode(), word);
+        }
+        assert_eq!(
+            Instruction::decode_with_raw(compressed)
+                .unwrap()
+                .instruction,
+            Instruction::C_SWSP {
+                rs2: Register::RA,
+                offset: 12
+            }
+        );
+        assert_eq!(
+            Instruction::decode_with_raw(0),
+            Err(Exception::UnimplementedInstruction(0))
+        );
+    }
+
+    #[test]
+    fn decode_defined_illegal() {
+        use crate::instruction_set::Exception;
+
+        for word in [0x0000_0000, 0xffff_ffff] {
+            assert!(Instruction::is_defined_illegal(word));
+            assert_eq!(
+                Instruction::decode(word),
+                Err(Exception::UnimplementedInstruction(word))
+            );
+        }
+        assert!(!Instruction::is_defined_illegal(0x0000_0013));
+        assert!(!Instruction::is_defined_illegal(0xffff_0000));
+    }
+
+    #[test]
+    fn classification() {
+        let (rd, rs1, rs2) = (Register::A0, Register::A1, Register::A2);
+        for (instruction, expected) in [
+            (
+                Instruction::BEQ {
+                    rs1,
+                    rs2,
+                    offset: 8,
+                },
+                "branch",
+            ),
+            (
+                Instruction::BGEU {
+                    rs1,
+                    rs2,
+                    offset: 8,
+                },
+                "branch",
+            ),
+            (Instruction::JAL { rd, offset: 8 }, "jump"),
+            (Instruction::JALR { rd, rs1, offset: 0 }, "jump"),
+            (Instruction::LB { rd, rs1, offset: 0 }, "load"),
+            (Instruction::LHU { rd, rs1, offset: 0 }, "load"),
+            (
+                Instruction::SW {
+                    rs1,
+                    rs2,
+                    offset: 0,
+                },
+                "store",
+            ),
+            (
+                Instruction::CSRRW {
+                    rd,
+                    rs1,
+                    csr: 0x300,
+                },
+                "csr",
+            ),
+            (
+                Instruction::CSRRCI {
+                    rd,
+                    csr: 0x300,
+                    imm: 1,
+                },
+                "csr",
+            ),
+            (Instruction::LUI { rd, imm: 1 }, "arithmetic"),
+            (Instruction::ADDI { rd, rs1, imm: 1 }, "arithmetic"),
+            (Instruction::SRA { rd, rs1, rs2 }, "arithmetic"),
+            (Instruction::MRET, "none"),
+            (Instruction::SFENCE_VMA { rs1, rs2 }, "none"),
+            (Instruction::C_LWSP { rd, offset: 4 }, "load"),
+            (Instruction::C_SWSP { rs2, offset: 4 }, "store"),
+            (Instruction::C_ADDI4SPN { rd, imm: 4 }, "arithmetic"),
+        ] {
+            let classes = [
+                (instruction.is_branch(), "branch"),
+                (instruction.is_jump(), "jump"),
+                (instruction.is_load(), "load"),
+                (instruction.is_store(), "store"),
+                (instruction.is_csr(), "csr"),
+                (instruction.is_arithmetic(), "arithmetic"),
+            ];
+            for (is_class, class) in classes {
+                assert_eq!(is_class, class == expected, "{instruction:?} is {class}");
+            }
+        }
+    }
+
+    #[test]
+    fn effective_address() {
+        let mut registers = crate::registers::Registers::<i32>::default();
+        registers[Register::A0] = 0x100;
+        registers[Register::SP] = 0x200;
+
+        let lw = Instruction::LW {
+            rd: Register::A1,
+            rs1: Register::A0,
+            offset: 8,
+        };
+        assert_eq!(lw.effective_address(&registers), Some(0x108));
+        let sw = Instruction::SW {
+            rs1: Register::A0,
+            rs2: Register::A1,
+            offset: -4,
+        };
+        assert_eq!(sw.effective_address(&registers), Some(0xFC));
+        let lwsp = Instruction::C_LWSP {
+            rd: Register::A1,
+            offset: 12,
+        };
+        assert_eq!(lwsp.effective_address(&registers), Some(0x20C));
+        let add = Instruction::ADD {
+            rd: Register::A1,
+            rs1: Register::A0,
+            rs2: Register::A0,
+        };
+        assert_eq!(add.effective_address(&registers), None);
+        registers[Register::A0] = i32::MAX;
+        assert_eq!(lw.effective_address(&registers), Some(i32::MIN + 7));
+    }
+
+    #[test]
+    fn effective_address_64() {
+        let mut registers = crate::registers::Registers::<i64>::default();
+        registers[Register::A0] = 0x1_0000_0000;
+
+        let lw = Instruction::LW {
+            rd: Register::A1,
+            rs1: Register::A0,
+            offset: -4,
+        };
+        assert_eq!(lw.effective_address(&registers), Some(0xFFFF_FFFC));
+        registers[Register::A0] = i64::MAX;
+        let sw = Instruction::SW {
+            rs1: Register::A0,
+            rs2: Register::A1,
+            offset: 1,
+        };
+        assert_eq!(sw.effective_address(&registers), Some(i64::MIN));
+    }
+
+    #[test]
+    fn is_nop_effect() {
+        for (instruction, expected) in [
+            (
+                Instruction::ADDI {
+                    rd: Register::ZERO,
+                    rs1: Register::ZERO,
+                    imm: 0,
+                },
+                true,
+            ),
+            (
+                Instruction::ADD {
+                    rd: Register::ZERO,
+                    rs1: Register::A0,
+                    rs2: Register::A1,
+                },
+                true,
+            ),
+            (
+                Instruction::DIV {
+                    rd: Register::ZERO,
+                    rs1: Register::A0,
+                    rs2: Register::A1,
+                },
+                true,
+            ),
+            (
+                Instruction::LUI {
+                    rd: Register::ZERO,
+                    imm: 1,
+                },
+                true,
+            ),
+            (
+                Instruction::ADD {
+                    rd: Register::A2,
+                    rs1: Register::A0,
+                    rs2: Register::A1,
+                },
+                false,
+            ),
+            (
+                Instruction::LW {
+                    rd: Register::ZERO,
+                    rs1: Register::A0,
+                    offset: 0,
+                },
+                false,
+            ),
+            (
+                Instruction::JAL {
+                    rd: Register::ZERO,
+                    offset: 8,
+                },
+                false,
+            ),
+            (
+                Instruction::CSRRW {
+                    rd: Register::ZERO,
+                    rs1: Register::A0,
+                    csr: 0x140,
+                },
+                false,
+            ),
+        ] {
+            assert_eq!(instruction.is_nop_effect(), expected, "{instruction:?}");
+        }
+    }
+
+    #[test]
+    fn validate_registers_for_rv32e() {
+        use crate::{instructions::EncodeError, isa::Isa};
+
+        let rv32i: Isa = "rv32i".parse().unwrap();
+        let rv32e: Isa = "rv32e".parse().unwrap();
+        for instruction in [
+            Instruction::ADDI {
+                rd: Register::S4,
+                rs1: Register::A0,
+                imm: 1,
+            },
+            Instruction::ADD {
+                rd: Register::A0,
+                rs1: Register::A1,
+                rs2: Register::S4,
+            },
+            Instruction::SW {
+                rs1: Register::S4,
+                rs2: Register::A0,
+                offset: 0,
+            },
+        ] {
+            assert_eq!(instruction.validate(&rv32i), Ok(()));
+            assert_eq!(
+                instruction.validate(&rv32e),
+                Err(EncodeError::UnavailableRegister {
+                    register: Register::S4
+                })
+            );
+        }
+        assert_eq!(
+            Instruction::ADD {
+                rd: Register::A5,
+                rs1: Register::A0,
+                rs2: Register::S1
+            }
+            .validate(&rv32e),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn instruction_as_map_key() {
+        let mut counts = std::collections::HashMap::new();
+        for instruction in [
+            Instruction::ADDI {
+                rd: Register::A0,
+                rs1: Register::A0,
+                imm: 1,
+            },
+            Instruction::MRET,
+            Instruction::ADDI {
+                rd: Register::A0,
+                rs1: Register::A0,
+                imm: 1,
+            },
+            Instruction::ADDI {
+                rd: Register::A0,
+                rs1: Register::A0,
+                imm: 2,
+            },
+        ] {
+            *counts.entry(instruction).or_insert(0) += 1;
+        }
+
+        assert_eq!(counts.len(), 3);
+        assert_eq!(
+            counts[&Instruction::ADDI {
+                rd: Register::A0,
+                rs1: Register::A0,
+                imm: 1
+            }],
+            2
+        );
+        assert_eq!(counts[&Instruction::MRET], 1);
+    }
+
     impl Instruction {
         fn from(value: u32) -> Self {
             Self::try_from(value).expect("Unimplemented")
@@ -1735,6 +3103,206 @@ mod test {
         );
     }
 
+    #[test]
+    fn mul_from_u32() {
+        assert_eq!(
+            Instruction::from(u32::from_le(0b_0000001_11111_11110_000_11101_0110011)),
+            Instruction::MUL {
+                rd: Register::T4,
+                rs1: Register::T5,
+                rs2: Register::T6,
+            }
+        );
+    }
+
+    #[test]
+    fn encode_mul() {
+        assert_eq!(
+            Instruction::MUL {
+                rd: Register::T4,
+                rs1: Register::T5,
+                rs2: Register::T6,
+            }
+            .encode(),
+            u32::from_le(0b_0000001_11111_11110_000_11101_0110011),
+        );
+    }
+
+    #[test]
+    fn mulh_from_u32() {
+        assert_eq!(
+            Instruction::from(u32::from_le(0b_0000001_11111_11110_001_11101_0110011)),
+            Instruction::MULH {
+                rd: Register::T4,
+                rs1: Register::T5,
+                rs2: Register::T6,
+            }
+        );
+    }
+
+    #[test]
+    fn encode_mulh() {
+        assert_eq!(
+            Instruction::MULH {
+                rd: Register::T4,
+                rs1: Register::T5,
+                rs2: Register::T6,
+            }
+            .encode(),
+            u32::from_le(0b_0000001_11111_11110_001_11101_0110011),
+        );
+    }
+
+    #[test]
+    fn mulhsu_from_u32() {
+        assert_eq!(
+            Instruction::from(u32::from_le(0b_0000001_11111_11110_010_11101_0110011)),
+            Instruction::MULHSU {
+                rd: Register::T4,
+                rs1: Register::T5,
+                rs2: Register::T6,
+            }
+        );
+    }
+
+    #[test]
+    fn encode_mulhsu() {
+        assert_eq!(
+            Instruction::MULHSU {
+                rd: Register::T4,
+                rs1: Register::T5,
+                rs2: Register::T6,
+            }
+            .encode(),
+            u32::from_le(0b_0000001_11111_11110_010_11101_0110011),
+        );
+    }
+
+    #[test]
+    fn mulhu_from_u32() {
+        assert_eq!(
+            Instruction::from(u32::from_le(0b_0000001_11111_11110_011_11101_0110011)),
+            Instruction::MULHU {
+                rd: Register::T4,
+                rs1: Register::T5,
+                rs2: Register::T6,
+            }
+        );
+    }
+
+    #[test]
+    fn encode_mulhu() {
+        assert_eq!(
+            Instruction::MULHU {
+                rd: Register::T4,
+                rs1: Register::T5,
+                rs2: Register::T6,
+            }
+            .encode(),
+            u32::from_le(0b_0000001_11111_11110_011_11101_0110011),
+        );
+    }
+
+    #[test]
+    fn div_from_u32() {
+        assert_eq!(
+            Instruction::from(u32::from_le(0b_0000001_11111_11110_100_11101_0110011)),
+            Instruction::DIV {
+                rd: Register::T4,
+                rs1: Register::T5,
+                rs2: Register::T6,
+            }
+        );
+    }
+
+    #[test]
+    fn encode_div() {
+        assert_eq!(
+            Instruction::DIV {
+                rd: Register::T4,
+                rs1: Register::T5,
+                rs2: Register::T6,
+            }
+            .encode(),
+            u32::from_le(0b_0000001_11111_11110_100_11101_0110011),
+        );
+    }
+
+    #[test]
+    fn divu_from_u32() {
+        assert_eq!(
+            Instruction::from(u32::from_le(0b_0000001_11111_11110_101_11101_0110011)),
+            Instruction::DIVU {
+                rd: Register::T4,
+                rs1: Register::T5,
+                rs2: Register::T6,
+            }
+        );
+    }
+
+    #[test]
+    fn encode_divu() {
+        assert_eq!(
+            Instruction::DIVU {
+                rd: Register::T4,
+                rs1: Register::T5,
+                rs2: Register::T6,
+            }
+            .encode(),
+            u32::from_le(0b_0000001_11111_11110_101_11101_0110011),
+        );
+    }
+
+    #[test]
+    fn rem_from_u32() {
+        assert_eq!(
+            Instruction::from(u32::from_le(0b_0000001_11111_11110_110_11101_0110011)),
+            Instruction::REM {
+                rd: Register::T4,
+                rs1: Register::T5,
+                rs2: Register::T6,
+            }
+        );
+    }
+
+    #[test]
+    fn encode_rem() {
+        assert_eq!(
+            Instruction::REM {
+                rd: Register::T4,
+                rs1: Register::T5,
+                rs2: Register::T6,
+            }
+            .encode(),
+            u32::from_le(0b_0000001_11111_11110_110_11101_0110011),
+        );
+    }
+
+    #[test]
+    fn remu_from_u32() {
+        assert_eq!(
+            Instruction::from(u32::from_le(0b_0000001_11111_11110_111_11101_0110011)),
+            Instruction::REMU {
+                rd: Register::T4,
+                rs1: Register::T5,
+                rs2: Register::T6,
+            }
+        );
+    }
+
+    #[test]
+    fn encode_remu() {
+        assert_eq!(
+            Instruction::REMU {
+                rd: Register::T4,
+                rs1: Register::T5,
+                rs2: Register::T6,
+            }
+            .encode(),
+            u32::from_le(0b_0000001_11111_11110_111_11101_0110011),
+        );
+    }
+
     #[test]
     fn lb_from_u32() {
         assert_eq!(
@@ -1935,6 +3503,180 @@ mod test {
         );
     }
 
+    #[test]
+    fn sfence_vma_from_u32() {
+        assert_eq!(
+            Instruction::from(u32::from_le(0b_0001001_01011_01010_000_00000_1110011)),
+            Instruction::SFENCE_VMA {
+                rs1: Register::A0,
+                rs2: Register::A1,
+            }
+        );
+        let reserved = u32::from_le(0b_0001001_01011_01010_000_00001_1110011);
+        assert_eq!(
+            Instruction::try_from(reserved),
+            Err(crate::instruction_set::Exception::UnimplementedInstruction(
+                reserved
+            ))
+        );
+    }
+
+    #[test]
+    fn encode_sfence_vma() {
+        assert_eq!(
+            Instruction::SFENCE_VMA {
+                rs1: Register::A0,
+                rs2: Register::A1,
+            }
+            .encode(),
+            u32::from_le(0b_0001001_01011_01010_000_00000_1110011),
+        );
+    }
+
+    #[test]
+    fn fence_from_u32() {
+        assert_eq!(
+            Instruction::from(u32::from_le(0b_0000_1111_1111_00000_000_00000_0001111)),
+            Instruction::FENCE {
+                pred: 0b1111,
+                succ: 0b1111
+            }
+        );
+        // fence rw,w with the ignored rs1 and rd fields set.
+        assert_eq!(
+            Instruction::from(u32::from_le(0b_0000_0011_0001_00001_000_00001_0001111)),
+            Instruction::FENCE {
+                pred: 0b0011,
+                succ: 0b0001
+            }
+        );
+    }
+
+    #[test]
+    fn encode_fence() {
+        assert_eq!(
+            Instruction::FENCE {
+                pred: 0b0011,
+                succ: 0b0001
+            }
+            .encode(),
+            u32::from_le(0b_0000_0011_0001_00000_000_00000_0001111),
+        );
+    }
+
+    #[test]
+    fn fence_tso_round_trips() {
+        let word = u32::from_le(0b_1000_0011_0011_00000_000_00000_0001111);
+        assert_eq!(Instruction::from(word), Instruction::FENCE_TSO);
+        assert_eq!(Instruction::FENCE_TSO.encode(), word);
+        assert_eq!(Instruction::FENCE_TSO.to_string(), "fence.tso");
+    }
+
+    #[test]
+    fn fence_reserved_modes() {
+        // FENCE.TSO with sets other than `rw,rw`, and the reserved fence
+        // mode 0b0001.
+        for word in [
+            0b_1000_1111_1111_00000_000_00000_0001111,
+            0b_0001_0011_0011_00000_000_00000_0001111,
+        ] {
+            assert_eq!(
+                Instruction::decode(word),
+                Err(crate::instruction_set::Exception::UnimplementedInstruction(
+                    word
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn fence_i_from_u32() {
+        assert_eq!(
+            Instruction::from(u32::from_le(0b_0000000_00000_00000_001_00000_0001111)),
+            Instruction::FENCE_I
+        );
+        assert_eq!(
+            Instruction::FENCE_I.encode(),
+            u32::from_le(0b_0000000_00000_00000_001_00000_0001111),
+        );
+    }
+
+    #[test]
+    fn wfi_from_u32() {
+        assert_eq!(
+            Instruction::from(u32::from_le(0b_0001000_00101_00000_000_00000_1110011)),
+            Instruction::WFI
+        );
+        assert_eq!(
+            Instruction::WFI.encode(),
+            u32::from_le(0b_0001000_00101_00000_000_00000_1110011),
+        );
+    }
+
+    #[test]
+    fn ecall_from_u32() {
+        assert_eq!(
+            Instruction::from(u32::from_le(0b_0000000_00000_00000_000_00000_1110011)),
+            Instruction::ECALL
+        );
+    }
+
+    #[test]
+    fn encode_ecall() {
+        assert_eq!(
+            Instruction::ECALL.encode(),
+            u32::from_le(0b_0000000_00000_00000_000_00000_1110011),
+        );
+    }
+
+    #[test]
+    fn ebreak_from_u32() {
+        assert_eq!(
+            Instruction::from(u32::from_le(0b_0000000_00001_00000_000_00000_1110011)),
+            Instruction::EBREAK
+        );
+    }
+
+    #[test]
+    fn encode_ebreak() {
+        assert_eq!(
+            Instruction::EBREAK.encode(),
+            u32::from_le(0b_0000000_00001_00000_000_00000_1110011),
+        );
+    }
+
+    #[test]
+    fn mret_from_u32() {
+        assert_eq!(
+            Instruction::from(u32::from_le(0b_0011000_00010_00000_000_00000_1110011)),
+            Instruction::MRET
+        );
+    }
+
+    #[test]
+    fn encode_mret() {
+        assert_eq!(
+            Instruction::MRET.encode(),
+            u32::from_le(0b_0011000_00010_00000_000_00000_1110011),
+        );
+    }
+
+    #[test]
+    fn sret_from_u32() {
+        assert_eq!(
+            Instruction::from(u32::from_le(0b_0001000_00010_00000_000_00000_1110011)),
+            Instruction::SRET
+        );
+    }
+
+    #[test]
+    fn encode_sret() {
+        assert_eq!(
+            Instruction::SRET.encode(),
+            u32::from_le(0b_0001000_00010_00000_000_00000_1110011),
+        );
+    }
+
     #[test]
     fn csrrw_from_u32() {
         assert_eq!(
@@ -2282,4 +4024,74 @@ mod test {
             u32::from_le(0b_1000001_01111_01101_111_00001_1100011),
         );
     }
+
+    #[test]
+    fn mnemonic() {
+        for (instruction, mnemonic) in [
+            (
+                Instruction::ADD {
+                    rd: Register::A0,
+                    rs1: Register::A1,
+                    rs2: Register::A2,
+                },
+                "add",
+            ),
+            (
+                Instruction::ADDI {
+                    rd: Register::A0,
+                    rs1: Register::A1,
+                    imm: 1,
+                },
+                "addi",
+            ),
+            (
+                Instruction::LW {
+                    rd: Register::A0,
+                    rs1: Register::A1,
+                    offset: 4,
+                },
+                "lw",
+            ),
+            (
+                Instruction::SW {
+                    rs1: Register::A0,
+                    rs2: Register::A1,
+                    offset: 4,
+                },
+                "sw",
+            ),
+            (
+                Instruction::BEQ {
+                    rs1: Register::A0,
+                    rs2: Register::A1,
+                    offset: 8,
+                },
+                "beq",
+            ),
+            (
+                Instruction::LUI {
+                    rd: Register::A0,
+                    imm: 1,
+                },
+                "lui",
+            ),
+            (
+                Instruction::JAL {
+                    rd: Register::RA,
+                    offset: 8,
+                },
+                "jal",
+            ),
+            (
+                Instruction::CSRRWI {
+                    rd: Register::A0,
+                    csr: 0x300,
+                    imm: 1,
+                },
+                "csrrwi",
+            ),
+        ] {
+            assert_eq!(instruction.mnemonic(), mnemonic);
+        }
+    }
 }
@@ -1,14 +1,58 @@
 //! RISC-V Pseudoinstruction
-use crate::{integer::i12, registers::Register};
+use crate::{csr, integer::i12, registers::Register};
 
 use super::{immu::ImmU, Instruction};
 
+/// Build a [Vec] of [Instruction]s from a mixture of instructions and
+/// pseudoinstructions, flattening each pseudoinstruction into the
+/// instructions it desugars to.
+///
+/// Each argument may be anything which can be iterated to yield instructions,
+/// such as an [Instruction], a pseudoinstruction, or an array of instructions.
+///
+/// # Example
+///
+/// ```
+/// use riskv::{instructions, instructions::Instruction, registers::Register, Rv32Processor};
+///
+/// let programme = instructions![
+///     Instruction::LI(Register::A0, 21),
+///     Instruction::LI(Register::A1, 0x12345),
+///     Instruction::ADD { rd: Register::A0, rs1: Register::A0, rs2: Register::A0 },
+/// ];
+/// // Loading the large immediate takes two instructions.
+/// assert_eq!(programme.len(), 4);
+///
+/// let processor = Rv32Processor::run_program(programme);
+/// assert_eq!(processor.return_value(), 42);
+/// ```
+#[macro_export]
+macro_rules! instructions {
+    ($($instruction:expr),* $(,)*) => {{
+        let mut vec = ::std::vec::Vec::new();
+        $(vec.extend($instruction);)*
+        vec
+    }};
+}
+
+/// Makes it possible to treat instructions and pseudoinstructions together as
+/// if of the same type, for example in the [crate::instructions!] macro.
+impl IntoIterator for Instruction {
+    type Item = Self;
+
+    type IntoIter = std::iter::Once<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
 /// An iterator of up to 3 instructions.
 ///
 /// All pseudoinstructions desugar to a number of instructions.
 ///
 /// This is encapsulated by this iterator.
-pub(crate) enum PseudoinstructionMappingIter {
+pub enum PseudoinstructionMappingIter {
     /// The iterator which will yield three instructions.
     Three(Instruction, Instruction, Instruction),
     /// The iterator which will yield two instructions.
@@ -77,7 +121,7 @@ impl Instruction {
     /// and a add immediate for the lower bits.
     /// See [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#load-immediate).
     #[allow(non_snake_case)]
-    pub(crate) fn LI(rd: Register, imm: i32) -> PseudoinstructionMappingIter {
+    pub fn LI(rd: Register, imm: i32) -> PseudoinstructionMappingIter {
         if imm >= i12::MIN as i32 && imm <= i12::MAX as i32 {
             PseudoinstructionMappingIter::One(Instruction::ADDI {
                 rd,
@@ -88,7 +132,7 @@ impl Instruction {
             PseudoinstructionMappingIter::Two(
                 Instruction::LUI {
                     rd,
-                    imm: (imm >> ImmU::RSHIFT) + with_signed_i12_adjustment(imm),
+                    imm: ((imm >> ImmU::RSHIFT) + with_signed_i12_adjustment(imm)) & ImmU::MAX,
                 },
                 Instruction::ADDI {
                     rd,
@@ -106,7 +150,7 @@ impl Instruction {
     /// Note: This pseudoinstruction desugars to `XORI rd, rs, -1`.
     /// See [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions).
     #[allow(non_snake_case)]
-    pub(crate) fn NOT(rd: Register, rs: Register) -> PseudoinstructionMappingIter {
+    pub fn NOT(rd: Register, rs: Register) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::XORI {
             rd,
             rs1: rs,
@@ -121,7 +165,7 @@ impl Instruction {
     /// Note: This pseudoinstruction desugars to `SUB rd, x0, rs`.
     /// See [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions).
     #[allow(non_snake_case)]
-    pub(crate) fn NEG(rd: Register, rs: Register) -> PseudoinstructionMappingIter {
+    pub fn NEG(rd: Register, rs: Register) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::SUB {
             rd,
             rs1: Register::ZERO,
@@ -136,7 +180,7 @@ impl Instruction {
     /// Note: This pseudoinstruction desugars to `ADDI rd, rs, 0`.
     /// See [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions).
     #[allow(non_snake_case)]
-    pub(crate) fn MOV(rd: Register, rs: Register) -> PseudoinstructionMappingIter {
+    pub fn MOV(rd: Register, rs: Register) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::ADDI {
             rd,
             rs1: rs,
@@ -152,7 +196,7 @@ impl Instruction {
     /// Note: This pseudoinstruction desugars to `SLTUI rd, rs, 1`.
     /// See [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions).
     #[allow(non_snake_case)]
-    pub(crate) fn SEQZ(rd: Register, rs: Register) -> PseudoinstructionMappingIter {
+    pub fn SEQZ(rd: Register, rs: Register) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::SLTIU {
             rd,
             rs1: rs,
@@ -168,7 +212,7 @@ impl Instruction {
     /// Note: This pseudoinstruction desugars to `SLTU rd, x0, rs`.
     /// See [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions).
     #[allow(non_snake_case)]
-    pub(crate) fn SNEZ(rd: Register, rs: Register) -> PseudoinstructionMappingIter {
+    pub fn SNEZ(rd: Register, rs: Register) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::SLTU {
             rd,
             rs1: Register::ZERO,
@@ -184,7 +228,7 @@ impl Instruction {
     /// Note: This pseudoinstruction desugars to `SLT rd, rs, x0`.
     /// See [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions).
     #[allow(non_snake_case)]
-    pub(crate) fn SLTZ(rd: Register, rs: Register) -> PseudoinstructionMappingIter {
+    pub fn SLTZ(rd: Register, rs: Register) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::SLT {
             rd,
             rs1: rs,
@@ -200,7 +244,7 @@ impl Instruction {
     /// Note: This pseudoinstruction desugars to `SLT rd, x0, rs`.
     /// See [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions).
     #[allow(non_snake_case)]
-    pub(crate) fn SGLZ(rd: Register, rs: Register) -> PseudoinstructionMappingIter {
+    pub fn SGLZ(rd: Register, rs: Register) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::SLT {
             rd,
             rs1: Register::ZERO,
@@ -215,7 +259,7 @@ impl Instruction {
     /// Note: This pseudoinstruction desugars to `ADDI x0, x0, 0`.
     /// See [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions).
     #[allow(non_snake_case)]
-    pub(crate) const NOP: PseudoinstructionMappingIter =
+    pub const NOP: PseudoinstructionMappingIter =
         PseudoinstructionMappingIter::One(Instruction::ADDI {
             rd: Register::ZERO,
             rs1: Register::ZERO,
@@ -230,7 +274,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#pseudoinstructions-for-accessing-control-and-status-registers)
     #[allow(non_snake_case)]
-    pub(crate) fn CSRR(rd: Register, csr: u16) -> PseudoinstructionMappingIter {
+    pub fn CSRR(rd: Register, csr: u16) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::CSRRW {
             rd,
             rs1: Register::ZERO,
@@ -238,6 +282,102 @@ impl Instruction {
         })
     }
 
+    /// # Read cycle counter
+    ///
+    /// Read the lower 32 bits of the cycle counter.
+    ///
+    /// Note: This pseudoinstruction desugars to `CSRRS rd, cycle, x0`
+    /// See
+    /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#pseudoinstructions-for-accessing-control-and-status-registers)
+    #[allow(non_snake_case)]
+    pub fn RDCYCLE(rd: Register) -> PseudoinstructionMappingIter {
+        PseudoinstructionMappingIter::One(Instruction::CSRRS {
+            rd,
+            rs1: Register::ZERO,
+            csr: csr::CYCLE,
+        })
+    }
+
+    /// # Read cycle counter upper half
+    ///
+    /// Read the upper 32 bits of the cycle counter, RV32 only.
+    ///
+    /// Note: This pseudoinstruction desugars to `CSRRS rd, cycleh, x0`
+    /// See
+    /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#pseudoinstructions-for-accessing-control-and-status-registers)
+    #[allow(non_snake_case)]
+    pub fn RDCYCLEH(rd: Register) -> PseudoinstructionMappingIter {
+        PseudoinstructionMappingIter::One(Instruction::CSRRS {
+            rd,
+            rs1: Register::ZERO,
+            csr: csr::CYCLEH,
+        })
+    }
+
+    /// # Read real-time clock
+    ///
+    /// Read the lower 32 bits of the real-time clock.
+    ///
+    /// Note: This pseudoinstruction desugars to `CSRRS rd, time, x0`
+    /// See
+    /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#pseudoinstructions-for-accessing-control-and-status-registers)
+    #[allow(non_snake_case)]
+    pub fn RDTIME(rd: Register) -> PseudoinstructionMappingIter {
+        PseudoinstructionMappingIter::One(Instruction::CSRRS {
+            rd,
+            rs1: Register::ZERO,
+            csr: csr::TIME,
+        })
+    }
+
+    /// # Read real-time clock upper half
+    ///
+    /// Read the upper 32 bits of the real-time clock, RV32 only.
+    ///
+    /// Note: This pseudoinstruction desugars to `CSRRS rd, timeh, x0`
+    /// See
+    /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#pseudoinstructions-for-accessing-control-and-status-registers)
+    #[allow(non_snake_case)]
+    pub fn RDTIMEH(rd: Register) -> PseudoinstructionMappingIter {
+        PseudoinstructionMappingIter::One(Instruction::CSRRS {
+            rd,
+            rs1: Register::ZERO,
+            csr: csr::TIMEH,
+        })
+    }
+
+    /// # Read instructions-retired counter
+    ///
+    /// Read the lower 32 bits of the instructions-retired counter.
+    ///
+    /// Note: This pseudoinstruction desugars to `CSRRS rd, instret, x0`
+    /// See
+    /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#pseudoinstructions-for-accessing-control-and-status-registers)
+    #[allow(non_snake_case)]
+    pub fn RDINSTRET(rd: Register) -> PseudoinstructionMappingIter {
+        PseudoinstructionMappingIter::One(Instruction::CSRRS {
+            rd,
+            rs1: Register::ZERO,
+            csr: csr::INSTRET,
+        })
+    }
+
+    /// # Read instructions-retired counter upper half
+    ///
+    /// Read the upper 32 bits of the instructions-retired counter, RV32 only.
+    ///
+    /// Note: This pseudoinstruction desugars to `CSRRS rd, instreth, x0`
+    /// See
+    /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#pseudoinstructions-for-accessing-control-and-status-registers)
+    #[allow(non_snake_case)]
+    pub fn RDINSTRETH(rd: Register) -> PseudoinstructionMappingIter {
+        PseudoinstructionMappingIter::One(Instruction::CSRRS {
+            rd,
+            rs1: Register::ZERO,
+            csr: csr::INSTRETH,
+        })
+    }
+
     /// # Write CSR
     ///
     /// Write CSR, no read side effects should be caused by this instruction.
@@ -246,7 +386,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#pseudoinstructions-for-accessing-control-and-status-registers)
     #[allow(non_snake_case)]
-    pub(crate) fn CSRW(rs1: Register, csr: u16) -> PseudoinstructionMappingIter {
+    pub fn CSRW(rs1: Register, csr: u16) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::CSRRW {
             rd: Register::ZERO,
             rs1,
@@ -263,7 +403,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#pseudoinstructions-for-accessing-control-and-status-registers)
     #[allow(non_snake_case)]
-    pub(crate) fn CSRS(rs1: Register, csr: u16) -> PseudoinstructionMappingIter {
+    pub fn CSRS(rs1: Register, csr: u16) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::CSRRS {
             rd: Register::ZERO,
             rs1,
@@ -280,7 +420,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#pseudoinstructions-for-accessing-control-and-status-registers)
     #[allow(non_snake_case)]
-    pub(crate) fn CSRC(rs1: Register, csr: u16) -> PseudoinstructionMappingIter {
+    pub fn CSRC(rs1: Register, csr: u16) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::CSRRC {
             rd: Register::ZERO,
             rs1,
@@ -297,7 +437,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#pseudoinstructions-for-accessing-control-and-status-registers)
     #[allow(non_snake_case)]
-    pub(crate) fn CSRWI(csr: u16, imm: u8) -> PseudoinstructionMappingIter {
+    pub fn CSRWI(csr: u16, imm: u8) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::CSRRWI {
             rd: Register::ZERO,
             imm,
@@ -314,7 +454,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#pseudoinstructions-for-accessing-control-and-status-registers)
     #[allow(non_snake_case)]
-    pub(crate) fn CSRSI(csr: u16, imm: u8) -> PseudoinstructionMappingIter {
+    pub fn CSRSI(csr: u16, imm: u8) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::CSRRSI {
             rd: Register::ZERO,
             imm,
@@ -331,7 +471,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#pseudoinstructions-for-accessing-control-and-status-registers)
     #[allow(non_snake_case)]
-    pub(crate) fn CSRCI(csr: u16, imm: u8) -> PseudoinstructionMappingIter {
+    pub fn CSRCI(csr: u16, imm: u8) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::CSRRCI {
             rd: Register::ZERO,
             imm,
@@ -347,7 +487,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions)
     #[allow(non_snake_case)]
-    pub(crate) fn JAL(offset: i32) -> PseudoinstructionMappingIter {
+    pub fn JAL(offset: i32) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::JAL {
             rd: Register::RA,
             offset,
@@ -362,7 +502,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions)
     #[allow(non_snake_case)]
-    pub(crate) fn J(offset: i32) -> PseudoinstructionMappingIter {
+    pub fn J(offset: i32) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::JAL {
             rd: Register::ZERO,
             offset,
@@ -377,7 +517,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions)
     #[allow(non_snake_case)]
-    pub(crate) fn JALR(rs: Register) -> PseudoinstructionMappingIter {
+    pub fn JALR(rs: Register) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::JALR {
             rd: Register::RA,
             rs1: rs,
@@ -393,7 +533,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions)
     #[allow(non_snake_case)]
-    pub(crate) fn JR(rs: Register) -> PseudoinstructionMappingIter {
+    pub fn JR(rs: Register) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::JALR {
             rd: Register::ZERO,
             rs1: rs,
@@ -408,7 +548,7 @@ impl Instruction {
     /// Note: This pseudoinstruction desugars to `JALR x0, x1, 0`
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions)
-    pub(crate) const RET: PseudoinstructionMappingIter =
+    pub const RET: PseudoinstructionMappingIter =
         PseudoinstructionMappingIter::One(Instruction::JALR {
             rd: Register::ZERO,
             rs1: Register::RA,
@@ -423,7 +563,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions)
     #[allow(non_snake_case)]
-    pub(crate) fn CALL(address: i32) -> PseudoinstructionMappingIter {
+    pub fn CALL(address: i32) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::Two(
             Instruction::AUIPC {
                 rd: Register::RA,
@@ -445,7 +585,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions)
     #[allow(non_snake_case)]
-    pub(crate) fn TAIL(address: i32) -> PseudoinstructionMappingIter {
+    pub fn TAIL(address: i32) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::Two(
             Instruction::AUIPC {
                 rd: Register::T1,
@@ -467,7 +607,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions)
     #[allow(non_snake_case)]
-    pub(crate) fn BEQZ(rs: Register, offset: i16) -> PseudoinstructionMappingIter {
+    pub fn BEQZ(rs: Register, offset: i16) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::BEQ {
             rs1: rs,
             rs2: Register::ZERO,
@@ -483,7 +623,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions)
     #[allow(non_snake_case)]
-    pub(crate) fn BNEZ(rs: Register, offset: i16) -> PseudoinstructionMappingIter {
+    pub fn BNEZ(rs: Register, offset: i16) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::BNE {
             rs1: rs,
             rs2: Register::ZERO,
@@ -499,7 +639,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions)
     #[allow(non_snake_case)]
-    pub(crate) fn BLEZ(rs: Register, offset: i16) -> PseudoinstructionMappingIter {
+    pub fn BLEZ(rs: Register, offset: i16) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::BGE {
             rs1: Register::ZERO,
             rs2: rs,
@@ -515,7 +655,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions)
     #[allow(non_snake_case)]
-    pub(crate) fn BGEZ(rs: Register, offset: i16) -> PseudoinstructionMappingIter {
+    pub fn BGEZ(rs: Register, offset: i16) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::BGE {
             rs1: rs,
             rs2: Register::ZERO,
@@ -531,7 +671,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions)
     #[allow(non_snake_case)]
-    pub(crate) fn BLTZ(rs: Register, offset: i16) -> PseudoinstructionMappingIter {
+    pub fn BLTZ(rs: Register, offset: i16) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::BLT {
             rs1: rs,
             rs2: Register::ZERO,
@@ -547,7 +687,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions)
     #[allow(non_snake_case)]
-    pub(crate) fn BGTZ(rs: Register, offset: i16) -> PseudoinstructionMappingIter {
+    pub fn BGTZ(rs: Register, offset: i16) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::BLT {
             rs1: Register::ZERO,
             rs2: rs,
@@ -563,7 +703,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions)
     #[allow(non_snake_case)]
-    pub(crate) fn BGT(rs1: Register, rs2: Register, offset: i16) -> PseudoinstructionMappingIter {
+    pub fn BGT(rs1: Register, rs2: Register, offset: i16) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::BLT {
             rs1: rs2,
             rs2: rs1,
@@ -579,7 +719,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions)
     #[allow(non_snake_case)]
-    pub(crate) fn BLE(rs1: Register, rs2: Register, offset: i16) -> PseudoinstructionMappingIter {
+    pub fn BLE(rs1: Register, rs2: Register, offset: i16) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::BGE {
             rs1: rs2,
             rs2: rs1,
@@ -596,7 +736,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions)
     #[allow(non_snake_case)]
-    pub(crate) fn BGTU(rs1: Register, rs2: Register, offset: i16) -> PseudoinstructionMappingIter {
+    pub fn BGTU(rs1: Register, rs2: Register, offset: i16) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::BLTU {
             rs1: rs2,
             rs2: rs1,
@@ -613,7 +753,7 @@ impl Instruction {
     /// See
     /// [ref](https://github.com/riscv-non-isa/riscv-asm-manual/blob/master/riscv-asm.md#-a-listing-of-standard-risc-v-pseudoinstructions)
     #[allow(non_snake_case)]
-    pub(crate) fn BLEU(rs1: Register, rs2: Register, offset: i16) -> PseudoinstructionMappingIter {
+    pub fn BLEU(rs1: Register, rs2: Register, offset: i16) -> PseudoinstructionMappingIter {
         PseudoinstructionMappingIter::One(Instruction::BGEU {
             rs1: rs2,
             rs2: rs1,
@@ -636,6 +776,41 @@ mod test {
     use super::*;
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn li_upper_immediate_carry() {
+        for (value, upper, lower) in [
+            (0x7FFF_F800, 0x80000, -0x800),
+            (0x8000_07FF_u32 as i32, 0x80000, 0x7FF),
+            (0x0000_0800, 0x00001, -0x800),
+            (0xFFFF_F7FF_u32 as i32, 0xFFFFF, 0x7FF),
+            (i32::MIN, 0x80000, 0),
+        ] {
+            assert_eq!(
+                Instruction::LI(Register::A0, value).collect::<Vec<_>>(),
+                vec![
+                    Instruction::LUI {
+                        rd: Register::A0,
+                        imm: upper,
+                    },
+                    Instruction::ADDI {
+                        rd: Register::A0,
+                        rs1: Register::A0,
+                        imm: lower,
+                    },
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn li_encoding_round_trips() {
+        for value in [0x7FFF_F800, 0x8000_07FF_u32 as i32, -1, i32::MIN, i32::MAX] {
+            for instruction in Instruction::LI(Register::A0, value) {
+                assert_eq!(Instruction::decode(instruction.encode()), Ok(instruction));
+            }
+        }
+    }
+
     #[test]
     fn iter_test() {
         let pseudoinstruction = PseudoinstructionMappingIter::Three(
@@ -0,0 +1,74 @@
+//! Extraction of the fields of an encoded instruction without decoding it.
+use crate::registers::Register;
+
+use super::{funct3::Funct3, funct7::Funct7, rd::Rd, rs1::Rs1, rs2::Rs2, Instruction};
+
+/// An encoded 32-bit instruction whose fields can be read without decoding
+/// it into an [Instruction].
+///
+/// This is useful when only part of an instruction is needed, for example
+/// the op code to classify it. The fields are extracted at the positions of
+/// the `R`-type format, regardless of the instruction's actual format.
+///
+/// # Example
+///
+/// ```
+/// use riskv::{instructions::RawInstruction, registers::Register};
+///
+/// // add a0,a1,a2
+/// let raw = RawInstruction(0x00c5_8533);
+/// assert_eq!(raw.opcode(), 0b_0110011);
+/// assert_eq!(raw.rd(), Register::A0);
+/// assert_eq!(raw.rs1(), Register::A1);
+/// assert_eq!(raw.rs2(), Register::A2);
+/// assert_eq!(raw.funct3(), 0);
+/// assert_eq!(raw.funct7(), 0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct RawInstruction(pub u32);
+
+impl RawInstruction {
+    /// Bits `0 - 6` of the instruction, the `opcode`.
+    #[inline]
+    pub const fn opcode(self) -> u8 {
+        Instruction::op_code(self.0)
+    }
+
+    /// Bits `7 - 11` of the instruction, often the destination register `rd`.
+    #[inline]
+    pub const fn rd(self) -> Register {
+        Rd::decode(self.0)
+    }
+
+    /// Bits `12 - 14` of the instruction, `funct3`.
+    #[inline]
+    pub const fn funct3(self) -> u8 {
+        Funct3::decode(self.0)
+    }
+
+    /// Bits `15 - 19` of the instruction, often the source register `rs1`.
+    #[inline]
+    pub const fn rs1(self) -> Register {
+        Rs1::decode(self.0)
+    }
+
+    /// Bits `20 - 24` of the instruction, often the source register `rs2`.
+    #[inline]
+    pub const fn rs2(self) -> Register {
+        Rs2::decode(self.0)
+    }
+
+    /// Bits `25 - 31` of the instruction, `funct7`.
+    #[inline]
+    pub const fn funct7(self) -> u8 {
+        Funct7::decode(self.0)
+    }
+}
+
+impl From<u32> for RawInstruction {
+    #[inline]
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
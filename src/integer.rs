@@ -28,6 +28,7 @@ pub(crate) mod i12 {
 }
 
 /// The 13-bit signed integer type.
+#[allow(dead_code)]
 pub(crate) mod i13 {
     /// The largest value that can be represented by this integer type
     /// (2<sup>20</sup> &minus; 1).
@@ -40,6 +41,7 @@ pub(crate) mod i13 {
 }
 
 /// The 21-bit signed integer type.
+#[allow(dead_code)]
 pub(crate) mod i21 {
     /// The largest value that can be represented by this integer type
     /// (2<sup>20</sup> &minus; 1).
@@ -87,6 +89,20 @@ pub(crate) trait AsSigned<N> {
     fn as_signed(&self) -> N;
 }
 
+/// Returns `true` if `a` is less than `b` when both are interpreted as
+/// unsigned, as compared by `SLTU`, `SLTIU`, and `BLTU`.
+#[inline]
+pub(crate) fn lt_unsigned<T: AsUnsigned<U>, U: PartialOrd>(a: T, b: T) -> bool {
+    a.as_unsigned() < b.as_unsigned()
+}
+
+/// Returns `true` if `a` is greater than or equal to `b` when both are
+/// interpreted as unsigned, as compared by `BGEU`.
+#[inline]
+pub(crate) fn ge_unsigned<T: AsUnsigned<U>, U: PartialOrd>(a: T, b: T) -> bool {
+    a.as_unsigned() >= b.as_unsigned()
+}
+
 /// Conversion to a [usize].
 pub trait AsUsize {
     /// Convert this to a [usize].
@@ -195,6 +211,26 @@ mod test {
         assert_eq!(u128::MAX.as_signed(), -1);
     }
 
+    #[test]
+    fn lt_unsigned() {
+        assert!(super::lt_unsigned(0, -1));
+        assert!(!super::lt_unsigned(-1, 0));
+        assert!(!super::lt_unsigned(-1, -1));
+        assert!(super::lt_unsigned(i32::MAX, i32::MIN));
+        assert!(super::lt_unsigned(-2_i64, -1));
+        assert!(!super::lt_unsigned(1_i64, 0));
+    }
+
+    #[test]
+    fn ge_unsigned() {
+        assert!(super::ge_unsigned(-1, 0));
+        assert!(!super::ge_unsigned(0, -1));
+        assert!(super::ge_unsigned(-1, -1));
+        assert!(super::ge_unsigned(i32::MIN, i32::MAX));
+        assert!(!super::ge_unsigned(-2_i64, -1));
+        assert!(super::ge_unsigned(0_i64, 0));
+    }
+
     #[test]
     #[cfg(target_pointer_width = "32")]
     fn as_usize() {
@@ -211,9 +247,9 @@ mod test {
 
     #[test]
     fn sign_extend_i12_test() {
-        let neg_1 = 0b_1111_1111_1111 as i32;
-        let max_i12 = 0b_0111_1111_1111 as i32;
-        let min_i12 = 0b_1000_0000_0000 as i32;
+        let neg_1 = 0b_1111_1111_1111_i32;
+        let max_i12 = 0b_0111_1111_1111_i32;
+        let min_i12 = 0b_1000_0000_0000_i32;
         assert_eq!(i12::sign_extend(neg_1), -1);
         assert_eq!(i12::sign_extend(max_i12), i12::MAX);
         assert_eq!(i12::sign_extend(min_i12), i12::MIN);
@@ -221,8 +257,8 @@ mod test {
 
     #[test]
     fn is_positive_i12_test() {
-        let neg_1 = 0b_1111_1111_1111 as i32;
-        let max_i12 = 0b_0111_1111_1111 as i32;
+        let neg_1 = 0b_1111_1111_1111_i32;
+        let max_i12 = 0b_0111_1111_1111_i32;
         assert!(!i12::is_positive(neg_1));
         assert!(i12::is_positive(max_i12));
     }
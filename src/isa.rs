@@ -0,0 +1,296 @@
+//! Configuration of the instruction set architecture (ISA) of a processor.
+//!
+//! A RISC-V target is specified by an ISA string such as `rv32imac` or
+//! `RV64GC`, naming the register width followed by the base integer
+//! instruction set and the standard extensions. The extensions which may be
+//! named are listed in [Extension], and `G` is shorthand for `IMAFD` with the
+//! `Zicsr` and `Zifencei` extensions.
+//!
+//! Multi-letter extensions, such as `_zicsr`, are accepted but not recorded
+//! since they are always available.
+use std::{fmt::Display, str::FromStr};
+
+/// A single-letter standard extension of the RISC-V ISA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Extension {
+    /// The base integer instruction set.
+    I,
+    /// The reduced base integer instruction set for embedded systems.
+    E,
+    /// Integer multiplication and division.
+    M,
+    /// Atomic instructions.
+    A,
+    /// Single-precision floating-point.
+    F,
+    /// Double-precision floating-point.
+    D,
+    /// Quad-precision floating-point.
+    Q,
+    /// Compressed instructions.
+    C,
+    /// Vector operations.
+    V,
+}
+
+impl Extension {
+    /// The lowercase letter naming this extension.
+    pub const fn letter(self) -> char {
+        match self {
+            Extension::I => 'i',
+            Extension::E => 'e',
+            Extension::M => 'm',
+            Extension::A => 'a',
+            Extension::F => 'f',
+            Extension::D => 'd',
+            Extension::Q => 'q',
+            Extension::C => 'c',
+            Extension::V => 'v',
+        }
+    }
+
+    /// The extension named by the `letter`, ignoring case.
+    pub const fn from_letter(letter: char) -> Option<Self> {
+        match letter.to_ascii_lowercase() {
+            'i' => Some(Extension::I),
+            'e' => Some(Extension::E),
+            'm' => Some(Extension::M),
+            'a' => Some(Extension::A),
+            'f' => Some(Extension::F),
+            'd' => Some(Extension::D),
+            'q' => Some(Extension::Q),
+            'c' => Some(Extension::C),
+            'v' => Some(Extension::V),
+            _ => None,
+        }
+    }
+
+    /// The bit representing this extension, following the layout of the
+    /// `misa` CSR.
+    const fn bit(self) -> u32 {
+        1 << (self.letter() as u32 - 'a' as u32)
+    }
+}
+
+/// The register width and enabled extensions of a processor.
+///
+/// # Example
+///
+/// ```
+/// use riskv::isa::{Extension, Isa};
+///
+/// let isa: Isa = "rv32imac".parse().unwrap();
+/// assert_eq!(isa.xlen(), 32);
+/// assert!(isa.supports(Extension::M));
+/// assert!(!isa.supports(Extension::F));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Isa {
+    /// The width of the integer registers in bits.
+    xlen: u32,
+    /// The enabled extensions, one bit per letter as in the `misa` CSR.
+    extensions: u32,
+}
+
+/// The extensions of `RV32IM`, the default configuration, which includes all
+/// of the implemented extensions except the compressed extension, C, since
+/// that relaxes the alignment of instructions.
+impl Default for Isa {
+    fn default() -> Self {
+        Self {
+            xlen: 32,
+            extensions: Extension::I.bit() | Extension::M.bit(),
+        }
+    }
+}
+
+impl Isa {
+    /// The width of the integer registers in bits.
+    pub const fn xlen(&self) -> u32 {
+        self.xlen
+    }
+
+    /// The same extensions with registers `xlen` bits wide.
+    pub(crate) const fn with_xlen(self, xlen: u32) -> Self {
+        Self { xlen, ..self }
+    }
+
+    /// Returns `true` if the `extension` is enabled.
+    ///
+    /// The E base has the same instructions as the I base, only fewer
+    /// registers, so it supports [Extension::I].
+    pub const fn supports(&self, extension: Extension) -> bool {
+        let extensions = if self.extensions & Extension::E.bit() != 0 {
+            self.extensions | Extension::I.bit()
+        } else {
+            self.extensions
+        };
+        extensions & extension.bit() != 0
+    }
+
+    /// The alignment, in bytes, required of instruction addresses.
+    ///
+    /// This is 2 when the compressed extension, C, is enabled and 4
+    /// otherwise.
+    pub const fn instruction_alignment(&self) -> usize {
+        if self.supports(Extension::C) {
+            2
+        } else {
+            4
+        }
+    }
+}
+
+impl FromStr for Isa {
+    type Err = IsaError;
+
+    fn from_str(isa: &str) -> Result<Self, Self::Err> {
+        let lowercase = isa.to_ascii_lowercase();
+        let rest = lowercase
+            .strip_prefix("rv")
+            .ok_or(IsaError::MissingPrefix)?;
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        let (xlen, rest) = rest.split_at(digits);
+        let xlen = match xlen {
+            "32" => 32,
+            "64" => 64,
+            "128" => 128,
+            _ => return Err(IsaError::InvalidXlen(xlen.to_owned())),
+        };
+
+        let mut letters = rest.chars();
+        let mut extensions = match letters.next() {
+            Some('i') => Extension::I.bit(),
+            Some('e') => Extension::E.bit(),
+            Some('g') => [
+                Extension::I,
+                Extension::M,
+                Extension::A,
+                Extension::F,
+                Extension::D,
+            ]
+            .iter()
+            .fold(0, |extensions, extension| extensions | extension.bit()),
+            base => return Err(IsaError::InvalidBase(base)),
+        };
+        for segment in letters.as_str().split('_') {
+            for letter in segment.chars() {
+                // The remainder of the segment names a multi-letter extension.
+                if matches!(letter, 'z' | 's' | 'x') {
+                    break;
+                }
+                let extension =
+                    Extension::from_letter(letter).ok_or(IsaError::UnknownExtension(letter))?;
+                extensions |= extension.bit();
+            }
+        }
+        Ok(Self { xlen, extensions })
+    }
+}
+
+/// The error returned when an ISA string cannot be parsed, or does not match
+/// the processor, see [crate::processor::Processor::with_isa].
+#[derive(Debug, PartialEq, Eq)]
+pub enum IsaError {
+    /// The ISA string does not start with `rv`.
+    MissingPrefix,
+    /// The register width is not one of `32`, `64`, or `128`.
+    InvalidXlen(String),
+    /// The base integer instruction set is not one of `i`, `e`, or `g`.
+    InvalidBase(Option<char>),
+    /// The letter does not name a supported extension.
+    UnknownExtension(char),
+    /// The register width does not match the processor's register type.
+    XlenMismatch {
+        /// The width of the processor's registers in bits.
+        expected: u32,
+        /// The width named by the ISA string.
+        found: u32,
+    },
+}
+
+impl Display for IsaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingPrefix => f.write_str("ISA string must start with 'rv'"),
+            Self::InvalidXlen(xlen) => write!(f, "invalid register width '{xlen}'"),
+            Self::InvalidBase(Some(base)) => write!(f, "invalid base instruction set '{base}'"),
+            Self::InvalidBase(None) => f.write_str("missing base instruction set"),
+            Self::UnknownExtension(letter) => write!(f, "unknown extension '{letter}'"),
+            Self::XlenMismatch { expected, found } => write!(
+                f,
+                "register width {found} does not match the {expected}-bit processor"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IsaError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_isa() {
+        let isa: Isa = "rv32imac".parse().unwrap();
+        assert_eq!(isa.xlen(), 32);
+        for extension in [Extension::I, Extension::M, Extension::A, Extension::C] {
+            assert!(isa.supports(extension), "{extension:?}");
+        }
+        for extension in [Extension::E, Extension::F, Extension::D, Extension::V] {
+            assert!(!isa.supports(extension), "{extension:?}");
+        }
+        assert_eq!(isa.instruction_alignment(), 2);
+    }
+
+    #[test]
+    fn embedded_base_supports_base_instructions() {
+        let isa: Isa = "rv32e".parse().unwrap();
+        assert!(isa.supports(Extension::E));
+        assert!(isa.supports(Extension::I));
+        assert!(!isa.supports(Extension::M));
+    }
+
+    #[test]
+    fn parse_general_purpose_isa() {
+        let isa: Isa = "RV64GC".parse().unwrap();
+        assert_eq!(isa.xlen(), 64);
+        for extension in [
+            Extension::I,
+            Extension::M,
+            Extension::A,
+            Extension::F,
+            Extension::D,
+            Extension::C,
+        ] {
+            assert!(isa.supports(extension), "{extension:?}");
+        }
+        assert!(!isa.supports(Extension::Q));
+    }
+
+    #[test]
+    fn parse_multi_letter_extensions() {
+        assert_eq!(
+            "rv32i_zicsr_zifencei".parse::<Isa>(),
+            "rv32i".parse::<Isa>()
+        );
+        assert_eq!("rv32im_zicsr_c".parse::<Isa>(), "rv32imc".parse::<Isa>());
+        assert_eq!("rv32i".parse::<Isa>().unwrap().instruction_alignment(), 4);
+    }
+
+    #[test]
+    fn parse_isa_errors() {
+        for (isa, error) in [
+            ("x86", IsaError::MissingPrefix),
+            ("rv16i", IsaError::InvalidXlen("16".to_owned())),
+            ("rvi", IsaError::InvalidXlen(String::new())),
+            ("rv32", IsaError::InvalidBase(None)),
+            ("rv32m", IsaError::InvalidBase(Some('m'))),
+            ("rv32imk", IsaError::UnknownExtension('k')),
+        ] {
+            assert_eq!(isa.parse::<Isa>(), Err(error), "{isa}");
+        }
+    }
+}
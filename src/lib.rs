@@ -6,12 +6,20 @@
     rustdoc::invalid_rust_codeblocks
 )]
 
+pub mod asm;
 pub mod csr;
+pub mod devices;
 pub mod instruction_set;
-mod instructions;
+pub mod instructions;
 mod integer;
-mod memory;
+pub mod isa;
+pub mod memory;
+pub mod mmu;
+pub mod privilege;
 pub mod processor;
-mod registers;
+pub mod profile;
+pub mod registers;
 #[cfg(any(test, doc))]
 mod test;
+
+pub use processor::{Rv32Processor, Rv64Processor};
@@ -1,64 +1,673 @@
 //! The computer's memory.
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::instruction_set::Exception;
 use crate::integer::AsSigned;
+use crate::mmu::Access;
+
+/// The access permissions of a region of memory.
+///
+/// Protections restrict whether a region can be read, written, or have
+/// instructions fetched from it.
+///
+/// _Note_: the load methods of [Memory] do not check the protection, it is
+/// checked by the load instructions, see [MemoryBus::check_readable].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::enum_variant_names)]
+pub enum Protection {
+    /// The region can be read, written, and have instructions fetched from it.
+    ///
+    /// This is the protection of any memory which has not been explicitly
+    /// protected.
+    ReadWriteExecute,
+    /// The region can be read and written, however, fetching an instruction
+    /// from it raises an [Exception::InstructionAccessFault].
+    ///
+    /// This is useful for data regions such as the stack and heap, catching a
+    /// runaway programme counter.
+    ReadWrite,
+    /// The region can be read and have instructions fetched from it, however,
+    /// any store into the region raises an [Exception::StoreAccessFault].
+    ///
+    /// This is useful for protecting a programme's code segment from being
+    /// accidentally overwritten.
+    ReadExecute,
+    /// The region can only be read, any store into the region raises an
+    /// [Exception::StoreAccessFault] and fetching an instruction from it raises
+    /// an [Exception::InstructionAccessFault].
+    ReadOnly,
+    /// The region cannot be accessed, any load from the region raises an
+    /// [Exception::MemoryAccessFault], any store an
+    /// [Exception::StoreAccessFault], and fetching an instruction an
+    /// [Exception::InstructionAccessFault].
+    ///
+    /// This is useful for a guard page, for example at address `0` to catch
+    /// null pointer dereferences. To map address `0` to memory-mapped I/O
+    /// instead, wrap the memory in a device mapped there, see
+    /// [crate::devices].
+    NoAccess,
+}
+
+impl Protection {
+    /// Returns `true` if loads from a region with this protection are allowed.
+    #[inline]
+    pub const fn is_readable(&self) -> bool {
+        !matches!(self, Self::NoAccess)
+    }
+
+    /// Returns `true` if stores into a region with this protection are allowed.
+    #[inline]
+    pub const fn is_writable(&self) -> bool {
+        matches!(self, Self::ReadWriteExecute | Self::ReadWrite)
+    }
+
+    /// Returns `true` if instructions can be fetched from a region with this
+    /// protection.
+    #[inline]
+    pub const fn is_executable(&self) -> bool {
+        matches!(self, Self::ReadWriteExecute | Self::ReadExecute)
+    }
+}
+
+/// A load or store performed by an instruction, see
+/// [crate::processor::Processor::set_memory_tracer].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemAccess {
+    /// The physical address of the access.
+    pub address: usize,
+    /// The number of bytes accessed.
+    pub width: usize,
+    /// Whether the access is a [Access::Load] or an [Access::Store].
+    pub access: Access,
+    /// The value loaded or stored, zero-extended from `width` bytes.
+    pub value: u32,
+}
+
+/// The interface between the processor and its memory, allowing the default
+/// [Memory] to be replaced by another backend, for example one backed by a
+/// memory-mapped file or with memory-mapped devices.
+///
+/// All addresses are physical, any virtual memory translation having been
+/// applied by the processor, see [crate::mmu].
+///
+/// Only the byte accesses, [MemoryBus::peek_word], and [MemoryBus::write]
+/// need to be implemented. The wider accesses are provided in terms of the
+/// byte accesses, little endian, and the fetches and [MemoryBus::check_readable]
+/// permit every access.
+pub trait MemoryBus {
+    /// Get 8 bits of memory zero-extended
+    fn load_byte_unsigned(&mut self, location: usize) -> u8;
+
+    /// Set 8 bits of memory
+    fn store_byte(&mut self, location: usize, value: i8) -> Result<(), Exception>;
+
+    /// Get 32 bits of memory zero-extended without any side effects, used to
+    /// inspect memory, for example by
+    /// [crate::processor::Processor::disassemble_text].
+    fn peek_word(&self, location: usize) -> u32;
+
+    /// Write the `bytes` into memory starting at `location` ignoring any
+    /// protection.
+    ///
+    /// This is used when loading a programme into memory in the same way a
+    /// loader would populate a code segment before protecting it.
+    fn write(&mut self, location: usize, bytes: &[u8]);
+
+    /// Get 8 bits of memory
+    fn load_byte(&mut self, location: usize) -> i8 {
+        self.load_byte_unsigned(location).as_signed()
+    }
+
+    /// Get 16 bits of memory
+    fn load_half(&mut self, location: usize) -> i16 {
+        self.load_half_unsigned(location).as_signed()
+    }
+
+    /// Get 32 bits of memory
+    fn load_word(&mut self, location: usize) -> i32 {
+        self.load_word_unsigned(location).as_signed()
+    }
+
+    /// Get 64 bits of memory
+    fn load_double(&mut self, location: usize) -> i64 {
+        i64::from_le_bytes(std::array::from_fn(|offset| {
+            self.load_byte_unsigned(location.wrapping_add(offset))
+        }))
+    }
+
+    /// Get 16 bits of memory zero-extended
+    fn load_half_unsigned(&mut self, location: usize) -> u16 {
+        u16::from_le_bytes(std::array::from_fn(|offset| {
+            self.load_byte_unsigned(location.wrapping_add(offset))
+        }))
+    }
+
+    /// Get 32 bits of memory zero-extended
+    fn load_word_unsigned(&mut self, location: usize) -> u32 {
+        u32::from_le_bytes(std::array::from_fn(|offset| {
+            self.load_byte_unsigned(location.wrapping_add(offset))
+        }))
+    }
+
+    /// Set 16 bits of memory
+    ///
+    /// By default the bytes are stored one at a time, so a fault part way
+    /// through leaves the preceding bytes stored.
+    fn store_half(&mut self, location: usize, value: i16) -> Result<(), Exception> {
+        value
+            .to_le_bytes()
+            .into_iter()
+            .enumerate()
+            .try_for_each(|(offset, byte)| {
+                self.store_byte(location.wrapping_add(offset), byte.as_signed())
+            })
+    }
+
+    /// Set 32 bits of memory
+    ///
+    /// By default the bytes are stored one at a time, so a fault part way
+    /// through leaves the preceding bytes stored.
+    fn store_word(&mut self, location: usize, value: i32) -> Result<(), Exception> {
+        value
+            .to_le_bytes()
+            .into_iter()
+            .enumerate()
+            .try_for_each(|(offset, byte)| {
+                self.store_byte(location.wrapping_add(offset), byte.as_signed())
+            })
+    }
+
+    /// Set 64 bits of memory
+    ///
+    /// By default the bytes are stored one at a time, so a fault part way
+    /// through leaves the preceding bytes stored.
+    fn store_double(&mut self, location: usize, value: i64) -> Result<(), Exception> {
+        value
+            .to_le_bytes()
+            .into_iter()
+            .enumerate()
+            .try_for_each(|(offset, byte)| {
+                self.store_byte(location.wrapping_add(offset), byte.as_signed())
+            })
+    }
+
+    /// Fetch the 16-bit instruction parcel at `location`.
+    ///
+    /// Instructions are made up of one or more 16-bit parcels, the lowest
+    /// bits of the first parcel determining the length of the instruction.
+    fn fetch_parcel(&mut self, location: usize) -> Result<u16, Exception> {
+        Ok(self.load_half_unsigned(location))
+    }
+
+    /// Fetch the 32-bit instruction at `location`.
+    fn fetch_instruction(&mut self, location: usize) -> Result<u32, Exception> {
+        Ok(self.load_word_unsigned(location))
+    }
+
+    /// Check that all `width` bytes from `location` can be read, raising
+    /// [Exception::MemoryAccessFault] otherwise.
+    ///
+    /// This is checked by the load instructions before loading.
+    fn check_readable(&self, _location: usize, _width: usize) -> Result<(), Exception> {
+        Ok(())
+    }
+
+    /// Allocate the memory up to `end` ahead of it being accessed, for
+    /// backends which grow on access such as [Memory].
+    ///
+    /// This is called after a programme has been stored, by default it does
+    /// nothing.
+    fn reserve(&mut self, _end: usize) {}
+
+    /// Take the exit status requested by a memory-mapped device, such as a
+    /// [crate::devices::ToHostDevice], since this was last called.
+    ///
+    /// This is checked after each instruction retires, stopping execution
+    /// with [crate::processor::ExecutionResult::Exited]. By default no exit is
+    /// ever requested.
+    fn take_exit_request(&mut self) -> Option<i32> {
+        None
+    }
+}
 
 /// An expandable implementation of the computer's memory.
 ///
 /// The bytes of memory are stored as little endian.
 ///
-// TODO: consider making memory a trait so we can support different endianess
-// and fixed size.
+/// Cloning a memory is cheap, the clones share their pages of bytes until one
+/// of them writes to a page, when only that page is copied.
 #[derive(Debug, Default, PartialEq, Eq, Clone)]
 pub struct Memory {
-    /// The raw bytes of the memory
-    data: Vec<u8>,
+    /// The pages of raw bytes of the memory, each shared copy-on-write
+    /// between clones.
+    ///
+    /// The bytes of the last page beyond [Memory::len] hold the fill byte.
+    pages: Vec<Arc<[u8; PAGE_SIZE]>>,
+    /// The number of bytes of memory allocated so far, see [Memory::len].
+    len: usize,
+    /// The protected regions of memory.
+    ///
+    /// When regions overlap, the most recently added region takes precedence.
+    protected_regions: Vec<(Range<usize>, Protection)>,
+    /// The byte newly grown regions of memory are filled with, see
+    /// [Memory::with_fill].
+    fill: u8,
 }
 
-impl Memory {
-    /// Get 8 bits of memory
-    pub fn load_byte(&mut self, location: usize) -> i8 {
+impl MemoryBus for Memory {
+    fn load_byte_unsigned(&mut self, location: usize) -> u8 {
         self.resize::<1>(location);
-        self.data[location].as_signed()
+        self.byte(location)
     }
 
-    /// Get 16 bits of memory
-    pub fn load_half(&mut self, location: usize) -> i16 {
+    fn store_byte(&mut self, location: usize, value: i8) -> Result<(), Exception> {
+        self.check_writable::<1>(location)?;
+        self.write(location, &value.to_le_bytes());
+        Ok(())
+    }
+
+    /// Bytes beyond the end of the memory read as the fill byte, see
+    /// [Memory::with_fill].
+    fn peek_word(&self, location: usize) -> u32 {
+        u32::from_le_bytes(std::array::from_fn(|offset| {
+            location
+                .checked_add(offset)
+                .map_or(self.fill, |location| self.byte(location))
+        }))
+    }
+
+    /// Only the pages written are copied if they are shared with a clone.
+    /// Bytes beyond [Memory::MAX_LEN] are discarded rather than allocated.
+    fn write(&mut self, location: usize, bytes: &[u8]) {
+        let bytes = &bytes[..bytes.len().min(Self::MAX_LEN.saturating_sub(location))];
+        if bytes.is_empty() {
+            return;
+        }
+        self.reserve(location + bytes.len());
+        let (mut location, mut bytes) = (location, bytes);
+        while !bytes.is_empty() {
+            let offset = location % PAGE_SIZE;
+            let count = bytes.len().min(PAGE_SIZE - offset);
+            Arc::make_mut(&mut self.pages[location / PAGE_SIZE])[offset..offset + count]
+                .copy_from_slice(&bytes[..count]);
+            location += count;
+            bytes = &bytes[count..];
+        }
+    }
+
+    fn load_byte(&mut self, location: usize) -> i8 {
+        self.resize::<1>(location);
+        self.byte(location).as_signed()
+    }
+
+    fn load_half(&mut self, location: usize) -> i16 {
         self.resize::<2>(location);
-        i16::from_le_bytes(self.data[location..location + 2].try_into().unwrap())
+        i16::from_le_bytes(self.read(location))
     }
 
-    /// Get 32 bits of memory
-    pub fn load_word(&mut self, location: usize) -> i32 {
+    fn load_word(&mut self, location: usize) -> i32 {
         self.resize::<4>(location);
-        i32::from_le_bytes(self.data[location..location + 4].try_into().unwrap())
+        i32::from_le_bytes(self.read(location))
     }
 
-    /// Set 32 bits of memory
-    pub fn store_byte(&mut self, location: usize, value: i8) {
-        self.resize::<1>(location);
-        self.data[location..location + 1].copy_from_slice(&value.to_le_bytes());
+    fn load_double(&mut self, location: usize) -> i64 {
+        self.resize::<8>(location);
+        i64::from_le_bytes(self.read(location))
     }
 
-    /// Set 32 bits of memory
-    pub fn store_half(&mut self, location: usize, value: i16) {
+    fn load_half_unsigned(&mut self, location: usize) -> u16 {
         self.resize::<2>(location);
-        self.data[location..location + 2].copy_from_slice(&value.to_le_bytes());
+        u16::from_le_bytes(self.read(location))
     }
 
-    /// Set 32 bits of memory
-    pub fn store_word(&mut self, location: usize, value: i32) {
+    fn load_word_unsigned(&mut self, location: usize) -> u32 {
         self.resize::<4>(location);
-        self.data[location..location + 4].copy_from_slice(&value.to_le_bytes());
+        u32::from_le_bytes(self.read(location))
+    }
+
+    /// The store raises [Exception::StoreAccessFault] without storing any of
+    /// the bytes if any of them is not writable.
+    fn store_half(&mut self, location: usize, value: i16) -> Result<(), Exception> {
+        self.check_writable::<2>(location)?;
+        self.write(location, &value.to_le_bytes());
+        Ok(())
+    }
+
+    /// The store raises [Exception::StoreAccessFault] without storing any of
+    /// the bytes if any of them is not writable.
+    fn store_word(&mut self, location: usize, value: i32) -> Result<(), Exception> {
+        self.check_writable::<4>(location)?;
+        self.write(location, &value.to_le_bytes());
+        Ok(())
+    }
+
+    /// The store raises [Exception::StoreAccessFault] without storing any of
+    /// the bytes if any of them is not writable.
+    fn store_double(&mut self, location: usize, value: i64) -> Result<(), Exception> {
+        self.check_writable::<8>(location)?;
+        self.write(location, &value.to_le_bytes());
+        Ok(())
+    }
+
+    /// Unlike [MemoryBus::load_half_unsigned] this checks the memory at
+    /// `location` is executable.
+    fn fetch_parcel(&mut self, location: usize) -> Result<u16, Exception> {
+        self.check_executable::<2>(location)?;
+        Ok(self.load_half_unsigned(location))
+    }
+
+    /// Unlike [MemoryBus::load_word] this checks the memory at `location` is
+    /// executable.
+    fn fetch_instruction(&mut self, location: usize) -> Result<u32, Exception> {
+        self.check_executable::<4>(location)?;
+        Ok(self.load_word_unsigned(location))
+    }
+
+    fn check_readable(&self, location: usize, width: usize) -> Result<(), Exception> {
+        let end = Self::end(location, width).ok_or(Exception::MemoryAccessFault)?;
+        if self.protected_regions.is_empty()
+            || (location..end).all(|location| self.protection(location).is_readable())
+        {
+            Ok(())
+        } else {
+            Err(Exception::MemoryAccessFault)
+        }
+    }
+
+    /// The new pages share a single page of the fill byte until they are
+    /// written.
+    fn reserve(&mut self, end: usize) {
+        if end > self.len {
+            let pages = end.div_ceil(PAGE_SIZE);
+            if pages > self.pages.len() {
+                let fill = Arc::new([self.fill; PAGE_SIZE]);
+                self.pages.resize(pages, fill);
+            }
+            self.len = end;
+        }
+    }
+}
+
+impl Memory {
+    /// The largest memory, 2 GiB, which is allocated.
+    ///
+    /// Since memory is allocated up to the highest address accessed, loads,
+    /// stores, and fetches beyond this fault rather than allocating
+    /// gigabytes, for example for a negative effective address. Use
+    /// [SparseMemory] for programmes at high addresses.
+    pub const MAX_LEN: usize = 1 << 31;
+
+    /// Create an empty memory whose newly grown regions are filled with the
+    /// `byte`, rather than zero.
+    ///
+    /// A poison pattern, such as `0xCD`, makes reads of uninitialised memory
+    /// easy to spot. Filling with `0xFF` additionally makes a programme
+    /// counter which runs off the end of a programme raise
+    /// [Exception::UnimplementedInstruction], since an all ones instruction
+    /// is illegal.
+    pub fn with_fill(byte: u8) -> Self {
+        Self {
+            fill: byte,
+            ..Self::default()
+        }
+    }
+
+    /// Get 8 bits of memory, see [MemoryBus::load_byte].
+    pub fn load_byte(&mut self, location: usize) -> i8 {
+        MemoryBus::load_byte(self, location)
+    }
+
+    /// Get 16 bits of memory, see [MemoryBus::load_half].
+    pub fn load_half(&mut self, location: usize) -> i16 {
+        MemoryBus::load_half(self, location)
+    }
+
+    /// Get 32 bits of memory, see [MemoryBus::load_word].
+    pub fn load_word(&mut self, location: usize) -> i32 {
+        MemoryBus::load_word(self, location)
+    }
+
+    /// Get 64 bits of memory, see [MemoryBus::load_double].
+    pub fn load_double(&mut self, location: usize) -> i64 {
+        MemoryBus::load_double(self, location)
+    }
+
+    /// Get 8 bits of memory zero-extended, see [MemoryBus::load_byte_unsigned].
+    pub fn load_byte_unsigned(&mut self, location: usize) -> u8 {
+        MemoryBus::load_byte_unsigned(self, location)
+    }
+
+    /// Get 16 bits of memory zero-extended, see [MemoryBus::load_half_unsigned].
+    pub fn load_half_unsigned(&mut self, location: usize) -> u16 {
+        MemoryBus::load_half_unsigned(self, location)
+    }
+
+    /// Get 32 bits of memory zero-extended, see [MemoryBus::load_word_unsigned].
+    pub fn load_word_unsigned(&mut self, location: usize) -> u32 {
+        MemoryBus::load_word_unsigned(self, location)
+    }
+
+    /// Get 32 bits of memory zero-extended without growing the memory, see
+    /// [MemoryBus::peek_word].
+    pub fn peek_word(&self, location: usize) -> u32 {
+        MemoryBus::peek_word(self, location)
+    }
+
+    /// Set 8 bits of memory, see [MemoryBus::store_byte].
+    pub fn store_byte(&mut self, location: usize, value: i8) -> Result<(), Exception> {
+        MemoryBus::store_byte(self, location, value)
+    }
+
+    /// Set 16 bits of memory, see [MemoryBus::store_half].
+    pub fn store_half(&mut self, location: usize, value: i16) -> Result<(), Exception> {
+        MemoryBus::store_half(self, location, value)
+    }
+
+    /// Set 32 bits of memory, see [MemoryBus::store_word].
+    pub fn store_word(&mut self, location: usize, value: i32) -> Result<(), Exception> {
+        MemoryBus::store_word(self, location, value)
+    }
+
+    /// Set 64 bits of memory, see [MemoryBus::store_double].
+    pub fn store_double(&mut self, location: usize, value: i64) -> Result<(), Exception> {
+        MemoryBus::store_double(self, location, value)
+    }
+
+    /// Fetch the 16-bit instruction parcel at `location`, see
+    /// [MemoryBus::fetch_parcel].
+    pub fn fetch_parcel(&mut self, location: usize) -> Result<u16, Exception> {
+        MemoryBus::fetch_parcel(self, location)
+    }
+
+    /// Fetch the 32-bit instruction at `location`, see
+    /// [MemoryBus::fetch_instruction].
+    pub fn fetch_instruction(&mut self, location: usize) -> Result<u32, Exception> {
+        MemoryBus::fetch_instruction(self, location)
+    }
+
+    /// The number of bytes of memory allocated so far.
+    ///
+    /// Memory grows as it is accessed, so this is one past the highest
+    /// address accessed, or reserved with [MemoryBus::reserve].
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no memory has been allocated yet.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The `count` bytes from `start`, for example for a hex dump.
+    ///
+    /// Unlike the load methods this does not grow the memory, bytes beyond
+    /// its end read as the fill byte, see [Memory::with_fill].
+    pub fn bytes(&self, start: usize, count: usize) -> impl Iterator<Item = u8> + '_ {
+        (0..count).map(move |offset| {
+            start
+                .checked_add(offset)
+                .map_or(self.fill, |location| self.byte(location))
+        })
+    }
+
+    /// The `count` little endian words from `start`, for example for a hex
+    /// dump, see [Memory::bytes].
+    pub fn words(&self, start: usize, count: usize) -> impl Iterator<Item = i32> + '_ {
+        (0..count).map(move |index| {
+            self.peek_word(start.wrapping_add(index.wrapping_mul(4)))
+                .as_signed()
+        })
+    }
+
+    /// Protect the `range` of memory with the given `protection`.
+    ///
+    /// If the `range` overlaps a previously protected region, the new
+    /// `protection` takes precedence for the overlapping bytes.
+    pub fn protect(&mut self, range: Range<usize>, protection: Protection) {
+        self.protected_regions.push((range, protection));
+    }
+
+    /// The protection of the byte at `location`.
+    pub fn protection(&self, location: usize) -> Protection {
+        self.protected_regions
+            .iter()
+            .rev()
+            .find(|(range, _)| range.contains(&location))
+            .map_or(Protection::ReadWriteExecute, |(_, protection)| *protection)
+    }
+
+    /// A summary of the contiguous runs of non-zero bytes in memory, one run
+    /// per line formatted as `[start..end] (len bytes)`.
+    ///
+    /// This is useful to see where a programme was loaded and what it wrote
+    /// without dumping every byte.
+    ///
+    /// _Note_: a zero byte within a run of data, for example in the encoding
+    /// of an instruction, splits the run.
+    pub fn map_summary(&self) -> String {
+        let mut summary = String::new();
+        for (start, run) in self.non_zero_runs() {
+            if !summary.is_empty() {
+                summary.push('\n');
+            }
+            let end = start + run.len();
+            summary.push_str(&format!("[{start:#x}..{end:#x}] ({} bytes)", run.len()));
+        }
+        summary
+    }
+
+    /// The bytes whose values differ from `other`, each with its address and
+    /// its value in `self` followed by its value in `other`.
+    ///
+    /// Bytes beyond the end of either memory are compared as its fill byte,
+    /// see [Memory::with_fill].
+    pub(crate) fn diff(&self, other: &Memory) -> Vec<(usize, u8, u8)> {
+        (0..self.len.max(other.len))
+            .map(|location| (location, self.byte(location), other.byte(location)))
+            .filter(|(_, byte, other)| byte != other)
+            .collect()
+    }
+
+    /// The contiguous runs of non-zero bytes in memory, with the address of
+    /// the start of each run.
+    pub(crate) fn non_zero_runs(&self) -> impl Iterator<Item = (usize, Vec<u8>)> + '_ {
+        let mut start = 0;
+        std::iter::from_fn(move || {
+            let run_start = (start..self.len).find(|location| self.byte(*location) != 0)?;
+            start = (run_start..self.len)
+                .find(|location| self.byte(*location) == 0)
+                .unwrap_or(self.len);
+            Some((
+                run_start,
+                (run_start..start)
+                    .map(|location| self.byte(location))
+                    .collect(),
+            ))
+        })
+    }
+
+    /// The byte at `location`, or the fill byte beyond the end of the memory,
+    /// see [Memory::with_fill].
+    #[inline]
+    fn byte(&self, location: usize) -> u8 {
+        self.pages
+            .get(location / PAGE_SIZE)
+            .map_or(self.fill, |page| page[location % PAGE_SIZE])
+    }
+
+    /// The `N` bytes from `location`, the bytes beyond the end of the memory
+    /// reading as the fill byte.
+    #[inline]
+    fn read<const N: usize>(&self, location: usize) -> [u8; N] {
+        let offset = location % PAGE_SIZE;
+        match self
+            .pages
+            .get(location / PAGE_SIZE)
+            .and_then(|page| page.get(offset..offset + N))
+        {
+            Some(bytes) => bytes.try_into().unwrap(),
+            // The bytes cross into the next page, or beyond the end.
+            None => std::array::from_fn(|index| {
+                location
+                    .checked_add(index)
+                    .map_or(self.fill, |location| self.byte(location))
+            }),
+        }
+    }
+
+    /// The end of the `width` bytes from `location`, or `None` if they
+    /// extend beyond [Memory::MAX_LEN].
+    #[inline]
+    const fn end(location: usize, width: usize) -> Option<usize> {
+        match location.checked_add(width) {
+            Some(end) if end <= Self::MAX_LEN => Some(end),
+            _ => None,
+        }
+    }
+
+    /// Check that all `N` bytes from `location` can be written.
+    ///
+    /// Raises [Exception::StoreAccessFault] if the bytes extend beyond
+    /// [Memory::MAX_LEN], rather than allocating them.
+    #[inline]
+    fn check_writable<const N: usize>(&self, location: usize) -> Result<(), Exception> {
+        let end = Self::end(location, N).ok_or(Exception::StoreAccessFault)?;
+        if self.protected_regions.is_empty()
+            || (location..end).all(|location| self.protection(location).is_writable())
+        {
+            Ok(())
+        } else {
+            Err(Exception::StoreAccessFault)
+        }
+    }
+
+    /// Check that all `N` bytes from `location` can be executed.
+    #[inline]
+    fn check_executable<const N: usize>(&self, location: usize) -> Result<(), Exception> {
+        let end = Self::end(location, N).ok_or(Exception::InstructionAccessFault)?;
+        if self.protected_regions.is_empty()
+            || (location..end).all(|location| self.protection(location).is_executable())
+        {
+            Ok(())
+        } else {
+            Err(Exception::InstructionAccessFault)
+        }
     }
 
     /// Resize this memory
     ///
     /// If `location + N` is greater than `len`, the `Memory` is extended by the
-    /// difference, with each additional slot filled with `value`.
-    /// If `location` is less than `len`, this method does nothing.
+    /// difference, with each additional slot filled with the fill byte, see
+    /// [Memory::with_fill].
+    /// If `location` is less than `len`, or the bytes extend beyond
+    /// [Memory::MAX_LEN], this method does nothing, and the bytes beyond the
+    /// end read as the fill byte.
     #[inline]
-    pub(super) fn resize<const N: usize>(&mut self, location: usize) {
-        if location + N > self.data.len() {
-            self.data.resize(location + N, 0);
+    fn resize<const N: usize>(&mut self, location: usize) {
+        if let Some(end) = Self::end(location, N) {
+            self.reserve(end);
         }
     }
 
@@ -69,12 +678,83 @@ impl Memory {
     /// `diff` rather than the whole state of the memory including all the
     /// programmes instructions.
     #[cfg(test)]
-    pub(crate) fn with_initial_state(&mut self, Self { mut data }: Self) {
-        std::mem::swap(&mut self.data, &mut data);
+    pub(crate) fn with_initial_state(&mut self, Self { pages, len, .. }: Self) {
+        let current = std::mem::replace(&mut self.pages, pages);
+        let current_len = std::mem::replace(&mut self.len, len);
 
-        data.into_iter()
-            .enumerate()
-            .for_each(|(i, value)| self.store_byte(i, value as i8));
+        for (index, page) in current.iter().enumerate() {
+            let start = index * PAGE_SIZE;
+            self.write(start, &page[..current_len.min(start + PAGE_SIZE) - start]);
+        }
+    }
+}
+
+/// The number of bytes in each page allocated by [Memory] and [SparseMemory].
+const PAGE_SIZE: usize = 4096;
+
+/// A sparse implementation of the computer's memory, which only allocates the
+/// pages of memory which have been written.
+///
+/// [Memory] allocates every byte up to the highest address accessed, so a
+/// programme linked at a high address, such as `0x8000_0000`, would allocate
+/// gigabytes. Unwritten memory reads as zero, and memory protection is not
+/// supported.
+///
+/// # Example
+///
+/// ```
+/// use riskv::memory::{MemoryBus, SparseMemory};
+///
+/// let mut memory = SparseMemory::default();
+/// memory.store_word(0x8000_0000, 42).unwrap();
+///
+/// assert_eq!(memory.load_word(0x8000_0000), 42);
+/// assert_eq!(memory.page_count(), 1);
+/// ```
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct SparseMemory {
+    /// The allocated pages, keyed by their page number.
+    pages: BTreeMap<usize, Box<[u8; PAGE_SIZE]>>,
+}
+
+impl MemoryBus for SparseMemory {
+    fn load_byte_unsigned(&mut self, location: usize) -> u8 {
+        self.byte(location)
+    }
+
+    fn store_byte(&mut self, location: usize, value: i8) -> Result<(), Exception> {
+        self.write(location, &value.to_le_bytes());
+        Ok(())
+    }
+
+    fn peek_word(&self, location: usize) -> u32 {
+        u32::from_le_bytes(std::array::from_fn(|offset| {
+            self.byte(location.wrapping_add(offset))
+        }))
+    }
+
+    fn write(&mut self, location: usize, bytes: &[u8]) {
+        for (offset, byte) in bytes.iter().enumerate() {
+            let location = location.wrapping_add(offset);
+            self.pages
+                .entry(location / PAGE_SIZE)
+                .or_insert_with(|| Box::new([0; PAGE_SIZE]))[location % PAGE_SIZE] = *byte;
+        }
+    }
+}
+
+impl SparseMemory {
+    /// The number of pages of memory allocated so far, each of 4096 bytes.
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// The byte at `location`, zero if its page has not been allocated.
+    #[inline]
+    fn byte(&self, location: usize) -> u8 {
+        self.pages
+            .get(&(location / PAGE_SIZE))
+            .map_or(0, |page| page[location % PAGE_SIZE])
     }
 }
 
@@ -86,10 +766,10 @@ mod test {
     #[test]
     fn store_and_load_word() {
         let mut mem = Memory::default();
-        mem.store_word(24, 54);
-        mem.store_word(50, i32::MAX);
-        mem.store_word(54, i32::MIN);
-        mem.store_word(58, -1);
+        mem.store_word(24, 54).unwrap();
+        mem.store_word(50, i32::MAX).unwrap();
+        mem.store_word(54, i32::MIN).unwrap();
+        mem.store_word(58, -1).unwrap();
         assert_eq!(mem.load_word(24), 54);
         assert_eq!(mem.load_word(50), i32::MAX);
         assert_eq!(mem.load_word(54), i32::MIN);
@@ -99,10 +779,10 @@ mod test {
     #[test]
     fn store_and_load_byte() {
         let mut mem = Memory::default();
-        mem.store_byte(42, 54);
-        mem.store_byte(43, i8::MAX);
-        mem.store_byte(44, i8::MIN);
-        mem.store_byte(45, -1);
+        mem.store_byte(42, 54).unwrap();
+        mem.store_byte(43, i8::MAX).unwrap();
+        mem.store_byte(44, i8::MIN).unwrap();
+        mem.store_byte(45, -1).unwrap();
         assert_eq!(mem.load_byte(42), 54);
         assert_eq!(mem.load_byte(43), i8::MAX);
         assert_eq!(mem.load_byte(44), i8::MIN);
@@ -112,20 +792,33 @@ mod test {
     #[test]
     fn store_and_load_half() {
         let mut mem = Memory::default();
-        mem.store_half(68, 54);
-        mem.store_half(70, i16::MAX);
-        mem.store_half(72, i16::MIN);
-        mem.store_half(74, -1);
+        mem.store_half(68, 54).unwrap();
+        mem.store_half(70, i16::MAX).unwrap();
+        mem.store_half(72, i16::MIN).unwrap();
+        mem.store_half(74, -1).unwrap();
         assert_eq!(mem.load_half(68), 54);
         assert_eq!(mem.load_half(70), i16::MAX);
         assert_eq!(mem.load_half(72), i16::MIN);
         assert_eq!(mem.load_half(74), -1);
     }
 
+    #[test]
+    fn store_and_load_double() {
+        let mut mem = Memory::default();
+        mem.store_double(8, 54).unwrap();
+        mem.store_double(17, i64::MAX).unwrap();
+        mem.store_double(25, i64::MIN).unwrap();
+        assert_eq!(mem.load_double(8), 54);
+        assert_eq!(mem.load_double(17), i64::MAX);
+        assert_eq!(mem.load_double(25), i64::MIN);
+        assert_eq!(mem.load_word(25), 0);
+        assert_eq!(mem.load_word(29), i32::MIN);
+    }
+
     #[test]
     fn store_and_load_various_sizes() {
         let mut mem = Memory::default();
-        mem.store_word(0, -1);
+        mem.store_word(0, -1).unwrap();
         assert_eq!(mem.load_half(0), -1);
         assert_eq!(mem.load_half(2), -1);
         assert_eq!(mem.load_byte(0), -1);
@@ -133,4 +826,259 @@ mod test {
         assert_eq!(mem.load_byte(2), -1);
         assert_eq!(mem.load_byte(3), -1);
     }
+
+    #[test]
+    fn load_unsigned() {
+        let mut mem = Memory::default();
+        mem.store_byte(0, -1).unwrap();
+        mem.store_half(2, -1).unwrap();
+        mem.store_word(4, -1).unwrap();
+
+        assert_eq!(mem.load_byte_unsigned(0), 0xFF);
+        assert_eq!(mem.load_byte_unsigned(1), 0);
+        assert_eq!(mem.load_half_unsigned(2), 0xFFFF);
+        assert_eq!(mem.load_word_unsigned(4), 0xFFFF_FFFF);
+        assert_eq!(
+            mem.load_byte_unsigned(0) as i32,
+            mem.load_byte(0) as u8 as i32
+        );
+        assert_eq!(
+            mem.load_half_unsigned(2) as i32,
+            mem.load_half(2) as u16 as i32
+        );
+    }
+
+    #[test]
+    fn store_into_protected_region() {
+        let mut mem = Memory::default();
+        mem.protect(8..16, Protection::ReadOnly);
+        assert_eq!(mem.store_word(8, 1), Err(Exception::StoreAccessFault));
+        assert_eq!(mem.store_half(14, 1), Err(Exception::StoreAccessFault));
+        assert_eq!(mem.store_byte(15, 1), Err(Exception::StoreAccessFault));
+        // A store which partially overlaps the protected region also faults.
+        assert_eq!(mem.store_word(6, 1), Err(Exception::StoreAccessFault));
+        assert_eq!(mem.store_double(4, -1), Err(Exception::StoreAccessFault));
+        assert_eq!(mem.load_word(8), 0);
+
+        assert_eq!(mem.store_word(4, -1), Ok(()));
+        assert_eq!(mem.store_byte(16, 1), Ok(()));
+        assert_eq!(mem.load_word(4), -1);
+        assert_eq!(mem.load_byte(16), 1);
+    }
+
+    #[test]
+    fn protection_most_recent_region_takes_precedence() {
+        let mut mem = Memory::default();
+        mem.protect(0..64, Protection::ReadOnly);
+        mem.protect(32..36, Protection::ReadWrite);
+        assert_eq!(mem.protection(31), Protection::ReadOnly);
+        assert_eq!(mem.protection(32), Protection::ReadWrite);
+        assert_eq!(mem.protection(64), Protection::ReadWriteExecute);
+        assert_eq!(mem.store_word(32, 42), Ok(()));
+        assert_eq!(mem.store_word(36, 42), Err(Exception::StoreAccessFault));
+    }
+
+    #[test]
+    fn no_access_region() {
+        let mut mem = Memory::default();
+        mem.protect(0..4096, Protection::NoAccess);
+        assert_eq!(mem.store_word(0, 1), Err(Exception::StoreAccessFault));
+        assert_eq!(mem.store_byte(4095, 1), Err(Exception::StoreAccessFault));
+        assert_eq!(
+            mem.fetch_instruction(0),
+            Err(Exception::InstructionAccessFault)
+        );
+        assert_eq!(
+            mem.check_readable(4094, 4),
+            Err(Exception::MemoryAccessFault)
+        );
+        assert_eq!(mem.check_readable(4096, 4), Ok(()));
+        assert_eq!(mem.store_word(4096, 1), Ok(()));
+    }
+
+    #[test]
+    fn store_near_max_address() {
+        let mut mem = Memory::default();
+        assert_eq!(
+            mem.store_word(usize::MAX - 1, 1),
+            Err(Exception::StoreAccessFault)
+        );
+        assert_eq!(
+            mem.store_half(usize::MAX, 1),
+            Err(Exception::StoreAccessFault)
+        );
+        assert_eq!(
+            mem.store_double(usize::MAX - 6, 1),
+            Err(Exception::StoreAccessFault)
+        );
+        assert_eq!(
+            mem.store_byte(isize::MAX as usize, 1),
+            Err(Exception::StoreAccessFault)
+        );
+        assert_eq!(
+            mem.check_readable(usize::MAX - 2, 4),
+            Err(Exception::MemoryAccessFault)
+        );
+        assert_eq!(
+            mem.fetch_instruction(usize::MAX),
+            Err(Exception::InstructionAccessFault)
+        );
+        assert_eq!(mem, Memory::default());
+    }
+
+    #[test]
+    fn access_at_largest_word_address() {
+        let mut mem = Memory::with_fill(0xcd);
+        let location = usize::MAX - 3;
+
+        assert_eq!(mem.load_word_unsigned(location), 0xcdcd_cdcd);
+        assert_eq!(mem.load_double(location), -0x3232_3232_3232_3233);
+        assert_eq!(
+            mem.store_word(location, 1),
+            Err(Exception::StoreAccessFault)
+        );
+        mem.write(location, &[1; 8]);
+        assert_eq!(mem, Memory::with_fill(0xcd));
+    }
+
+    #[test]
+    fn map_summary() {
+        let mut mem = Memory::default();
+        assert_eq!(mem.map_summary(), "");
+        mem.store_word(0x10, -1).unwrap();
+        mem.store_half(0x14, 0x0101).unwrap();
+        mem.store_byte(0x40, 1).unwrap();
+        mem.store_word(0x100, 0x7f).unwrap();
+        assert_eq!(
+            mem.map_summary(),
+            "[0x10..0x16] (6 bytes)\n[0x40..0x41] (1 bytes)\n[0x100..0x101] (1 bytes)"
+        );
+    }
+
+    #[test]
+    fn peek_word() {
+        let mut mem = Memory::with_fill(0xcd);
+        mem.store_half(0, 0x1234).unwrap();
+        let before = mem.clone();
+        assert_eq!(mem.peek_word(0), 0xcdcd_1234);
+        assert_eq!(mem.peek_word(usize::MAX), 0xcdcd_cdcd);
+        assert_eq!(mem, before);
+    }
+
+    #[test]
+    fn words_and_bytes() {
+        let mut mem = Memory::with_fill(0xcd);
+        mem.store_word(0, 54).unwrap();
+        mem.store_word(4, -1).unwrap();
+        mem.store_half(10, 0x1234).unwrap();
+        let before = mem.clone();
+
+        let words: Vec<i32> = mem.words(0, 4).collect();
+        assert_eq!(mem, before);
+        assert_eq!(
+            words,
+            (0..4)
+                .map(|index| mem.load_word(index * 4))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(words, vec![54, -1, 0x1234_cdcd, 0xcdcd_cdcd_u32 as i32]);
+        assert_eq!(
+            mem.bytes(9, 4).collect::<Vec<_>>(),
+            vec![0xcd, 0x34, 0x12, 0xcd]
+        );
+        assert_eq!(mem.bytes(usize::MAX, 2).collect::<Vec<_>>(), vec![0xcd; 2]);
+    }
+
+    #[test]
+    fn clone_is_copy_on_write() {
+        let mut mem = Memory::default();
+        mem.store_word(0, 54).unwrap();
+        let mut clone = mem.clone();
+        assert!(Arc::ptr_eq(&mem.pages[0], &clone.pages[0]));
+
+        clone.store_word(0, 42).unwrap();
+        assert!(!Arc::ptr_eq(&mem.pages[0], &clone.pages[0]));
+        assert_eq!(mem.load_word(0), 54);
+        assert_eq!(clone.load_word(0), 42);
+    }
+
+    #[test]
+    fn store_copies_only_the_page_written() {
+        let mut mem = Memory::default();
+        mem.store_word(0, 1).unwrap();
+        mem.store_word(PAGE_SIZE, 2).unwrap();
+        mem.store_word(2 * PAGE_SIZE, 3).unwrap();
+        let mut clone = mem.clone();
+
+        clone.store_word(PAGE_SIZE + 4, 42).unwrap();
+
+        assert!(Arc::ptr_eq(&mem.pages[0], &clone.pages[0]));
+        assert!(!Arc::ptr_eq(&mem.pages[1], &clone.pages[1]));
+        assert!(Arc::ptr_eq(&mem.pages[2], &clone.pages[2]));
+        assert_eq!(mem.load_word(PAGE_SIZE + 4), 0);
+        assert_eq!(clone.load_word(PAGE_SIZE + 4), 42);
+    }
+
+    #[test]
+    fn access_across_a_page_boundary() {
+        let mut mem = Memory::with_fill(0xcd);
+        mem.store_word(PAGE_SIZE - 2, 0x1234_5678).unwrap();
+
+        assert_eq!(mem.len(), PAGE_SIZE + 2);
+        assert_eq!(mem.load_word(PAGE_SIZE - 2), 0x1234_5678);
+        assert_eq!(mem.load_half_unsigned(PAGE_SIZE), 0x1234);
+        assert_eq!(mem.peek_word(PAGE_SIZE), 0xcdcd_1234);
+    }
+
+    #[test]
+    fn fill_byte() {
+        let mut mem = Memory::with_fill(0xcd);
+        mem.store_byte(2, 1).unwrap();
+        assert_eq!(mem.load_byte_unsigned(0), 0xcd);
+        assert_eq!(mem.load_byte_unsigned(2), 1);
+        assert_eq!(mem.load_word_unsigned(8), 0xcdcd_cdcd);
+        assert_eq!(Memory::default().load_word(8), 0);
+    }
+
+    #[test]
+    fn fetch_from_protected_region() {
+        let mut mem = Memory::default();
+        mem.store_word(0, 0x13).unwrap();
+        mem.store_word(8, 0x13).unwrap();
+        mem.protect(0..4, Protection::ReadExecute);
+        mem.protect(4..8, Protection::ReadWrite);
+        mem.protect(8..12, Protection::ReadOnly);
+        assert_eq!(mem.fetch_instruction(0), Ok(0x13));
+        assert_eq!(
+            mem.fetch_instruction(4),
+            Err(Exception::InstructionAccessFault)
+        );
+        assert_eq!(
+            mem.fetch_instruction(8),
+            Err(Exception::InstructionAccessFault)
+        );
+        // An instruction straddling a no-execute region also faults.
+        assert_eq!(
+            mem.fetch_instruction(2),
+            Err(Exception::InstructionAccessFault)
+        );
+        assert_eq!(mem.fetch_instruction(12), Ok(0));
+    }
+
+    #[test]
+    fn sparse_memory_at_high_address() {
+        let mut mem = SparseMemory::default();
+        mem.store_word(0x8000_0000, 54).unwrap();
+        // A word straddling the end of a page allocates the next page.
+        mem.store_word(0x8000_0ffe, -1).unwrap();
+        mem.store_byte(usize::MAX, 7).unwrap();
+
+        assert_eq!(mem.load_word(0x8000_0000), 54);
+        assert_eq!(mem.load_word(0x8000_0ffe), -1);
+        assert_eq!(mem.load_half_unsigned(0x8000_1000), 0xffff);
+        assert_eq!(mem.load_byte(usize::MAX), 7);
+        assert_eq!(mem.load_word(0x4000), 0);
+        assert_eq!(mem.peek_word(0x8000_0ffc), 0xffff_0000);
+        assert_eq!(mem.page_count(), 3);
+    }
 }
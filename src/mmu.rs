@@ -0,0 +1,213 @@
+//! Sv32 virtual memory.
+//!
+//! When the `satp` CSR selects the Sv32 mode, virtual addresses are translated
+//! into physical addresses by walking a two-level page table held in memory.
+//! In bare mode addresses are used unchanged.
+use crate::{instruction_set::Exception, memory::MemoryBus};
+
+/// The `satp` mode bit selecting Sv32 translation.
+const SATP_MODE_SV32: u32 = 1 << 31;
+/// The bit mask for the physical page number of the root page table in `satp`.
+const SATP_PPN_MASK: u32 = (1 << 22) - 1;
+/// The number of bits in the page offset.
+const PAGE_SHIFT: usize = 12;
+/// The size in bytes of a page.
+pub(crate) const PAGE_SIZE: usize = 1 << PAGE_SHIFT;
+/// The number of bits in each virtual page number.
+const VPN_BITS: usize = 10;
+/// The size in bytes of a page table entry.
+const PTE_SIZE: usize = 4;
+/// The number of levels in the page table.
+const LEVELS: usize = 2;
+/// The right shift to apply to a page table entry to extract the physical page number.
+const PTE_PPN_SHIFT: usize = 10;
+
+/// Page table entry valid bit.
+const PTE_V: u32 = 1 << 0;
+/// Page table entry readable bit.
+const PTE_R: u32 = 1 << 1;
+/// Page table entry writable bit.
+const PTE_W: u32 = 1 << 2;
+/// Page table entry executable bit.
+const PTE_X: u32 = 1 << 3;
+
+/// The kind of memory access being translated.
+///
+/// This determines the permission required of the page and the page fault
+/// raised if translation fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// A load from memory.
+    Load,
+    /// A store to memory.
+    Store,
+    /// An instruction fetch.
+    Fetch,
+}
+
+impl Access {
+    /// The page table entry permission bit required for this access.
+    const fn permission(self) -> u32 {
+        match self {
+            Access::Load => PTE_R,
+            Access::Store => PTE_W,
+            Access::Fetch => PTE_X,
+        }
+    }
+
+    /// The page fault raised when this access cannot be translated.
+    const fn page_fault(self) -> Exception {
+        match self {
+            Access::Load => Exception::LoadPageFault,
+            Access::Store => Exception::StorePageFault,
+            Access::Fetch => Exception::InstructionPageFault,
+        }
+    }
+}
+
+/// Translate the virtual `address` into a physical address using the
+/// translation mode and root page table held in `satp`.
+///
+/// Raises the page fault corresponding to `access` if a page table entry is
+/// invalid or does not permit the access.
+///
+/// _Note_: the user and accessed/dirty bits of the page table entries are not
+/// yet checked.
+pub fn translate(
+    memory: &mut impl MemoryBus,
+    satp: u32,
+    address: usize,
+    access: Access,
+) -> Result<usize, Exception> {
+    if satp & SATP_MODE_SV32 == 0 {
+        return Ok(address);
+    }
+
+    let mut table = ((satp & SATP_PPN_MASK) as usize) << PAGE_SHIFT;
+    for level in (0..LEVELS).rev() {
+        let offset_bits = PAGE_SHIFT + VPN_BITS * level;
+        let vpn = (address >> offset_bits) & ((1 << VPN_BITS) - 1);
+        let pte = memory.load_word_unsigned(table + vpn * PTE_SIZE);
+        if pte & PTE_V == 0 || (pte & PTE_R == 0 && pte & PTE_W != 0) {
+            return Err(access.page_fault());
+        }
+
+        let ppn = (pte >> PTE_PPN_SHIFT) as usize;
+        if pte & (PTE_R | PTE_X) == 0 {
+            // A pointer to the next level of the page table.
+            table = ppn << PAGE_SHIFT;
+            continue;
+        }
+
+        let offset_mask = (1 << offset_bits) - 1;
+        if pte & access.permission() == 0 || (ppn << PAGE_SHIFT) & offset_mask != 0 {
+            // Either the page does not permit the access or it is a misaligned superpage.
+            return Err(access.page_fault());
+        }
+        return Ok((ppn << PAGE_SHIFT) | (address & offset_mask));
+    }
+    Err(access.page_fault())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::memory::Memory;
+    use pretty_assertions::assert_eq;
+
+    /// The root page table is placed at physical page 1.
+    const SATP: u32 = SATP_MODE_SV32 | 1;
+
+    /// Build a page table entry for the physical page number `ppn`.
+    fn pte(ppn: u32, flags: u32) -> i32 {
+        ((ppn << PTE_PPN_SHIFT) | flags | PTE_V) as i32
+    }
+
+    /// A page table mapping:
+    /// - the virtual page `0x0040_0000` to physical page `3` (read/write);
+    /// - the virtual page `0x0040_1000` to physical page `4` (execute only);
+    /// - the virtual superpage `0x8000_0000` to physical address `0`.
+    fn memory() -> Memory {
+        let mut memory = Memory::default();
+        // The root table entry for VPN[1] = 1 points to the table at page 2.
+        memory.store_word(0x1000 + 4, pte(2, 0)).unwrap();
+        // The root table entry for VPN[1] = 0x200 is a superpage.
+        memory
+            .store_word(0x1000 + 0x200 * 4, pte(0, PTE_R))
+            .unwrap();
+        memory.store_word(0x2000, pte(3, PTE_R | PTE_W)).unwrap();
+        memory.store_word(0x2000 + 4, pte(4, PTE_X)).unwrap();
+        memory
+    }
+
+    #[test]
+    fn bare_mode() {
+        assert_eq!(
+            translate(&mut Memory::default(), 0, 0x1234, Access::Load),
+            Ok(0x1234)
+        );
+        assert_eq!(
+            translate(&mut Memory::default(), 0, 0x1_0000_1234, Access::Load),
+            Ok(0x1_0000_1234)
+        );
+    }
+
+    #[test]
+    fn translate_mapped_page() {
+        let mut memory = memory();
+        assert_eq!(
+            translate(&mut memory, SATP, 0x0040_0123, Access::Load),
+            Ok(0x3123)
+        );
+        assert_eq!(
+            translate(&mut memory, SATP, 0x0040_0FFC, Access::Store),
+            Ok(0x3FFC)
+        );
+        assert_eq!(
+            translate(&mut memory, SATP, 0x0040_1010, Access::Fetch),
+            Ok(0x4010)
+        );
+    }
+
+    #[test]
+    fn translate_superpage() {
+        assert_eq!(
+            translate(&mut memory(), SATP, 0x8012_3456, Access::Load),
+            Ok(0x12_3456)
+        );
+    }
+
+    #[test]
+    fn unmapped_page_faults() {
+        let mut memory = memory();
+        assert_eq!(
+            translate(&mut memory, SATP, 0x0040_2000, Access::Load),
+            Err(Exception::LoadPageFault)
+        );
+        assert_eq!(
+            translate(&mut memory, SATP, 0x0000_0000, Access::Store),
+            Err(Exception::StorePageFault)
+        );
+        assert_eq!(
+            translate(&mut memory, SATP, 0x0000_0000, Access::Fetch),
+            Err(Exception::InstructionPageFault)
+        );
+    }
+
+    #[test]
+    fn permission_faults() {
+        let mut memory = memory();
+        assert_eq!(
+            translate(&mut memory, SATP, 0x0040_1000, Access::Load),
+            Err(Exception::LoadPageFault)
+        );
+        assert_eq!(
+            translate(&mut memory, SATP, 0x0040_0000, Access::Fetch),
+            Err(Exception::InstructionPageFault)
+        );
+        assert_eq!(
+            translate(&mut memory, SATP, 0x8000_0000, Access::Store),
+            Err(Exception::StorePageFault)
+        );
+    }
+}
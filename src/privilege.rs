@@ -0,0 +1,45 @@
+//! RISC-V privilege levels.
+
+/// The privilege level a hart is executing at.
+///
+/// Privilege levels are used to provide protection between different
+/// components of the software stack. The hart starts in machine mode, the
+/// most privileged level.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Privilege {
+    /// User mode, for application code.
+    User = 0b00,
+    /// Supervisor mode, for operating systems.
+    Supervisor = 0b01,
+    /// Machine mode, for firmware with full access to the machine.
+    #[default]
+    Machine = 0b11,
+}
+
+impl Privilege {
+    /// Decode a privilege level from its two bit encoding, as found in the
+    /// `MPP` field of `mstatus`.
+    ///
+    /// The reserved encoding `0b10` is treated as user mode.
+    pub const fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0b11 => Privilege::Machine,
+            0b01 => Privilege::Supervisor,
+            _ => Privilege::User,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn from_bits() {
+        for privilege in [Privilege::User, Privilege::Supervisor, Privilege::Machine] {
+            assert_eq!(Privilege::from_bits(privilege as u8), privilege);
+        }
+        assert_eq!(Privilege::from_bits(0b10), Privilege::User);
+    }
+}
@@ -1,18 +1,66 @@
 //! Core model of the central processing unit.
 //!
 //! The processor implements execution pipeline.
-use crate::csr::ControlStatusRegisters;
+use std::collections::BTreeSet;
+use std::fmt::{Display, LowerHex, Write};
+use std::io::{self, Read};
+use std::ops::BitAnd;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::asm::{self, Program};
+use crate::csr::{
+    ControlStatusRegisters, MStatus, CSR32, CSR64, CYCLE, INSTRET, MCAUSE, MEDELEG, MEPC, MTVAL,
+    MTVEC, SATP, SCAUSE, SEPC, STVAL, STVEC, TDATA1, TDATA2, TIME,
+};
 use crate::instruction_set::{Exception, InstructionSet};
+use crate::instructions::Instruction;
 use crate::integer::AsUsize;
-use crate::memory::Memory;
-use crate::registers::Registers;
+use crate::isa::{Extension, Isa, IsaError};
+use crate::memory::{MemAccess, Memory, MemoryBus};
+use crate::mmu::{self, Access};
+use crate::privilege::Privilege;
+use crate::profile::InstructionProfile;
+use crate::registers::{Register, Registers};
+
+/// The number of instructions executed between checks of the clock by
+/// [Processor::run_with_timeout].
+const TIMEOUT_CHECK_INTERVAL: u32 = 1024;
+
+/// The number of bytes read at a time by [Processor::load_from_reader].
+const LOAD_CHUNK_SIZE: usize = 4096;
+
+/// The `a7` value of an `ECALL` requesting the programme exit, following the
+/// Linux system call numbering.
+const EXIT_SYSCALL: i32 = 93;
+
+/// The registers shown by the one-line summary of a processor, its
+/// [Display], the return value registers and the stack pointer.
+const SUMMARY_REGISTERS: [Register; 3] = [Register::A0, Register::A1, Register::SP];
+
+/// The `type` field of [TDATA1] for an address match (`mcontrol`) trigger.
+const MCONTROL: usize = 2;
+
+/// The `execute` bit of an `mcontrol` trigger, matching instruction fetches.
+const MCONTROL_EXECUTE: usize = 1 << 2;
+
+/// A callback invoked with each memory access, see
+/// [Processor::set_memory_tracer].
+pub(crate) struct MemoryTracer(Box<dyn FnMut(MemAccess) + Send>);
+
+impl std::fmt::Debug for MemoryTracer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("MemoryTracer")
+    }
+}
 
 /// The RISC-V machines central processing unit.
 ///
 /// To support different architectures the processor is generic over the
-/// register type and CSR type.
-#[derive(Debug, Default, PartialEq, Eq)]
-pub struct Processor<R, CSRs: ControlStatusRegisters<Register = R>> {
+/// register type and CSR type. It is also generic over its memory, which
+/// defaults to [Memory], see [MemoryBus].
+#[derive(Debug)]
+pub struct Processor<R, CSRs: ControlStatusRegisters<Register = R>, M = Memory> {
     /// The processors registers.
     pub(crate) registers: Registers<R>,
     /// Programme Counter
@@ -20,87 +68,1472 @@ pub struct Processor<R, CSRs: ControlStatusRegisters<Register = R>> {
     /// The control status registers.
     pub(crate) csrs: CSRs,
     /// The computer memory.
-    pub(crate) memory: Memory,
-    // TODO add privilege modes
+    pub(crate) memory: M,
+    /// The current privilege level.
+    pub(crate) privilege: Privilege,
+    /// The raw encoding of the most recently executed instruction.
+    pub(crate) last_instruction: Option<u32>,
+    /// The amount the `time` CSR advances with each step.
+    pub(crate) time_increment: u64,
+    /// Whether the `cycle` and `instret` CSRs count steps and retired
+    /// instructions.
+    pub(crate) count_instructions: bool,
+    /// The callback invoked with each load and store.
+    pub(crate) memory_tracer: Option<MemoryTracer>,
+    /// The most recent store performed by an instruction.
+    pub(crate) last_store: Option<MemAccess>,
+    /// The return addresses of the active calls, if call stack tracking is
+    /// enabled.
+    pub(crate) call_stack: Option<Vec<R>>,
+    /// What happens when an `EBREAK` is executed.
+    pub(crate) ebreak_policy: EbreakPolicy,
+    /// The register width and enabled extensions.
+    pub(crate) isa: Isa,
+    /// What happens when a load or store is not naturally aligned.
+    pub(crate) unaligned_policy: UnalignedPolicy,
+    /// The lowest address the stack pointer may reach, if the stack guard is
+    /// enabled, see [Processor::set_stack_guard].
+    pub(crate) stack_limit: Option<R>,
 }
 
-impl<R, CSRs: ControlStatusRegisters<Register = R>> Processor<R, CSRs>
+/// A one-line summary of the processor, such as
+/// `pc=0x1004 a0=42 a1=21 sp=0x7ff0`, see [Processor::summary].
+impl<R, CSRs, M> Display for Processor<R, CSRs, M>
+where
+    R: AsUsize
+        + From<i32>
+        + Into<i64>
+        + TryFrom<i64>
+        + BitAnd<Output = R>
+        + PartialEq
+        + Copy
+        + Display
+        + LowerHex,
+    CSRs: ControlStatusRegisters<Register = R>,
+    M: MemoryBus,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.summary(&SUMMARY_REGISTERS))
+    }
+}
+
+/// Processors are equal when their architectural state, that is their
+/// registers, programme counter, CSRs, memory, and privilege level, are equal.
+impl<R, CSRs, M> PartialEq for Processor<R, CSRs, M>
+where
+    R: PartialEq,
+    CSRs: ControlStatusRegisters<Register = R> + PartialEq,
+    M: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.registers == other.registers
+            && self.pc == other.pc
+            && self.csrs == other.csrs
+            && self.memory == other.memory
+            && self.privilege == other.privilege
+    }
+}
+
+impl<R, CSRs, M> Eq for Processor<R, CSRs, M>
+where
+    R: Eq,
+    CSRs: ControlStatusRegisters<Register = R> + Eq,
+    M: Eq,
+{
+}
+
+/// An empty processor in machine mode, whose default extensions are at the
+/// width of the register type, for example `RV64IM` for [Rv64Processor].
+impl<R, CSRs, M> Default for Processor<R, CSRs, M>
+where
+    R: Default,
+    CSRs: ControlStatusRegisters<Register = R> + Default,
+    M: Default,
+{
+    fn default() -> Self {
+        Self {
+            registers: Registers::default(),
+            pc: R::default(),
+            csrs: CSRs::default(),
+            memory: M::default(),
+            privilege: Privilege::default(),
+            last_instruction: None,
+            time_increment: 0,
+            count_instructions: false,
+            memory_tracer: None,
+            last_store: None,
+            call_stack: None,
+            ebreak_policy: EbreakPolicy::default(),
+            isa: Isa::default().with_xlen(8 * std::mem::size_of::<R>() as u32),
+            unaligned_policy: UnalignedPolicy::default(),
+            stack_limit: None,
+        }
+    }
+}
+
+impl<R, CSRs: ControlStatusRegisters<Register = R>, M: MemoryBus> Processor<R, CSRs, M>
 where
-    R: AsUsize,
+    R: AsUsize + From<i32> + Into<i64> + TryFrom<i64> + BitAnd<Output = R> + PartialEq + Copy,
 {
+    /// Fetch and decode the instruction at the programme counter.
+    ///
+    /// Raises [Exception::MisalignedInstructionFetch] if the programme counter
+    /// is not aligned to the instruction granularity, and
+    /// [Exception::UnimplementedInstruction] if the instruction belongs to an
+    /// extension which is not enabled, see [Processor::with_isa].
+    #[inline]
+    fn fetch<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(
+        &mut self,
+    ) -> Result<I, Exception> {
+        let address = Self::unsigned_address(self.pc.as_usize());
+        if !address.is_multiple_of(self.isa.instruction_alignment()) {
+            return Err(Exception::MisalignedInstructionFetch);
+        }
+        let location = self.translate(address, Access::Fetch)?;
+        let raw_instruction = if self.isa.supports(Extension::C) {
+            // Only the first parcel is fetched for compressed instructions so
+            // that one at the end of executable memory can be fetched.
+            let parcel = self.memory.fetch_parcel(location)?;
+            if Instruction::length_of(parcel) == 2 {
+                parcel.into()
+            } else {
+                // A 32-bit instruction need only be 2-byte aligned, so its
+                // second parcel may be on the next page and is translated
+                // separately.
+                let location = self.translate(
+                    Self::unsigned_address(address.wrapping_add(2)),
+                    Access::Fetch,
+                )?;
+                u32::from(parcel) | u32::from(self.memory.fetch_parcel(location)?) << 16
+            }
+        } else {
+            self.memory.fetch_instruction(location)?
+        };
+        let instruction = I::decode_for(raw_instruction, &self.isa)?;
+        if !self.isa.supports(instruction.extension()) {
+            return Err(Exception::UnimplementedInstruction(raw_instruction));
+        }
+        self.last_instruction = Some(raw_instruction);
+        Ok(instruction)
+    }
+
+    /// The `address` truncated to the width of the registers, so that an
+    /// address in the upper half of the RV32 address space, which is
+    /// negative as an [i32], is not sign-extended.
+    #[inline]
+    fn unsigned_address(address: usize) -> usize {
+        let bits = 8 * std::mem::size_of::<R>() as u32;
+        address & (usize::MAX >> usize::BITS.saturating_sub(bits))
+    }
+
+    /// Translate the virtual `address` into a physical address according to
+    /// the `satp` CSR, see [crate::mmu].
+    ///
+    /// Addresses are not translated in machine mode. Only Sv32 is
+    /// implemented, so addresses are not translated on wider processors
+    /// either.
+    #[inline]
+    pub(crate) fn translate(&mut self, address: usize, access: Access) -> Result<usize, Exception> {
+        if self.privilege == Privilege::Machine || self.isa.xlen() != 32 {
+            return Ok(address);
+        }
+        let satp = self.csrs.read(SATP).as_usize() as u32;
+        mmu::translate(&mut self.memory, satp, address, access)
+    }
+
     /// Execute a single step of the processor pipeline:
     /// `load instruction -> decode instruction -> execute instruction`
     /// returning nothing or an exception if raised.
+    ///
+    /// Before the instruction is executed `trace` is called with the
+    /// programme counter and the decoded instruction.
     #[inline]
     fn inner_step<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(
         &mut self,
+        trace: impl FnOnce(&R, &I),
     ) -> Result<(), Exception> {
-        I::decode(self.memory.load_word(self.pc.as_usize()) as u32)?.execute(self)
+        self.check_trigger()?;
+        let instruction = self.fetch::<I>()?;
+        trace(&self.pc, &instruction);
+        instruction.execute(self)
+    }
+
+    /// Raise [Exception::Breakpoint] if the debug trigger matches the
+    /// instruction about to be executed.
+    ///
+    /// A single trigger is implemented, so [crate::csr::TSELECT] always reads
+    /// as trigger 0. It fires when [TDATA1] configures an `mcontrol` trigger
+    /// with the `execute` bit and the bit for the current privilege mode set,
+    /// and the pc equals [TDATA2].
+    ///
+    /// In machine mode the trigger only fires while `mstatus.MIE` is set, so
+    /// that it does not fire again in the trap handler, which may return with
+    /// `MPIE` cleared to continue past the trigger.
+    #[inline]
+    fn check_trigger(&self) -> Result<(), Exception> {
+        let tdata1 = self.csrs.read(TDATA1).as_usize();
+        if tdata1 & MCONTROL_EXECUTE == 0 {
+            return Ok(());
+        }
+        let xlen = std::mem::size_of::<R>() * 8;
+        let mode = match self.privilege {
+            Privilege::Machine if !MStatus::new(&self.csrs).mie() => return Ok(()),
+            Privilege::Machine => 1 << 6,
+            Privilege::Supervisor => 1 << 4,
+            Privilege::User => 1 << 3,
+        };
+        if (tdata1 >> (xlen - 4)) & 0xF == MCONTROL
+            && tdata1 & mode != 0
+            && self.csrs.read(TDATA2) == self.pc
+        {
+            return Err(Exception::Breakpoint);
+        }
+        Ok(())
     }
 
     /// Step the process one instruction forward handling any exception which might be raised.
     #[inline]
     pub fn step<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(&mut self) -> ExecutionResult {
-        match self.inner_step::<I>() {
-            Err(exception) => self.handle_exception(exception),
-            Ok(()) => ExecutionResult::Continue,
+        self.step_with_trace::<I>(|_, _| {})
+    }
+
+    /// Step the process one instruction forward handling any exception which
+    /// might be raised.
+    ///
+    /// Before the instruction is executed `trace` is called with the
+    /// programme counter and the decoded instruction.
+    #[inline]
+    pub fn step_with_trace<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(
+        &mut self,
+        trace: impl FnOnce(&R, &I),
+    ) -> ExecutionResult {
+        let (result, retired) = match self.inner_step::<I>(trace) {
+            Err(exception) => (self.handle_exception(exception), false),
+            Ok(()) => match self.memory.take_exit_request() {
+                Some(code) => (ExecutionResult::Exited(code), true),
+                None if self.stack_overflowed() => (ExecutionResult::StackOverflow, true),
+                None => (ExecutionResult::Continue, true),
+            },
+        };
+        self.advance_time();
+        // The processor stops without completing a halting step.
+        if result != ExecutionResult::Halt {
+            self.advance_counters(retired);
+        }
+        result
+    }
+
+    /// Step the processor one instruction forward, reporting the instruction
+    /// executed and the changes it made to the registers and memory.
+    ///
+    /// This is everything an interactive monitor needs to show what a step
+    /// did. Like [Processor::step] any exception is handled, in which case
+    /// the report includes the changes made by entering the trap handler.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use riskv::{csr::CSR32, instructions::Instruction, processor::Processor, registers::Register};
+    ///
+    /// let mut processor = Processor::<i32, CSR32>::default();
+    /// processor.store_instructions(
+    ///     0,
+    ///     [Instruction::ADDI { rd: Register::A0, rs1: Register::ZERO, imm: 42 }],
+    /// );
+    ///
+    /// let report = processor.step_and_report::<Instruction>();
+    /// assert_eq!(report.instruction.as_deref(), Some("addi a0,zero,42"));
+    /// assert_eq!(report.registers, vec![(Register::A0, 0, 42)]);
+    /// ```
+    pub fn step_and_report<I>(&mut self) -> StepReport<R>
+    where
+        I: InstructionSet<RegisterType = R, CSRType = CSRs> + Display,
+    {
+        let address = self.pc;
+        let registers = self.registers.clone();
+        self.last_store = None;
+        let mut instruction = None;
+        let result =
+            self.step_with_trace::<I>(|_, decoded| instruction = Some(decoded.to_string()));
+        StepReport {
+            address,
+            instruction,
+            registers: registers.diff(&self.registers),
+            store: self.last_store.take(),
+            result,
+        }
+    }
+
+    /// Step the processor up to `n` instructions forward, returning the
+    /// report of each step, see [Processor::step_and_report].
+    ///
+    /// Stepping stops early after a step which halts or exits, so the final
+    /// report's result says why fewer than `n` reports were returned.
+    pub fn step_n<I>(&mut self, n: usize) -> Vec<StepReport<R>>
+    where
+        I: InstructionSet<RegisterType = R, CSRType = CSRs> + Display,
+    {
+        let mut reports = Vec::with_capacity(n);
+        while reports.len() < n {
+            let report = self.step_and_report::<I>();
+            let result = report.result;
+            reports.push(report);
+            if result != ExecutionResult::Continue {
+                break;
+            }
+        }
+        reports
+    }
+
+    /// Execute the decoded `instruction` against the current state, without
+    /// fetching it from memory.
+    ///
+    /// Unlike [Processor::step], an exception raised by the instruction is
+    /// returned rather than trapped, leaving the exception handling to the
+    /// caller. This is useful for testing the semantics of an instruction
+    /// against a preset state, see [Processor::set_register].
+    pub fn execute_one<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(
+        &mut self,
+        instruction: I,
+    ) -> Result<(), Exception> {
+        instruction.execute(self)
+    }
+
+    /// Decode and execute the arbitrary 32-bit `word` against the current
+    /// state, returning any exception raised rather than trapping it.
+    ///
+    /// This is intended as a fuzzing entry point: it never panics, whatever
+    /// the `word` or processor state, with arithmetic wrapping as defined by
+    /// the specification. Instructions of extensions which are not enabled
+    /// raise [Exception::UnimplementedInstruction], as they would on fetch.
+    ///
+    /// _Note_: memory grows to cover the highest address accessed, so a
+    /// fuzzer should bound the accessible memory, for example by protecting
+    /// the memory above a limit with [crate::memory::Protection::NoAccess].
+    pub fn fuzz_step<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(
+        &mut self,
+        word: u32,
+    ) -> Result<(), Exception> {
+        let instruction = I::decode_for(word, &self.isa)?;
+        if !self.isa.supports(instruction.extension()) {
+            return Err(Exception::UnimplementedInstruction(word));
+        }
+        instruction.execute(self)
+    }
+
+    /// The processor's registers, for example to report the changes made by
+    /// a step with [Registers::diff].
+    pub fn registers(&self) -> &Registers<R> {
+        &self.registers
+    }
+
+    /// The value of the `register`.
+    pub fn register(&self, register: Register) -> R {
+        self.registers[register]
+    }
+
+    /// The value returned by the programme, the value of `a0`.
+    ///
+    /// Under the standard calling convention a function returns its result
+    /// in `a0`, and `a1` for the upper half of a result twice the register
+    /// width.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use riskv::{csr::CSR32, instructions::Instruction, processor::Processor, registers::Register};
+    ///
+    /// let processor = Processor::<i32, CSR32>::run_program([
+    ///     Instruction::ADDI { rd: Register::A0, rs1: Register::ZERO, imm: 6 },
+    ///     Instruction::ADDI { rd: Register::A1, rs1: Register::ZERO, imm: 7 },
+    ///     Instruction::MUL { rd: Register::A0, rs1: Register::A0, rs2: Register::A1 },
+    /// ]);
+    /// assert_eq!(processor.return_value(), 42);
+    /// ```
+    pub fn return_value(&self) -> R {
+        self.registers[Register::A0]
+    }
+
+    /// A compact one-line summary of the programme counter and the
+    /// `registers`, such as `pc=0x1004 a0=42 sp=0x7ff0`, for logging.
+    ///
+    /// The programme counter and the pointer registers `ra`, `sp`, `gp`, and
+    /// `tp` are shown in hexadecimal, other registers in decimal. The
+    /// [Display] of a processor summarises `a0`, `a1`, and `sp`.
+    pub fn summary(&self, registers: &[Register]) -> String
+    where
+        R: Display + LowerHex,
+    {
+        let mut summary = format!("pc={:#x}", self.pc);
+        for register in registers {
+            let value = self.registers[*register];
+            let _ = match register {
+                Register::RA | Register::SP | Register::GP | Register::TP => {
+                    write!(summary, " {}={value:#x}", register.name())
+                }
+                _ => write!(summary, " {}={value}", register.name()),
+            };
+        }
+        summary
+    }
+
+    /// Set the value of the `register`.
+    ///
+    /// Writes to [Register::ZERO] are discarded.
+    pub fn set_register(&mut self, register: Register, value: R)
+    where
+        R: Default,
+    {
+        self.registers[register] = value;
+    }
+
+    /// The value of the CSR at `index`.
+    ///
+    /// Raises [Exception::UnimplementedInstruction], the illegal instruction
+    /// exception, if `index` is not a CSR address rather than panicking.
+    pub fn read_csr(&self, index: u16) -> Result<R, Exception> {
+        self.csrs.try_read(index)
+    }
+
+    /// Set the value of the CSR at `index`.
+    ///
+    /// Raises [Exception::UnimplementedInstruction], the illegal instruction
+    /// exception, if `index` is not a CSR address rather than panicking.
+    pub fn write_csr(&mut self, index: u16, value: R) -> Result<(), Exception> {
+        self.csrs.try_read_write(index, value).map(|_| ())
+    }
+
+    /// Set the stack pointer, [Register::SP], to the `top` of the stack, as
+    /// expected by compiled programmes at entry.
+    ///
+    /// The calling convention requires the stack pointer to be 16-byte
+    /// aligned, so `top` is rounded down to a multiple of 16.
+    pub fn set_stack_top(&mut self, top: R)
+    where
+        R: Default,
+    {
+        self.registers[Register::SP] = top & R::from(!0xf);
+    }
+
+    /// The processor's memory.
+    pub fn memory(&self) -> &M {
+        &self.memory
+    }
+
+    /// The processor's memory, for staging data before running a programme.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use riskv::{
+    ///     csr::CSR32, instructions::Instruction, memory::MemoryBus, processor::Processor,
+    ///     registers::Register,
+    /// };
+    ///
+    /// let mut processor = Processor::<i32, CSR32>::default();
+    /// processor.memory_mut().store_word(0x100, 42).unwrap();
+    /// processor.store_instructions(
+    ///     0,
+    ///     [Instruction::LW { rd: Register::A0, rs1: Register::ZERO, offset: 0x100 }],
+    /// );
+    ///
+    /// processor.run::<Instruction>();
+    /// assert_eq!(processor.register(Register::A0), 42);
+    /// ```
+    pub fn memory_mut(&mut self) -> &mut M {
+        &mut self.memory
+    }
+
+    /// Allocate the first `bytes` of memory up front, so that loading a large
+    /// programme does not grow the memory piecemeal.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use riskv::{csr::CSR32, processor::Processor};
+    ///
+    /// let mut processor = Processor::<i32, CSR32>::default();
+    /// processor.reserve_memory(0x1000);
+    /// assert_eq!(processor.memory().len(), 0x1000);
+    /// ```
+    pub fn reserve_memory(&mut self, bytes: usize) {
+        self.memory.reserve(bytes);
+    }
+
+    /// The programme counter.
+    pub fn pc(&self) -> R {
+        self.pc
+    }
+
+    /// Set the programme counter, the address of the next instruction to be
+    /// fetched.
+    pub fn set_pc(&mut self, pc: R) {
+        self.pc = pc;
+    }
+
+    /// Create a processor for the target described by the `isa` string, for
+    /// example `"rv32imac"`, see [crate::isa].
+    ///
+    /// Instructions of extensions which are not enabled raise
+    /// [Exception::UnimplementedInstruction], the illegal instruction
+    /// exception. Since the register width is determined by the register
+    /// type, an ISA string with a different width is rejected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use riskv::{csr::CSR32, isa::IsaError, processor::Processor};
+    ///
+    /// assert!(Processor::<i32, CSR32>::with_isa("rv32imac").is_ok());
+    /// assert_eq!(
+    ///     Processor::<i32, CSR32>::with_isa("rv64gc").unwrap_err(),
+    ///     IsaError::XlenMismatch { expected: 32, found: 64 },
+    /// );
+    /// ```
+    pub fn with_isa(isa: &str) -> Result<Self, IsaError>
+    where
+        R: Default,
+        CSRs: Default,
+        M: Default,
+    {
+        let isa: Isa = isa.parse()?;
+        let xlen = 8 * std::mem::size_of::<R>() as u32;
+        if isa.xlen() != xlen {
+            return Err(IsaError::XlenMismatch {
+                expected: xlen,
+                found: isa.xlen(),
+            });
+        }
+        Ok(Self {
+            isa,
+            ..Self::default()
+        })
+    }
+
+    /// The register width and enabled extensions of the processor.
+    pub fn isa(&self) -> Isa {
+        self.isa
+    }
+
+    /// Decompose the processor into its registers, programme counter, CSRs,
+    /// and memory, for example to share memory with another processor.
+    ///
+    /// The privilege level and any tracing configuration are discarded.
+    pub fn into_parts(self) -> (Registers<R>, R, CSRs, M) {
+        (self.registers, self.pc, self.csrs, self.memory)
+    }
+
+    /// Assemble a processor from the parts returned by
+    /// [Processor::into_parts].
+    ///
+    /// The processor starts in machine mode with tracing disabled, and with
+    /// the default extensions, see [Isa], at the width of the register type.
+    pub fn from_parts(registers: Registers<R>, pc: R, csrs: CSRs, memory: M) -> Self {
+        Self {
+            registers,
+            pc,
+            csrs,
+            memory,
+            privilege: Privilege::default(),
+            last_instruction: None,
+            time_increment: 0,
+            count_instructions: false,
+            memory_tracer: None,
+            last_store: None,
+            call_stack: None,
+            ebreak_policy: EbreakPolicy::default(),
+            isa: Isa::default().with_xlen(8 * std::mem::size_of::<R>() as u32),
+            unaligned_policy: UnalignedPolicy::default(),
+            stack_limit: None,
+        }
+    }
+
+    /// Capture the architectural state of the processor, that is its
+    /// registers, programme counter, CSRs, memory, and privilege level, to
+    /// roll back to with [Processor::restore].
+    ///
+    /// The memory is cloned, which for [Memory] is cheap since the checkpoint
+    /// shares its pages with the processor, and a write copies only the page
+    /// written.
+    pub fn checkpoint(&self) -> Checkpoint<R, CSRs, M>
+    where
+        CSRs: Clone,
+        M: Clone,
+    {
+        Checkpoint {
+            registers: self.registers.clone(),
+            pc: self.pc,
+            csrs: self.csrs.clone(),
+            memory: self.memory.clone(),
+            privilege: self.privilege,
+        }
+    }
+
+    /// Roll the processor back to the state captured by
+    /// [Processor::checkpoint].
+    ///
+    /// The configuration of the processor, such as its ISA and policies, is
+    /// not part of a checkpoint and is unchanged.
+    pub fn restore(&mut self, checkpoint: Checkpoint<R, CSRs, M>) {
+        self.registers = checkpoint.registers;
+        self.pc = checkpoint.pc;
+        self.csrs = checkpoint.csrs;
+        self.memory = checkpoint.memory;
+        self.privilege = checkpoint.privilege;
+    }
+
+    /// Set what happens when an `EBREAK` is executed, by default it raises
+    /// [Exception::Breakpoint].
+    pub fn set_ebreak_policy(&mut self, policy: EbreakPolicy) {
+        self.ebreak_policy = policy;
+    }
+
+    /// Set what happens when a load or store is not naturally aligned, by
+    /// default the access is emulated.
+    pub fn set_unaligned_policy(&mut self, policy: UnalignedPolicy) {
+        self.unaligned_policy = policy;
+    }
+
+    /// Set the lowest address the stack pointer, [Register::SP], may reach,
+    /// or disable the stack guard with `None`, the default.
+    ///
+    /// When an instruction moves the stack pointer below the `limit`, for
+    /// example a function prologue growing the stack too far, the step
+    /// returns [ExecutionResult::StackOverflow] after the instruction has
+    /// executed, stopping any run.
+    pub fn set_stack_guard(&mut self, limit: Option<R>) {
+        self.stack_limit = limit;
+    }
+
+    /// Whether the stack pointer is below the limit set by the stack guard.
+    #[inline]
+    fn stack_overflowed(&self) -> bool {
+        self.stack_limit
+            .is_some_and(|limit| self.registers[Register::SP].as_usize() < limit.as_usize())
+    }
+
+    /// Set the amount the `time` CSR advances with each step, by default the
+    /// `time` CSR does not advance.
+    pub fn set_time_increment(&mut self, increment: u64) {
+        self.time_increment = increment;
+    }
+
+    /// Set the `tracer` to be called with each load and store performed by
+    /// the executed instructions, for example to drive a cache model.
+    ///
+    /// Accesses are reported in programme order after they succeed, so a
+    /// faulting store is not reported. Instruction fetches are not reported.
+    ///
+    /// The `tracer` must be [Send] so the processor can still be moved to
+    /// another thread.
+    pub fn set_memory_tracer(&mut self, tracer: impl FnMut(MemAccess) + Send + 'static) {
+        self.memory_tracer = Some(MemoryTracer(Box::new(tracer)));
+    }
+
+    /// Report the memory `access` to the memory tracer, if any.
+    #[inline]
+    pub(crate) fn trace_memory(&mut self, access: MemAccess) {
+        if access.access == Access::Store {
+            self.last_store = Some(access);
+        }
+        if let Some(MemoryTracer(tracer)) = &mut self.memory_tracer {
+            tracer(access);
+        }
+    }
+
+    /// Enable or disable counting in the `cycle` and `instret` CSRs, read by
+    /// `RDCYCLE` and `RDINSTRET`.
+    ///
+    /// When enabled, each step advances `cycle` by one, and `instret` by one
+    /// if the instruction retired without raising an exception. A step which
+    /// halts the processor advances neither.
+    pub fn set_count_instructions(&mut self, enabled: bool) {
+        self.count_instructions = enabled;
+    }
+
+    /// Enable or disable tracking of the call stack, see [Processor::backtrace].
+    ///
+    /// Enabling tracking starts from an empty call stack.
+    pub fn track_call_stack(&mut self, enabled: bool) {
+        self.call_stack = enabled.then(Vec::new);
+    }
+
+    /// The return addresses of the active calls, innermost first.
+    ///
+    /// A call is a `JAL` or `JALR` linking to `ra`, and a return is
+    /// `JALR zero, ra, 0`. Calls made before call stack tracking was enabled,
+    /// see [Processor::track_call_stack], are not included, and the backtrace
+    /// is empty if tracking is disabled.
+    pub fn backtrace(&self) -> Vec<R> {
+        self.call_stack
+            .iter()
+            .flat_map(|call_stack| call_stack.iter().rev().copied())
+            .collect()
+    }
+
+    /// Record a call which will return to the `return_address`.
+    #[inline]
+    pub(crate) fn record_call(&mut self, return_address: R) {
+        if let Some(call_stack) = &mut self.call_stack {
+            call_stack.push(return_address);
+        }
+    }
+
+    /// Record a return from the innermost call.
+    #[inline]
+    pub(crate) fn record_return(&mut self) {
+        if let Some(call_stack) = &mut self.call_stack {
+            call_stack.pop();
+        }
+    }
+
+    /// Advance the `time` CSR by the configured increment.
+    #[inline]
+    fn advance_time(&mut self) {
+        if self.time_increment != 0 {
+            let time = self.csrs.read_counter(TIME);
+            self.csrs
+                .write_counter(TIME, time.wrapping_add(self.time_increment));
+        }
+    }
+
+    /// Advance the `cycle` CSR, and the `instret` CSR if an instruction
+    /// `retired`, when counting is enabled, see
+    /// [Processor::set_count_instructions].
+    #[inline]
+    fn advance_counters(&mut self, retired: bool) {
+        if self.count_instructions {
+            let cycle = self.csrs.read_counter(CYCLE);
+            self.csrs.write_counter(CYCLE, cycle.wrapping_add(1));
+            if retired {
+                let instret = self.csrs.read_counter(INSTRET);
+                self.csrs.write_counter(INSTRET, instret.wrapping_add(1));
+            }
+        }
+    }
+
+    /// The most recently executed instruction.
+    ///
+    /// This is the last instruction successfully fetched and decoded by
+    /// [Processor::step], including an instruction whose execution raised an
+    /// exception.
+    pub fn last_instruction<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(
+        &self,
+    ) -> Option<I> {
+        self.last_instruction
+            .and_then(|raw_instruction| I::decode_for(raw_instruction, &self.isa).ok())
+    }
+
+    /// Run the processor forward until the next [ExecutionResult::Halt] or
+    /// [ExecutionResult::Exited], returning which.
+    pub fn run<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(&mut self) -> ExecutionResult {
+        self.run_with_trace::<I>(|_, _| {})
+    }
+
+    /// Create a processor with the `instructions` stored from address 0 and
+    /// run it until the next [ExecutionResult::Halt], returning the final
+    /// state of the processor.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use riskv::{csr::CSR32, instructions::Instruction, processor::Processor, registers::Register};
+    ///
+    /// let processor = Processor::<i32, CSR32>::run_program([
+    ///     Instruction::ADDI { rd: Register::A0, rs1: Register::ZERO, imm: 40 },
+    ///     Instruction::ADDI { rd: Register::A1, rs1: Register::ZERO, imm: 2 },
+    ///     Instruction::ADD { rd: Register::A2, rs1: Register::A0, rs2: Register::A1 },
+    /// ]);
+    /// assert_eq!(processor.register(Register::A2), 42);
+    /// ```
+    pub fn run_program<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(
+        instructions: impl IntoIterator<Item = I>,
+    ) -> Self
+    where
+        Self: Default,
+    {
+        let mut processor = Self::default();
+        processor.store_instructions(0, instructions);
+        processor.run::<I>();
+        processor
+    }
+
+    /// Run the processor forward until the next [ExecutionResult::Halt] or
+    /// [ExecutionResult::Exited], returning which.
+    ///
+    /// Before each instruction is executed `trace` is called with the
+    /// programme counter and the decoded instruction.
+    pub fn run_with_trace<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(
+        &mut self,
+        mut trace: impl FnMut(&R, &I),
+    ) -> ExecutionResult {
+        loop {
+            match self.step_with_trace::<I>(&mut trace) {
+                ExecutionResult::Continue => {}
+                result => return result,
+            }
+        }
+    }
+
+    /// Run the processor forward until the next [ExecutionResult::Halt] or
+    /// [ExecutionResult::Exited], calling `tick` after every `every`
+    /// instructions.
+    ///
+    /// This suits refreshing a user interface during a run, where tracing
+    /// each instruction would be too frequent.
+    ///
+    /// # Panics
+    ///
+    /// If `every` is zero.
+    pub fn run_with_tick<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(
+        &mut self,
+        every: u64,
+        mut tick: impl FnMut(&Self),
+    ) -> ExecutionResult {
+        assert!(every > 0, "the tick interval must be non-zero");
+        let mut steps = 0;
+        loop {
+            match self.step::<I>() {
+                ExecutionResult::Continue => {}
+                result => return result,
+            }
+            steps += 1;
+            if steps % every == 0 {
+                tick(self);
+            }
+        }
+    }
+
+    /// Run the processor forward until the next [ExecutionResult::Halt] or
+    /// [ExecutionResult::Exited], counting the number of times each
+    /// instruction is executed.
+    pub fn run_with_profile<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(
+        &mut self,
+    ) -> InstructionProfile {
+        let mut profile = InstructionProfile::default();
+        self.run_with_trace::<I>(|_, instruction| profile.record(instruction.mnemonic()));
+        profile
+    }
+
+    /// Run the processor forward until the next [ExecutionResult::Halt] or
+    /// [ExecutionResult::Exited], collecting the address of each instruction
+    /// executed at least once.
+    pub fn run_with_coverage<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(
+        &mut self,
+    ) -> BTreeSet<R>
+    where
+        R: Ord,
+    {
+        let mut coverage = BTreeSet::new();
+        self.run_with_trace::<I>(|pc, _| {
+            coverage.insert(*pc);
+        });
+        coverage
+    }
+
+    /// Run the processor forward until the next [ExecutionResult::Halt] or
+    /// until `duration` has elapsed as measured by the `time` CSR.
+    ///
+    /// Returns [ExecutionResult::Continue] if execution stopped because the
+    /// deadline was reached, and the result which stopped execution
+    /// otherwise.
+    ///
+    /// _Note_: unless a time increment has been set, see
+    /// [Processor::set_time_increment], the deadline can only be reached by
+    /// the programme writing to the `time` CSR.
+    pub fn run_for<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(
+        &mut self,
+        duration: u64,
+    ) -> ExecutionResult {
+        let deadline = self.csrs.read_counter(TIME).saturating_add(duration);
+        while self.csrs.read_counter(TIME) < deadline {
+            match self.step::<I>() {
+                ExecutionResult::Continue => {}
+                result => return result,
+            }
         }
+        ExecutionResult::Continue
     }
 
-    /// Run the processor forward until the next [ExecutionResult::Halt].
-    pub fn run<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(&mut self) {
-        while let ExecutionResult::Continue = self.step::<I>() {}
+    /// Run the processor forward until the next [ExecutionResult::Halt] or
+    /// [ExecutionResult::Exited], or until `timeout` of wall-clock time has
+    /// elapsed, returning [ExecutionResult::TimedOut].
+    ///
+    /// This guards interactive tools against programmes which never halt.
+    /// The clock is only checked every few instructions, so the run may
+    /// overrun the `timeout` slightly.
+    pub fn run_with_timeout<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(
+        &mut self,
+        timeout: Duration,
+    ) -> ExecutionResult {
+        let start = Instant::now();
+        loop {
+            for _ in 0..TIMEOUT_CHECK_INTERVAL {
+                match self.step::<I>() {
+                    ExecutionResult::Continue => {}
+                    result => return result,
+                }
+            }
+            if start.elapsed() >= timeout {
+                return ExecutionResult::TimedOut;
+            }
+        }
+    }
+
+    /// Run the processor forward until a store to the `tohost_address`,
+    /// returning the word at that address.
+    ///
+    /// This is the completion convention of the RISC-V conformance tests,
+    /// where the test writes to its `tohost` symbol and `1` indicates that the
+    /// test passed.
+    ///
+    /// If the processor halts before writing to the `tohost_address` the
+    /// current word at that address is returned.
+    ///
+    /// Alternatively, a [crate::devices::ToHostDevice] stops any run with
+    /// the exit status requested by the test.
+    pub fn run_until_tohost<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(
+        &mut self,
+        tohost_address: usize,
+    ) -> i32 {
+        self.last_store = None;
+        while let ExecutionResult::Continue = self.step::<I>() {
+            if let Some(store) = self.last_store.take() {
+                if (store.address..store.address + store.width).contains(&tohost_address) {
+                    break;
+                }
+            }
+        }
+        self.memory.load_word(tohost_address)
     }
 
     /// Run the processor forward from the provided memory location until the
-    /// next [ExecutionResult::Halt].
+    /// next [ExecutionResult::Halt] or [ExecutionResult::Exited], returning
+    /// which.
     pub fn run_from<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(
         &mut self,
         initial_mem_location: R,
-    ) {
+    ) -> ExecutionResult {
         self.pc = initial_mem_location;
         self.run::<I>()
     }
 
     /// The processor exception handler.
     ///
-    /// At some point this should jump execution to into a specified trap handler.
+    /// Traps to the handler in `mtvec`, or to the handler in `stvec` if the
+    /// exception was raised below machine mode and is delegated to supervisor
+    /// mode in `medeleg`. The cause, faulting programme counter, and privilege
+    /// level are recorded in the corresponding CSRs and `mstatus` fields.
+    ///
+    /// If no trap handler has been installed, execution is halted instead,
+    /// or exits if the exception is an exit request, see
+    /// [ExecutionResult::Exited].
+    ///
+    /// _Note_: only the direct trap vector mode is supported.
     #[inline]
-    fn handle_exception(&self, _exception: Exception) -> ExecutionResult {
-        // TODO handle other types of interrupts
-        ExecutionResult::Halt
+    fn handle_exception(&mut self, exception: Exception) -> ExecutionResult {
+        // TODO handle interrupts
+        if exception == Exception::Breakpoint && self.ebreak_policy == EbreakPolicy::Halt {
+            return ExecutionResult::Halt;
+        }
+        let code = exception.code();
+        let delegated = self.privilege != Privilege::Machine
+            && (self.csrs.read(MEDELEG).as_usize() >> code) & 1 == 1;
+        let (tvec, epc, cause, tval) = if delegated {
+            (STVEC, SEPC, SCAUSE, STVAL)
+        } else {
+            (MTVEC, MEPC, MCAUSE, MTVAL)
+        };
+
+        let handler = self.csrs.read(tvec) & R::from(!0b11);
+        if handler == R::from(0) {
+            if matches!(exception, Exception::EnvironmentCall(_))
+                && self.registers[Register::A7] == R::from(EXIT_SYSCALL)
+            {
+                return ExecutionResult::Exited(self.registers[Register::A0].as_usize() as i32);
+            }
+            return ExecutionResult::Halt;
+        }
+
+        self.csrs.read_write(epc, self.pc);
+        self.csrs.read_write(cause, R::from(code as i32));
+        self.csrs
+            .read_write(tval, R::from(exception.value() as i32));
+        let mstatus = MStatus::new(&self.csrs);
+        if delegated {
+            mstatus.set_spie(mstatus.sie());
+            mstatus.set_sie(false);
+            mstatus.set_spp(self.privilege as u8);
+            self.privilege = Privilege::Supervisor;
+        } else {
+            mstatus.set_mpie(mstatus.mie());
+            mstatus.set_mie(false);
+            mstatus.set_mpp(self.privilege as u8);
+            self.privilege = Privilege::Machine;
+        }
+        self.pc = handler;
+        ExecutionResult::Continue
     }
 
     /// Store the `instructions` into memory starting from the `initial_memory_location`.
+    ///
+    /// The instructions are loaded ignoring any memory protection, so a code
+    /// segment can be loaded before being marked as read-only. Each
+    /// instruction takes its [InstructionSet::instruction_size], so compressed
+    /// instructions are packed into 2 bytes.
     pub fn store_instructions<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(
         &mut self,
         initial_mem_location: usize,
         instructions: impl IntoIterator<Item = I>,
     ) {
-        let final_location = instructions.into_iter().map(I::encode).fold(
-            initial_mem_location,
-            |location, instruction| {
-                let next_location = location + std::mem::size_of_val(&instruction);
-                self.memory.store_word(location, instruction as i32);
-                next_location
-            },
-        );
-        self.memory.resize::<4>(final_location);
+        let final_location =
+            instructions
+                .into_iter()
+                .fold(initial_mem_location, |location, instruction| {
+                    let size = instruction.instruction_size().as_usize();
+                    self.memory
+                        .write(location, &instruction.encode().to_le_bytes()[..size]);
+                    location + size
+                });
+        self.memory.reserve(final_location + 4);
     }
-}
 
-/// The result of executing an execution.
-pub enum ExecutionResult {
+    /// Disassemble the instructions in memory from `start` up to `end`, in
+    /// the style of `objdump`, one instruction per line with its address and
+    /// encoding.
+    ///
+    /// Words which cannot be decoded, or whose extension is not enabled, are
+    /// shown as a `.word` directive.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use riskv::{csr::CSR32, instructions::Instruction, processor::Processor, registers::Register};
+    ///
+    /// let mut processor = Processor::<i32, CSR32>::default();
+    /// processor.store_instructions(
+    ///     0x100,
+    ///     [Instruction::ADD { rd: Register::A0, rs1: Register::A1, rs2: Register::A0 }],
+    /// );
+    ///
+    /// assert_eq!(
+    ///     processor.disassemble_text::<Instruction>(0x100, 0x104),
+    ///     "        100: 00a58533  add a0,a1,a0\n"
+    /// );
+    /// ```
+    pub fn disassemble_text<I>(&self, start: usize, end: usize) -> String
+    where
+        I: InstructionSet<RegisterType = R, CSRType = CSRs> + Display,
+    {
+        let mut text = String::new();
+        let mut address = start;
+        while address < end {
+            let word = self.memory.peek_word(address);
+            let (size, assembly) = match I::decode_for(word, &self.isa) {
+                Ok(instruction) if self.isa.supports(instruction.extension()) => (
+                    instruction.instruction_size().as_usize(),
+                    instruction.to_string(),
+                ),
+                _ => (4, format!(".word {word:#010x}")),
+            };
+            let raw = match size {
+                2 => format!("{:04x}", word as u16),
+                _ => format!("{word:08x}"),
+            };
+            // Writing to a `String` cannot fail.
+            let _ = writeln!(text, "{address:>11x}: {raw:<8}  {assembly}");
+            address += size;
+        }
+        text
+    }
+
+    /// Replace the instruction at `address` with `instruction`, returning the
+    /// instruction it replaced so that it can be restored later.
+    ///
+    /// This is how a debugger sets a software breakpoint, patching in an
+    /// `ebreak` and restoring the original instruction once it is hit. Like
+    /// [Processor::store_instructions], the instruction is written ignoring
+    /// any memory protection.
+    ///
+    /// Raises [Exception::UnimplementedInstruction] without patching if the
+    /// memory at `address` does not hold an instruction, or holds one of an
+    /// extension which is not enabled, see [Processor::with_isa].
+    ///
+    /// _Note_: only the bytes of the new instruction are written, so replacing
+    /// a compressed instruction with a full width one also overwrites the
+    /// following parcel.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use riskv::{csr::CSR32, instructions::Instruction, processor::Processor, registers::Register};
+    ///
+    /// let mut processor = Processor::<i32, CSR32>::default();
+    /// let addi = Instruction::ADDI { rd: Register::A0, rs1: Register::ZERO, imm: 1 };
+    /// processor.store_instructions(0, [addi]);
+    ///
+    /// assert_eq!(processor.patch_instruction(0, Instruction::EBREAK), Ok(addi));
+    /// assert_eq!(processor.patch_instruction(0, addi), Ok(Instruction::EBREAK));
+    /// ```
+    pub fn patch_instruction<I: InstructionSet<RegisterType = R, CSRType = CSRs>>(
+        &mut self,
+        address: usize,
+        instruction: I,
+    ) -> Result<I, Exception> {
+        let raw_instruction = self.memory.peek_word(address);
+        let previous = I::decode_for(raw_instruction, &self.isa)?;
+        if !self.isa.supports(previous.extension()) {
+            return Err(Exception::UnimplementedInstruction(raw_instruction));
+        }
+        let size = instruction.instruction_size().as_usize();
+        self.memory
+            .write(address, &instruction.encode().to_le_bytes()[..size]);
+        Ok(previous)
+    }
+
+    /// Store the pre-encoded instruction `words` into memory starting from
+    /// the `base` address, for example the output of an external assembler.
+    ///
+    /// Like [Processor::store_instructions], the words are stored little
+    /// endian ignoring any memory protection.
+    pub fn store_words(&mut self, base: usize, words: &[u32]) {
+        for (index, word) in words.iter().enumerate() {
+            self.memory.write(
+                base + index * std::mem::size_of::<u32>(),
+                &word.to_le_bytes(),
+            );
+        }
+        self.memory.reserve(base + std::mem::size_of_val(words) + 4);
+    }
+
+    /// Load the bytes from the `reader` into memory starting from `base`,
+    /// returning the number of bytes loaded.
+    ///
+    /// The bytes are streamed into memory in chunks avoiding reading the whole
+    /// image into an intermediate buffer. Like [Processor::store_instructions],
+    /// the bytes are loaded ignoring any memory protection.
+    pub fn load_from_reader(&mut self, base: usize, mut reader: impl Read) -> io::Result<usize> {
+        let mut buffer = [0; LOAD_CHUNK_SIZE];
+        let mut loaded = 0;
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => return Ok(loaded),
+                Ok(read) => {
+                    self.memory.write(base + loaded, &buffer[..read]);
+                    loaded += read;
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Load the segments of an assembled `program` into memory.
+    ///
+    /// Like [Processor::store_instructions], the programme is loaded ignoring
+    /// any memory protection.
+    pub fn load_program(&mut self, program: &Program) {
+        for segment in &program.segments {
+            self.memory.write(segment.address, &segment.bytes);
+        }
+    }
+
+    /// Write each span of bytes in the `entries` manifest into memory at its
+    /// address, for example to set up a test fixture with scattered code and
+    /// data regions in one call.
+    ///
+    /// Like [Processor::store_instructions], the bytes are written ignoring
+    /// any memory protection. Later entries overwrite earlier ones where they
+    /// overlap.
+    pub fn load_manifest(&mut self, entries: &[(usize, &[u8])]) {
+        for (address, bytes) in entries {
+            self.memory.write(*address, bytes);
+        }
+    }
+
+    /// Assemble the RISC-V assembly file at `path`, see [crate::asm], and load
+    /// the programme into memory starting from `base`.
+    ///
+    /// Errors raised while assembling are reported as
+    /// [io::ErrorKind::InvalidData].
+    pub fn load_asm_file(&mut self, path: &Path, base: usize) -> io::Result<()> {
+        let source = std::fs::read_to_string(path)?;
+        let program = asm::assemble(&source, base)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        self.load_program(&program);
+        Ok(())
+    }
+}
+
+impl<R, CSRs: ControlStatusRegisters<Register = R>> Processor<R, CSRs>
+where
+    R: Display + From<i32> + PartialEq + Copy,
+{
+    /// The differences between the architectural state of this processor and
+    /// `other`, that is their programme counters, privilege levels,
+    /// registers, CSRs, and memory.
+    ///
+    /// This is the assertion primitive for differential testing against a
+    /// reference model, reporting every difference at once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use riskv::{csr::CSR32, processor::Processor, registers::Register};
+    ///
+    /// let processor = Processor::<i32, CSR32>::default();
+    /// let mut other = Processor::<i32, CSR32>::default();
+    /// other.set_register(Register::A0, 42);
+    ///
+    /// assert_eq!(processor.diff(&other).to_string(), "a0: 0 != 42");
+    /// ```
+    pub fn diff(&self, other: &Self) -> StateDiff<R> {
+        StateDiff {
+            pc: (self.pc != other.pc).then_some((self.pc, other.pc)),
+            privilege: (self.privilege != other.privilege)
+                .then_some((self.privilege, other.privilege)),
+            registers: self.registers.diff(&other.registers),
+            csrs: (0..4096)
+                .map(|index| (index, self.csrs.read(index), other.csrs.read(index)))
+                .filter(|(_, value, other)| value != other)
+                .collect(),
+            memory: self.memory.diff(&other.memory),
+        }
+    }
+
+    /// A JSON snapshot of the processor's state for logging and golden-file
+    /// tests.
+    ///
+    /// To keep the snapshot small, only the non-zero registers and CSRs are
+    /// included, and memory is given as the runs of non-zero bytes, see
+    /// [Memory::map_summary], keyed by their hexadecimal start address.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use riskv::{csr::CSR32, instructions::Instruction, processor::Processor, registers::Register};
+    ///
+    /// let mut processor = Processor::<i32, CSR32>::default();
+    /// processor.set_register(Register::A0, 42);
+    /// processor.set_pc(8);
+    ///
+    /// assert_eq!(
+    ///     processor.to_json(),
+    ///     r#"{"pc":8,"privilege":"Machine","registers":{"a0":42},"csrs":{},"memory":{}}"#,
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        let zero = R::from(0);
+        let mut json = format!(
+            r#"{{"pc":{},"privilege":"{:?}","registers":{{"#,
+            self.pc, self.privilege
+        );
+        let registers = (1..32)
+            .map(Register::from)
+            .filter(|register| self.registers[*register] != zero)
+            .map(|register| format!(r#""{}":{}"#, register.name(), self.registers[register]));
+        json.push_str(&registers.collect::<Vec<_>>().join(","));
+        json.push_str(r#"},"csrs":{"#);
+        let csrs = (0..4096)
+            .filter(|index| self.csrs.read(*index) != zero)
+            .map(|index| match crate::csr::name(index) {
+                Some(name) => format!(r#""{name}":{}"#, self.csrs.read(index)),
+                None => format!(r#""{index:#x}":{}"#, self.csrs.read(index)),
+            });
+        json.push_str(&csrs.collect::<Vec<_>>().join(","));
+        json.push_str(r#"},"memory":{"#);
+        let memory = self.memory.non_zero_runs().map(|(start, run)| {
+            let bytes: String = run.iter().map(|byte| format!("{byte:02x}")).collect();
+            format!(r#""{start:#x}":"{bytes}""#)
+        });
+        json.push_str(&memory.collect::<Vec<_>>().join(","));
+        json.push_str("}}");
+        json
+    }
+}
+
+/// A processor with the 32-bit registers and CSRs of RV32.
+///
+/// # Example
+///
+/// ```
+/// use riskv::{instructions::Instruction, registers::Register, Rv32Processor};
+///
+/// let mut processor = Rv32Processor::default();
+/// processor.store_instructions(0, Instruction::LI(Register::A0, 42));
+/// processor.run::<Instruction>();
+///
+/// assert_eq!(processor.return_value(), 42);
+/// ```
+pub type Rv32Processor = Processor<i32, CSR32>;
+
+/// A processor with the 64-bit registers and CSRs of RV64.
+///
+/// The default processor is configured as `RV64IM`, however this crate has
+/// no [InstructionSet] for RV64 yet, since [Instruction] operates on 32-bit
+/// registers, so instructions cannot be executed on it.
+///
+/// # Example
+///
+/// ```
+/// use riskv::{registers::Register, Rv64Processor};
+///
+/// let mut processor = Rv64Processor::default();
+/// processor.set_register(Register::A0, i64::MAX);
+///
+/// assert_eq!(processor.isa().xlen(), 64);
+/// assert_eq!(processor.return_value(), i64::MAX);
+/// ```
+pub type Rv64Processor = Processor<i64, CSR64>;
+
+/// The result of executing an execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionResult {
     /// Execution should continue to the next instruction.
     Continue,
     /// Execution should halt and yield control to the caller.
     Halt,
+    /// The programme requested to exit with the status code, by executing
+    /// an `ECALL` with `a7 == 93` and the status in `a0`, and no trap handler
+    /// is installed, or through a memory-mapped device, see
+    /// [MemoryBus::take_exit_request].
+    Exited(i32),
+    /// The run was stopped after exceeding its wall-clock time limit, see
+    /// [Processor::run_with_timeout].
+    TimedOut,
+    /// The stack pointer was moved below the limit of the stack guard, see
+    /// [Processor::set_stack_guard].
+    StackOverflow,
+}
+
+/// What happened during a single step, see [Processor::step_and_report].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepReport<R> {
+    /// The address of the instruction, the programme counter before the step.
+    pub address: R,
+    /// The disassembled instruction, or `None` if it could not be fetched.
+    pub instruction: Option<String>,
+    /// The registers changed by the step, each with its value before and
+    /// after, see [Registers::diff].
+    pub registers: Vec<(Register, R, R)>,
+    /// The store performed by the step, if any.
+    pub store: Option<MemAccess>,
+    /// The result of the step.
+    pub result: ExecutionResult,
+}
+
+/// The differences between the architectural state of two processors, see
+/// [Processor::diff].
+///
+/// Each difference holds the value in the first processor followed by the
+/// value in the second.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateDiff<R> {
+    /// The programme counters, if they differ.
+    pub pc: Option<(R, R)>,
+    /// The privilege levels, if they differ.
+    pub privilege: Option<(Privilege, Privilege)>,
+    /// The registers which differ, see [Registers::diff].
+    pub registers: Vec<(Register, R, R)>,
+    /// The CSRs which differ, by address.
+    pub csrs: Vec<(u16, R, R)>,
+    /// The bytes of memory which differ, by address.
+    pub memory: Vec<(usize, u8, u8)>,
+}
+
+impl<R> StateDiff<R> {
+    /// Returns `true` if the states are the same.
+    pub fn is_empty(&self) -> bool {
+        self.pc.is_none()
+            && self.privilege.is_none()
+            && self.registers.is_empty()
+            && self.csrs.is_empty()
+            && self.memory.is_empty()
+    }
+}
+
+/// The architectural state of a processor captured by
+/// [Processor::checkpoint], which it can be rolled back to with
+/// [Processor::restore].
+#[derive(Debug, Clone)]
+pub struct Checkpoint<R, CSRs, M = Memory> {
+    /// The processor's registers.
+    registers: Registers<R>,
+    /// The programme counter.
+    pc: R,
+    /// The control status registers.
+    csrs: CSRs,
+    /// The processor's memory.
+    memory: M,
+    /// The privilege level.
+    privilege: Privilege,
+}
+
+/// Lists each difference on its own line, such as `a0: 5 != 42`.
+impl<R: Display> Display for StateDiff<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines = Vec::new();
+        if let Some((pc, other)) = &self.pc {
+            lines.push(format!("pc: {pc} != {other}"));
+        }
+        if let Some((privilege, other)) = &self.privilege {
+            lines.push(format!("privilege: {privilege:?} != {other:?}"));
+        }
+        for (register, value, other) in &self.registers {
+            lines.push(format!("{}: {value} != {other}", register.name()));
+        }
+        for (csr, value, other) in &self.csrs {
+            match crate::csr::name(*csr) {
+                Some(name) => lines.push(format!("{name}: {value} != {other}")),
+                None => lines.push(format!("csr {csr:#x}: {value} != {other}")),
+            }
+        }
+        for (address, byte, other) in &self.memory {
+            lines.push(format!("[{address:#x}]: {byte:#04x} != {other:#04x}"));
+        }
+        f.write_str(&lines.join("\n"))
+    }
+}
+
+/// What happens when an `EBREAK` is executed with no debugger attached.
+///
+/// Bare-metal programmes often use `ebreak` to mark the end of execution.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EbreakPolicy {
+    /// Raise [Exception::Breakpoint], trapping to the handler if one is
+    /// installed and halting otherwise.
+    #[default]
+    Trap,
+    /// Halt execution, leaving the programme counter at the `ebreak`.
+    Halt,
+    /// Treat the `ebreak` as a no-op and continue with the next instruction.
+    Ignore,
+}
+
+/// What happens when a load or store is not naturally aligned, that is its
+/// address is not a multiple of its width.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum UnalignedPolicy {
+    /// Raise [Exception::LoadAddressMisaligned] or
+    /// [Exception::StoreAddressMisaligned], allowing a trap handler to
+    /// emulate the access in software.
+    Trap,
+    /// Emulate the access by reading or writing each byte, as if the hardware
+    /// supported unaligned accesses.
+    #[default]
+    Emulate,
 }
 
 #[cfg(test)]
 mod test {
     use crate::test::macros::*;
-    use crate::{csr::CSR32, instructions::Instruction, registers::Register};
+    use crate::{
+        csr::{CSR32, MSTATUS, SSCRATCH, TSELECT},
+        devices::ToHostDevice,
+        instructions::Instruction,
+        memory::{Protection, SparseMemory},
+        registers::Register,
+    };
     use pretty_assertions::assert_eq;
 
     use super::*;
@@ -118,4 +1551,1931 @@ mod test {
             results_in: {registers: {a0: 21, a1:21, a2: 42}, pc: 12},
         );
     }
+
+    #[test]
+    fn fetch_from_data_region() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(
+            0,
+            [Instruction::JAL {
+                rd: Register::ZERO,
+                offset: 64,
+            }],
+        );
+        processor.memory.protect(64..128, Protection::ReadWrite);
+
+        assert_eq!(processor.inner_step::<Instruction>(|_, _| {}), Ok(()));
+        assert_eq!(processor.pc, 64);
+        assert_eq!(
+            processor.inner_step::<Instruction>(|_, _| {}),
+            Err(Exception::InstructionAccessFault)
+        );
+        assert_eq!(processor.pc, 64);
+    }
+
+    #[test]
+    fn fetch_misaligned() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(0, instructions![Instruction::LI(Register::A0, 21)]);
+        processor.pc = 1;
+
+        assert_eq!(
+            processor.inner_step::<Instruction>(|_, _| {}),
+            Err(Exception::MisalignedInstructionFetch)
+        );
+        assert!(matches!(
+            processor.step::<Instruction>(),
+            ExecutionResult::Halt
+        ));
+        assert_eq!(processor.pc, 1);
+        assert_eq!(processor.registers[Register::A0], 0);
+    }
+
+    #[test]
+    fn run_with_profile() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(
+            0,
+            instructions![
+                Instruction::LI(Register::A0, 21),
+                Instruction::LI(Register::A1, 21),
+                Instruction::ADD {
+                    rd: Register::A2,
+                    rs1: Register::A1,
+                    rs2: Register::A0,
+                },
+                Instruction::SW {
+                    rs1: Register::ZERO,
+                    rs2: Register::A2,
+                    offset: 64,
+                },
+            ],
+        );
+
+        let profile = processor.run_with_profile::<Instruction>();
+
+        assert_eq!(profile.total(), 4);
+        assert_eq!(profile.count("addi"), 2);
+        assert_eq!(profile.count("add"), 1);
+        assert_eq!(profile.count("sw"), 1);
+        assert_eq!(profile.count("lw"), 0);
+    }
+
+    #[test]
+    fn run_with_coverage() {
+        let program = asm::assemble(
+            "
+                    li a0, 2
+            loop:   addi a0, a0, -1
+                    bnez a0, loop
+                    bnez a1, target
+                    j done
+            target: li a2, 1
+            done:   nop
+            ",
+            0,
+        )
+        .unwrap();
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.load_program(&program);
+
+        let coverage = processor.run_with_coverage::<Instruction>();
+
+        assert_eq!(coverage, BTreeSet::from([0, 4, 8, 12, 16, 24]));
+    }
+
+    #[test]
+    fn disassemble_text() {
+        let mut processor = Processor::<i32, CSR32>::with_isa("rv32imc").unwrap();
+        processor.store_words(
+            0x1000,
+            &[
+                Instruction::ADDI {
+                    rd: Register::SP,
+                    rs1: Register::SP,
+                    imm: -16,
+                }
+                .encode(),
+                // c.swsp ra, 12(sp) followed by c.lwsp ra, 12(sp).
+                0x40b2_c606,
+                Instruction::BNE {
+                    rs1: Register::A0,
+                    rs2: Register::ZERO,
+                    offset: -12,
+                }
+                .encode(),
+                0xffff_ffff,
+                Instruction::MRET.encode(),
+            ],
+        );
+
+        assert_eq!(
+            processor.disassemble_text::<Instruction>(0x1000, 0x1014),
+            [
+                "       1000: ff010113  addi sp,sp,-16",
+                "       1004: c606      c.swsp ra,12(sp)",
+                "       1006: 40b2      c.lwsp ra,12(sp)",
+                "       1008: fe051ae3  bne a0,zero,-12",
+                "       100c: ffffffff  .word 0xffffffff",
+                "       1010: 30200073  mret",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn store_words() {
+        let instructions = [
+            Instruction::ADDI {
+                rd: Register::A0,
+                rs1: Register::ZERO,
+                imm: 40,
+            },
+            Instruction::ADD {
+                rd: Register::A1,
+                rs1: Register::A0,
+                rs2: Register::A0,
+            },
+            Instruction::SW {
+                rs1: Register::SP,
+                rs2: Register::A1,
+                offset: -4,
+            },
+        ];
+        let words = instructions.map(Instruction::encode);
+
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_words(8, &words);
+
+        for (index, instruction) in instructions.into_iter().enumerate() {
+            assert_eq!(
+                Instruction::decode_at(&mut processor.memory, 8 + index * 4),
+                Ok((instruction, 4))
+            );
+        }
+        assert_eq!(processor.memory.load_word(20), 0);
+    }
+
+    #[test]
+    fn load_from_reader() {
+        let image: Vec<u8> = (0..10_000).map(|i| (i % 251) as u8).collect();
+        let mut processor = Processor::<i32, CSR32>::default();
+
+        let loaded = processor
+            .load_from_reader(16, std::io::Cursor::new(&image))
+            .unwrap();
+
+        assert_eq!(loaded, image.len());
+        for (i, byte) in image.iter().enumerate() {
+            assert_eq!(processor.memory.load_byte(16 + i) as u8, *byte);
+        }
+        assert_eq!(processor.memory.load_byte(15), 0);
+    }
+
+    #[test]
+    fn load_manifest() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.memory.protect(32..64, Protection::ReadOnly);
+
+        processor.load_manifest(&[(4, &[0x13, 0, 0, 0]), (32, &[1, 2, 3])]);
+
+        assert_eq!(processor.memory.load_word(4), 0x13);
+        assert_eq!(processor.memory.load_word_unsigned(32), 0x0003_0201);
+        for gap in 8..32 {
+            assert_eq!(processor.memory.load_byte(gap), 0, "{gap}");
+        }
+    }
+
+    #[test]
+    fn load_asm_file() {
+        let path = std::env::temp_dir().join(format!("riskv-load-asm-{}.s", std::process::id()));
+        std::fs::write(
+            &path,
+            "
+            # Double the data item.
+            .text
+                lw a0, value(zero)
+                add a0, a0, a0
+            .org 16
+            value:
+                .word 21
+            ",
+        )
+        .unwrap();
+        let mut processor = Processor::<i32, CSR32>::default();
+
+        let result = processor.load_asm_file(&path, 0);
+        std::fs::remove_file(&path).unwrap();
+        result.unwrap();
+        processor.run::<Instruction>();
+
+        assert_eq!(processor.registers[Register::A0], 42);
+        assert_eq!(processor.pc, 8);
+    }
+
+    #[test]
+    fn load_asm_file_with_error() {
+        let path = std::env::temp_dir().join(format!("riskv-asm-error-{}.s", std::process::id()));
+        std::fs::write(&path, "addi a0, a9, 1").unwrap();
+        let mut processor = Processor::<i32, CSR32>::default();
+
+        let result = processor.load_asm_file(&path, 0);
+        std::fs::remove_file(&path).unwrap();
+
+        let error = result.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 10: unknown register 'a9'"
+        );
+    }
+
+    #[test]
+    fn time_advances() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(
+            0,
+            instructions![
+                Instruction::CSRR(Register::A0, TIME),
+                Instruction::CSRR(Register::A1, TIME),
+                Instruction::CSRR(Register::A2, TIME),
+            ],
+        );
+        processor.set_time_increment(10);
+
+        processor.run::<Instruction>();
+
+        assert_eq!(processor.registers[Register::A0], 0);
+        assert_eq!(processor.registers[Register::A1], 10);
+        assert_eq!(processor.registers[Register::A2], 20);
+        assert_eq!(processor.csrs.read_counter(TIME), 40);
+    }
+
+    /// A fixed-size memory which faults on stores beyond its end.
+    #[derive(Debug)]
+    struct FixedMemory([u8; 64]);
+
+    impl MemoryBus for FixedMemory {
+        fn load_byte_unsigned(&mut self, location: usize) -> u8 {
+            self.peek_word(location) as u8
+        }
+
+        fn store_byte(&mut self, location: usize, value: i8) -> Result<(), Exception> {
+            let byte = self
+                .0
+                .get_mut(location)
+                .ok_or(Exception::StoreAccessFault)?;
+            *byte = value as u8;
+            Ok(())
+        }
+
+        fn peek_word(&self, location: usize) -> u32 {
+            u32::from_le_bytes(std::array::from_fn(|offset| {
+                self.0.get(location + offset).copied().unwrap_or_default()
+            }))
+        }
+
+        fn write(&mut self, location: usize, bytes: &[u8]) {
+            self.0[location..location + bytes.len()].copy_from_slice(bytes);
+        }
+    }
+
+    #[test]
+    fn run_at_high_address_on_sparse_memory() {
+        let mut processor = Processor::<i32, CSR32, _>::from_parts(
+            Registers::default(),
+            0x8000_0000_u32 as i32,
+            CSR32::default(),
+            SparseMemory::default(),
+        );
+        processor.store_instructions(
+            0x8000_0000,
+            instructions![
+                Instruction::LI(Register::A0, 42),
+                [
+                    Instruction::SW {
+                        rs1: Register::SP,
+                        rs2: Register::A0,
+                        offset: -4,
+                    },
+                    Instruction::LW {
+                        rd: Register::A1,
+                        rs1: Register::SP,
+                        offset: -4,
+                    },
+                ],
+            ],
+        );
+        processor.set_stack_top(0x7000_0000);
+
+        processor.run::<Instruction>();
+
+        assert_eq!(processor.registers[Register::A1], 42);
+        assert_eq!(processor.memory.load_word(0x6fff_fffc), 42);
+        // Only the code page and the stack page are allocated.
+        assert_eq!(processor.memory.page_count(), 2);
+    }
+
+    #[test]
+    fn run_on_custom_memory_bus() {
+        let mut processor = Processor::<i32, CSR32, _>::from_parts(
+            Registers::default(),
+            0,
+            CSR32::default(),
+            FixedMemory([0; 64]),
+        );
+        processor.store_instructions(
+            0,
+            instructions![
+                Instruction::LI(Register::A0, 0x1234_5678),
+                Instruction::SW {
+                    rs1: Register::ZERO,
+                    rs2: Register::A0,
+                    offset: 32,
+                },
+                Instruction::LH {
+                    rd: Register::A1,
+                    rs1: Register::ZERO,
+                    offset: 34,
+                },
+                Instruction::SW {
+                    rs1: Register::ZERO,
+                    rs2: Register::A0,
+                    offset: 64,
+                },
+            ],
+        );
+
+        processor.run::<Instruction>();
+
+        assert_eq!(processor.register(Register::A1), 0x1234);
+        assert_eq!(processor.memory().peek_word(32), 0x1234_5678);
+        // The store beyond the end of memory faulted.
+        assert_eq!(processor.pc(), 16);
+        assert_eq!(
+            processor.disassemble_text::<Instruction>(16, 20),
+            "         10: 04a02023  sw a0,64(zero)\n"
+        );
+    }
+
+    #[test]
+    fn counters_advance() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(
+            0,
+            instructions![
+                Instruction::RDCYCLE(Register::A0),
+                Instruction::NOP,
+                Instruction::RDINSTRET(Register::A1),
+                Instruction::RDCYCLEH(Register::A2),
+                Instruction::RDINSTRETH(Register::A3),
+                Instruction::RDTIME(Register::A4),
+            ],
+        );
+        processor.set_count_instructions(true);
+        processor.set_time_increment(10);
+
+        processor.run::<Instruction>();
+
+        assert_eq!(processor.registers[Register::A0], 0);
+        assert_eq!(processor.registers[Register::A1], 2);
+        assert_eq!(processor.registers[Register::A2], 0);
+        assert_eq!(processor.registers[Register::A3], 0);
+        assert_eq!(processor.registers[Register::A4], 50);
+        // The final step fetches an illegal instruction and halts, which
+        // is not counted.
+        assert_eq!(processor.csrs.read_counter(CYCLE), 6);
+        assert_eq!(processor.csrs.read_counter(INSTRET), 6);
+    }
+
+    #[test]
+    fn faulting_step_does_not_retire() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(0, Instruction::ECALL);
+        processor.csrs.read_write(MTVEC, 64);
+        processor.set_count_instructions(true);
+
+        assert_eq!(processor.step::<Instruction>(), ExecutionResult::Continue);
+        assert_eq!(processor.pc, 64);
+        assert_eq!(processor.csrs.read_counter(CYCLE), 1);
+        assert_eq!(processor.csrs.read_counter(INSTRET), 0);
+    }
+
+    #[test]
+    fn counters_disabled() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(0, Instruction::RDINSTRET(Register::A0));
+
+        processor.run::<Instruction>();
+
+        assert_eq!(processor.csrs.read_counter(CYCLE), 0);
+        assert_eq!(processor.csrs.read_counter(INSTRET), 0);
+    }
+
+    #[test]
+    fn run_for_stops_at_deadline() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(
+            0,
+            instructions![
+                [Instruction::ADDI {
+                    rd: Register::A0,
+                    rs1: Register::A0,
+                    imm: 1
+                }],
+                Instruction::J(-4),
+            ],
+        );
+        processor.set_time_increment(5);
+
+        assert!(matches!(
+            processor.run_for::<Instruction>(50),
+            ExecutionResult::Continue
+        ));
+        assert_eq!(processor.registers[Register::A0], 5);
+        assert_eq!(processor.pc, 0);
+
+        assert!(matches!(
+            processor.run_for::<Instruction>(20),
+            ExecutionResult::Continue
+        ));
+        assert_eq!(processor.registers[Register::A0], 7);
+        assert_eq!(processor.csrs.read_counter(TIME), 70);
+    }
+
+    #[test]
+    fn run_for_halts() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.set_time_increment(1);
+
+        assert!(matches!(
+            processor.run_for::<Instruction>(100),
+            ExecutionResult::Halt
+        ));
+        assert_eq!(processor.csrs.read_counter(TIME), 1);
+    }
+
+    #[test]
+    fn memory_tracer() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(
+            0,
+            instructions![
+                Instruction::LI(Register::A0, -2),
+                [
+                    Instruction::SW {
+                        rs1: Register::ZERO,
+                        rs2: Register::A0,
+                        offset: 0x100
+                    },
+                    Instruction::LBU {
+                        rd: Register::A1,
+                        rs1: Register::ZERO,
+                        offset: 0x101
+                    },
+                    Instruction::SH {
+                        rs1: Register::ZERO,
+                        rs2: Register::A1,
+                        offset: 0x104
+                    },
+                    Instruction::LH {
+                        rd: Register::A2,
+                        rs1: Register::ZERO,
+                        offset: 0x100
+                    },
+                ],
+            ],
+        );
+        let trace = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = trace.clone();
+        processor.set_memory_tracer(move |access| recorder.lock().unwrap().push(access));
+
+        processor.run::<Instruction>();
+        // A processor with a memory tracer can be moved to another thread.
+        let processor = std::thread::spawn(move || processor).join().unwrap();
+        assert!(processor.memory_tracer.is_some());
+
+        let access = |address, width, access, value| MemAccess {
+            address,
+            width,
+            access,
+            value,
+        };
+        assert_eq!(
+            *trace.lock().unwrap(),
+            vec![
+                access(0x100, 4, Access::Store, 0xFFFF_FFFE),
+                access(0x101, 1, Access::Load, 0xFF),
+                access(0x104, 2, Access::Store, 0xFF),
+                access(0x100, 2, Access::Load, 0xFFFE),
+            ]
+        );
+    }
+
+    #[test]
+    fn run_until_tohost() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(
+            0,
+            instructions![
+                Instruction::LI(Register::A0, 1),
+                [Instruction::SW {
+                    rs1: Register::ZERO,
+                    rs2: Register::A0,
+                    offset: 0x200
+                }],
+                Instruction::LI(Register::A1, 42),
+            ],
+        );
+
+        assert_eq!(processor.run_until_tohost::<Instruction>(0x200), 1);
+        assert_eq!(processor.pc, 8);
+        assert_eq!(processor.registers[Register::A1], 0);
+    }
+
+    #[test]
+    fn run_with_tohost_device() {
+        let mut processor = Processor::<i32, CSR32, _>::from_parts(
+            Registers::default(),
+            0,
+            CSR32::default(),
+            ToHostDevice::new(Memory::default(), 0x200),
+        );
+        processor.store_instructions(
+            0,
+            instructions![
+                Instruction::LI(Register::A0, 1),
+                [Instruction::SW {
+                    rs1: Register::ZERO,
+                    rs2: Register::A0,
+                    offset: 0x200
+                }],
+                Instruction::LI(Register::A1, 42),
+            ],
+        );
+
+        assert_eq!(processor.run::<Instruction>(), ExecutionResult::Exited(0));
+        assert_eq!(processor.pc, 8);
+        assert_eq!(processor.registers[Register::A1], 0);
+    }
+
+    #[test]
+    fn run_until_tohost_halts() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(0, Instruction::LI(Register::A0, 1));
+
+        assert_eq!(processor.run_until_tohost::<Instruction>(0x200), 0);
+        assert_eq!(processor.registers[Register::A0], 1);
+    }
+
+    #[test]
+    fn execute_one() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.set_register(Register::A0, 40);
+        processor.set_register(Register::A1, 2);
+        processor.set_register(Register::ZERO, 1);
+        processor.set_pc(0x100);
+
+        assert_eq!(
+            processor.execute_one(Instruction::ADD {
+                rd: Register::A2,
+                rs1: Register::A0,
+                rs2: Register::A1
+            }),
+            Ok(())
+        );
+
+        assert_eq!(processor.register(Register::A2), 42);
+        assert_eq!(processor.register(Register::ZERO), 0);
+        assert_eq!(processor.pc(), 0x104);
+        assert_eq!(
+            processor.execute_one(Instruction::LW {
+                rd: Register::A0,
+                rs1: Register::ZERO,
+                offset: -4
+            }),
+            Err(Exception::MemoryAccessFault)
+        );
+        assert_eq!(processor.pc(), 0x104);
+    }
+
+    #[test]
+    fn backtrace() {
+        let program = asm::assemble(
+            "
+            main:   call outer
+                    j done
+            outer:  mv s0, ra
+                    call inner
+                    mv ra, s0
+                    ret
+            inner:  nop
+                    ret
+            done:
+            ",
+            0,
+        )
+        .unwrap();
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.load_program(&program);
+        processor.track_call_stack(true);
+
+        while processor.pc != 32 {
+            processor.step::<Instruction>();
+        }
+        assert_eq!(processor.backtrace(), vec![24, 8]);
+
+        processor.step::<Instruction>();
+        processor.step::<Instruction>();
+        assert_eq!(processor.pc, 24);
+        assert_eq!(processor.backtrace(), vec![8]);
+
+        processor.run::<Instruction>();
+        assert_eq!(processor.pc, 40);
+        assert_eq!(processor.backtrace(), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn with_isa_gates_extensions() {
+        let mul = Instruction::MUL {
+            rd: Register::A0,
+            rs1: Register::A1,
+            rs2: Register::A2,
+        };
+
+        let mut processor = Processor::<i32, CSR32>::with_isa("rv32i").unwrap();
+        processor.store_instructions(0, [mul]);
+        processor.csrs.read_write(MTVEC, 64);
+        processor.step::<Instruction>();
+        assert_eq!(processor.pc, 64);
+        assert_eq!(processor.csrs.read(MCAUSE), 2);
+        assert_eq!(processor.csrs.read(MTVAL), mul.encode() as i32);
+
+        let mut processor = Processor::<i32, CSR32>::with_isa("rv32im").unwrap();
+        processor.store_instructions(0, [mul]);
+        processor.registers[Register::A1] = 6;
+        processor.registers[Register::A2] = 7;
+        processor.step::<Instruction>();
+        assert_eq!(processor.pc, 4);
+        assert_eq!(processor.registers[Register::A0], 42);
+    }
+
+    #[test]
+    fn run_on_embedded_base() {
+        let mut processor = Processor::<i32, CSR32>::with_isa("rv32e").unwrap();
+        let addi = Instruction::ADDI {
+            rd: Register::A0,
+            rs1: Register::ZERO,
+            imm: 5,
+        };
+        processor.store_instructions(0, [addi]);
+
+        assert_eq!(
+            processor.disassemble_text::<Instruction>(0, 4),
+            "          0: 00500513  addi a0,zero,5\n"
+        );
+        processor.run::<Instruction>();
+
+        assert_eq!(processor.pc, 4);
+        assert_eq!(processor.registers[Register::A0], 5);
+    }
+
+    #[test]
+    fn trap_handler_emulates_division_without_m() {
+        let mut processor = Processor::<i32, CSR32>::with_isa("rv32i").unwrap();
+        processor.set_ebreak_policy(EbreakPolicy::Halt);
+        processor.store_instructions(
+            0,
+            [
+                Instruction::DIVU {
+                    rd: Register::A0,
+                    rs1: Register::A1,
+                    rs2: Register::A2,
+                },
+                Instruction::EBREAK,
+            ],
+        );
+        // A handler emulating `divu a0, a1, a2` by repeated subtraction,
+        // before returning to the instruction after the `divu`.
+        processor.store_instructions(
+            64,
+            [
+                Instruction::ADDI {
+                    rd: Register::A0,
+                    rs1: Register::ZERO,
+                    imm: 0,
+                },
+                Instruction::BLTU {
+                    rs1: Register::A1,
+                    rs2: Register::A2,
+                    offset: 16,
+                },
+                Instruction::SUB {
+                    rd: Register::A1,
+                    rs1: Register::A1,
+                    rs2: Register::A2,
+                },
+                Instruction::ADDI {
+                    rd: Register::A0,
+                    rs1: Register::A0,
+                    imm: 1,
+                },
+                Instruction::JAL {
+                    rd: Register::ZERO,
+                    offset: -12,
+                },
+                Instruction::CSRRS {
+                    rd: Register::T0,
+                    rs1: Register::ZERO,
+                    csr: MEPC,
+                },
+                Instruction::ADDI {
+                    rd: Register::T0,
+                    rs1: Register::T0,
+                    imm: 4,
+                },
+                Instruction::CSRRW {
+                    rd: Register::ZERO,
+                    rs1: Register::T0,
+                    csr: MEPC,
+                },
+                Instruction::MRET,
+            ],
+        );
+        processor.csrs.read_write(MTVEC, 64);
+        processor.registers[Register::A1] = 23;
+        processor.registers[Register::A2] = 7;
+
+        assert_eq!(processor.run::<Instruction>(), ExecutionResult::Halt);
+        assert_eq!(processor.pc, 4);
+        assert_eq!(processor.registers[Register::A0], 3);
+        assert_eq!(processor.csrs.read(MCAUSE), 2);
+    }
+
+    #[test]
+    fn with_isa_compressed() {
+        let swsp = Instruction::C_SWSP {
+            rs2: Register::RA,
+            offset: 4,
+        };
+        let lwsp = Instruction::C_LWSP {
+            rd: Register::A0,
+            offset: 4,
+        };
+        let mut processor = Processor::<i32, CSR32>::with_isa("rv32ic").unwrap();
+        processor
+            .memory
+            .store_half(0, swsp.encode() as i16)
+            .unwrap();
+        processor
+            .memory
+            .store_half(2, lwsp.encode() as i16)
+            .unwrap();
+        // Only the first parcel of a compressed instruction is fetched.
+        processor.memory.protect(4..8, Protection::ReadWrite);
+        processor.registers[Register::SP] = 64;
+        processor.registers[Register::RA] = 42;
+
+        processor.step::<Instruction>();
+        processor.step::<Instruction>();
+
+        assert_eq!(processor.pc, 4);
+        assert_eq!(processor.registers[Register::A0], 42);
+        assert_eq!(processor.memory.load_word(68), 42);
+        assert_eq!(processor.last_instruction::<Instruction>(), Some(lwsp));
+
+        // Without the C extension, compressed instructions are illegal.
+        let mut processor = Processor::<i32, CSR32>::with_isa("rv32i").unwrap();
+        processor
+            .memory
+            .store_half(0, swsp.encode() as i16)
+            .unwrap();
+        assert!(matches!(
+            processor.step::<Instruction>(),
+            ExecutionResult::Halt
+        ));
+    }
+
+    #[test]
+    fn store_compressed_instructions() {
+        let mut processor = Processor::<i32, CSR32>::with_isa("rv32ic").unwrap();
+        processor.store_instructions(
+            0,
+            [
+                Instruction::ADDI {
+                    rd: Register::RA,
+                    rs1: Register::ZERO,
+                    imm: 42,
+                },
+                Instruction::C_SWSP {
+                    rs2: Register::RA,
+                    offset: 4,
+                },
+                Instruction::C_LWSP {
+                    rd: Register::A0,
+                    offset: 4,
+                },
+                Instruction::ADDI {
+                    rd: Register::A0,
+                    rs1: Register::A0,
+                    imm: 1,
+                },
+            ],
+        );
+        processor.registers[Register::SP] = 64;
+
+        assert_eq!(
+            processor
+                .disassemble_text::<Instruction>(0, 12)
+                .lines()
+                .count(),
+            4
+        );
+        for _ in 0..4 {
+            processor.step::<Instruction>();
+        }
+
+        assert_eq!(processor.pc, 12);
+        assert_eq!(processor.registers[Register::A0], 43);
+    }
+
+    #[test]
+    fn with_isa_errors() {
+        assert_eq!(
+            Processor::<i32, CSR32>::with_isa("rv64gc").unwrap_err(),
+            IsaError::XlenMismatch {
+                expected: 32,
+                found: 64
+            }
+        );
+        assert_eq!(
+            Processor::<i32, CSR32>::with_isa("rv32xyz").unwrap_err(),
+            IsaError::InvalidBase(Some('x'))
+        );
+    }
+
+    #[test]
+    fn default_isa_matches_register_width() {
+        assert_eq!(Rv32Processor::default().isa(), "rv32im".parse().unwrap());
+        assert_eq!(Rv64Processor::default().isa(), "rv64im".parse().unwrap());
+    }
+
+    #[test]
+    fn fuzz_step_never_panics() {
+        const OPCODES: [u32; 10] = [
+            0b_0110111, 0b_0010111, 0b_0010011, 0b_0110011, 0b_0000011, 0b_0100011, 0b_1110011,
+            0b_1101111, 0b_1100111, 0b_1100011,
+        ];
+        const VALUES: [i32; 6] = [0, 1, -1, 2047, i32::MIN, i32::MAX];
+
+        // A xorshift generator, for a reproducible sequence of words.
+        let mut state = 0x2545_f491_u32;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        };
+        for _ in 0..20_000 {
+            let mut processor = Processor::<i32, CSR32>::with_isa("rv32imc").unwrap();
+            processor
+                .memory
+                .protect(4096..usize::MAX, Protection::NoAccess);
+            for index in 1..32 {
+                processor.registers[Register::const_from(index)] =
+                    VALUES[next() as usize % VALUES.len()];
+            }
+            processor.pc = [0, i32::MAX - 1, i32::MIN][next() as usize % 3];
+            let word = match next() % 4 {
+                0 => next(),
+                _ => next() & !0b_1111111 | OPCODES[next() as usize % OPCODES.len()],
+            };
+
+            let _ = processor.fuzz_step::<Instruction>(word);
+        }
+    }
+
+    #[test]
+    fn fuzz_step_rejects_reserved_shift() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        let word = Instruction::SLLI {
+            rd: Register::A0,
+            rs1: Register::A0,
+            shamt: 32,
+        }
+        .encode();
+
+        assert_eq!(
+            processor.fuzz_step::<Instruction>(word),
+            Err(Exception::UnimplementedInstruction(word))
+        );
+    }
+
+    #[test]
+    fn into_parts_round_trips() {
+        let processor: Processor<i32, CSR32> = processor_state!(
+            registers: {a0: 1, t6: -1}, memory: {64: 42}, csr: {0x300: 8}, pc: 12
+        );
+        let (registers, pc, csrs, mut memory) = processor.into_parts();
+
+        assert_eq!(pc, 12);
+        assert_eq!(memory.load_word(64), 42);
+        assert_eq!(
+            Processor::from_parts(registers, pc, csrs, memory),
+            processor_state!(
+                registers: {a0: 1, t6: -1}, memory: {64: 42}, csr: {0x300: 8}, pc: 12
+            )
+        );
+    }
+
+    /// The [Instruction]s decoded for an RV64 processor, only fetched and not
+    /// executed.
+    #[derive(Debug, PartialEq)]
+    struct Rv64Instruction(Instruction);
+
+    impl InstructionSet for Rv64Instruction {
+        type RegisterType = i64;
+        type CSRType = CSR64;
+
+        fn decode(raw_instruction: u32) -> Result<Self, Exception> {
+            Instruction::try_from(raw_instruction).map(Self)
+        }
+
+        fn decode_for(raw_instruction: u32, isa: &Isa) -> Result<Self, Exception> {
+            Instruction::decode_for(raw_instruction, isa).map(Self)
+        }
+
+        fn encode(self) -> u32 {
+            self.0.encode()
+        }
+
+        fn execute<M: MemoryBus>(
+            self,
+            _processor: &mut Processor<i64, CSR64, M>,
+        ) -> Result<(), Exception> {
+            Ok(())
+        }
+
+        fn instruction_size(&self) -> i64 {
+            self.0.instruction_size().into()
+        }
+
+        fn extension(&self) -> Extension {
+            self.0.extension()
+        }
+    }
+
+    #[test]
+    fn translate_keeps_wide_addresses() {
+        let mut processor = Rv64Processor::with_isa("rv64i").unwrap();
+        assert_eq!(
+            processor.translate(0x1_0000_1234, Access::Load),
+            Ok(0x1_0000_1234)
+        );
+
+        // Sv32 is not used by a 64-bit processor.
+        processor.privilege = Privilege::Supervisor;
+        processor.csrs.read_write(SATP, 1 << 31);
+        assert_eq!(
+            processor.translate(0x1_0000_1234, Access::Fetch),
+            Ok(0x1_0000_1234)
+        );
+    }
+
+    #[test]
+    fn fetch_c_jal_on_rv64() {
+        let mut processor = Rv64Processor::with_isa("rv64ic").unwrap();
+        processor.memory.write(0, &0x2001_u16.to_le_bytes());
+        processor.csrs.read_write(MTVEC, 64);
+
+        assert_eq!(
+            processor.fetch::<Rv64Instruction>(),
+            Err(Exception::UnimplementedInstruction(0x2001))
+        );
+        assert_eq!(
+            processor.step::<Rv64Instruction>(),
+            ExecutionResult::Continue
+        );
+        assert_eq!(processor.pc, 64);
+        assert_eq!(processor.csrs.read(MCAUSE), 2);
+    }
+
+    #[test]
+    fn fetch_from_poisoned_memory() {
+        let mut processor = Processor::<i32, CSR32>::from_parts(
+            Registers::default(),
+            0,
+            CSR32::default(),
+            Memory::with_fill(0xff),
+        );
+        processor.store_instructions(0, Instruction::NOP);
+
+        assert!(matches!(
+            processor.step::<Instruction>(),
+            ExecutionResult::Continue
+        ));
+        // An all ones instruction is illegal.
+        assert_eq!(
+            processor.fetch::<Instruction>(),
+            Err(Exception::UnimplementedInstruction(0xffff_ffff))
+        );
+        assert!(matches!(
+            processor.step::<Instruction>(),
+            ExecutionResult::Halt
+        ));
+        assert_eq!(processor.pc, 4);
+    }
+
+    #[test]
+    fn stack_top() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.set_stack_top(0x1008);
+        assert_eq!(processor.register(Register::SP), 0x1000);
+
+        // A function prologue.
+        processor.set_register(Register::RA, 0x42);
+        processor.store_instructions(
+            0,
+            [
+                Instruction::ADDI {
+                    rd: Register::SP,
+                    rs1: Register::SP,
+                    imm: -16,
+                },
+                Instruction::SW {
+                    rs1: Register::SP,
+                    rs2: Register::RA,
+                    offset: 12,
+                },
+            ],
+        );
+        processor.step::<Instruction>();
+        processor.step::<Instruction>();
+        assert_eq!(processor.pc, 8);
+        assert_eq!(processor.register(Register::SP), 0xff0);
+        assert_eq!(processor.memory.load_word(0xffc), 0x42);
+    }
+
+    #[test]
+    fn fetch_at_half_word_alignment() {
+        let mut processor = Processor::<i32, CSR32>::with_isa("rv32imc").unwrap();
+        let addi = Instruction::ADDI {
+            rd: Register::A0,
+            rs1: Register::A0,
+            imm: 42,
+        };
+        processor.store_words(0, &[addi.encode() << 16, addi.encode() >> 16]);
+        processor.pc = 2;
+
+        assert_eq!(processor.fetch::<Instruction>(), Ok(addi));
+    }
+
+    #[test]
+    fn fetch_across_page_boundary() {
+        let mut processor = Processor::<i32, CSR32>::with_isa("rv32imc").unwrap();
+        let addi = Instruction::ADDI {
+            rd: Register::A0,
+            rs1: Register::A0,
+            imm: 42,
+        };
+        // Map the virtual pages 0x0040_0000 and 0x0040_1000 to the physical
+        // pages 5 and 3, with the root page table at page 1.
+        let pte = |ppn: u32, flags: u32| ((ppn << 10) | flags | 1) as i32;
+        processor.memory.store_word(0x1004, pte(2, 0)).unwrap();
+        processor.memory.store_word(0x2000, pte(5, 0b1000)).unwrap();
+        processor.memory.store_word(0x2004, pte(3, 0b1000)).unwrap();
+        processor
+            .memory
+            .store_half(0x5ffe, addi.encode() as i16)
+            .unwrap();
+        processor
+            .memory
+            .store_half(0x3000, (addi.encode() >> 16) as i16)
+            .unwrap();
+        processor.csrs.read_write(SATP, (1 << 31) | 1);
+        processor.privilege = Privilege::Supervisor;
+        processor.pc = 0x0040_0ffe;
+
+        assert_eq!(processor.fetch::<Instruction>(), Ok(addi));
+    }
+
+    #[test]
+    fn read_and_write_csr() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        assert_eq!(processor.write_csr(MTVEC, 0x100), Ok(()));
+        assert_eq!(processor.read_csr(MTVEC), Ok(0x100));
+        assert_eq!(
+            processor.write_csr(0x1000, 1),
+            Err(Exception::UnimplementedInstruction(0))
+        );
+        assert_eq!(
+            processor.read_csr(u16::MAX),
+            Err(Exception::UnimplementedInstruction(0))
+        );
+    }
+
+    #[test]
+    fn backtrace_disabled() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(0, Instruction::JAL(8));
+
+        processor.step::<Instruction>();
+
+        assert_eq!(processor.registers[Register::RA], 4);
+        assert_eq!(processor.backtrace(), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn last_instruction() {
+        let add = Instruction::ADD {
+            rd: Register::A2,
+            rs1: Register::A1,
+            rs2: Register::A0,
+        };
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(8, [add]);
+        processor.pc = 8;
+        assert_eq!(processor.last_instruction::<Instruction>(), None);
+
+        processor.step::<Instruction>();
+
+        assert_eq!(processor.last_instruction::<Instruction>(), Some(add));
+    }
+
+    #[test]
+    fn trap_to_machine_handler() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.memory.store_word(8, -1).unwrap();
+        processor.csrs.read_write(MTVEC, 64);
+        processor.csrs.read_write(MSTATUS, MStatus::<CSR32>::MIE);
+        processor.pc = 8;
+
+        assert!(matches!(
+            processor.step::<Instruction>(),
+            ExecutionResult::Continue
+        ));
+
+        assert_eq!(processor.pc, 64);
+        assert_eq!(processor.privilege, Privilege::Machine);
+        assert_eq!(processor.csrs.read(MEPC), 8);
+        assert_eq!(processor.csrs.read(MCAUSE), 2);
+        assert_eq!(processor.csrs.read(MTVAL), -1);
+        let mstatus = MStatus::new(&processor.csrs);
+        assert!(!mstatus.mie());
+        assert!(mstatus.mpie());
+        assert_eq!(mstatus.mpp(), Privilege::Machine as u8);
+    }
+
+    #[test]
+    fn step_and_report() {
+        let mut processor = processor_state!(registers: {a0: 5}, pc: 8);
+        processor.store_instructions(
+            8,
+            [
+                Instruction::ADDI {
+                    rd: Register::A1,
+                    rs1: Register::A0,
+                    imm: 37,
+                },
+                Instruction::SW {
+                    rs1: Register::ZERO,
+                    rs2: Register::A1,
+                    offset: 64,
+                },
+            ],
+        );
+
+        assert_eq!(
+            processor.step_and_report::<Instruction>(),
+            StepReport {
+                address: 8,
+                instruction: Some("addi a1,a0,37".to_owned()),
+                registers: vec![(Register::A1, 0, 42)],
+                store: None,
+                result: ExecutionResult::Continue,
+            }
+        );
+        assert_eq!(
+            processor.step_and_report::<Instruction>(),
+            StepReport {
+                address: 12,
+                instruction: Some("sw a1,64(zero)".to_owned()),
+                registers: vec![],
+                store: Some(MemAccess {
+                    address: 64,
+                    width: 4,
+                    access: Access::Store,
+                    value: 42,
+                }),
+                result: ExecutionResult::Continue,
+            }
+        );
+    }
+
+    #[test]
+    fn step_and_report_illegal_instruction() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_words(0, &[0xffff_ffff]);
+
+        assert_eq!(
+            processor.step_and_report::<Instruction>(),
+            StepReport {
+                address: 0,
+                instruction: None,
+                registers: vec![],
+                store: None,
+                result: ExecutionResult::Halt,
+            }
+        );
+    }
+
+    #[test]
+    fn step_n() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(
+            0,
+            instructions![
+                Instruction::LI(Register::A0, 21),
+                Instruction::LI(Register::A1, 21),
+                Instruction::ADD {
+                    rd: Register::A2,
+                    rs1: Register::A1,
+                    rs2: Register::A0,
+                },
+            ],
+        );
+
+        let reports = processor.step_n::<Instruction>(3);
+
+        assert_eq!(
+            reports
+                .iter()
+                .map(|report| (report.address, report.instruction.as_deref().unwrap()))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, "addi a0,zero,21"),
+                (4, "addi a1,zero,21"),
+                (8, "add a2,a1,a0")
+            ]
+        );
+        assert_eq!(processor.pc, 12);
+        assert_eq!(processor.registers[Register::A2], 42);
+    }
+
+    #[test]
+    fn step_n_stops_on_halt() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(0, Instruction::NOP);
+
+        let reports = processor.step_n::<Instruction>(5);
+
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[1].result, ExecutionResult::Halt);
+        assert!(processor.step_n::<Instruction>(0).is_empty());
+    }
+
+    #[test]
+    fn ecall_exit() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(
+            0,
+            instructions![
+                Instruction::LI(Register::A0, 7),
+                Instruction::LI(Register::A7, 93),
+                Instruction::ECALL,
+                Instruction::LI(Register::A0, 0),
+            ],
+        );
+
+        assert!(matches!(
+            processor.run::<Instruction>(),
+            ExecutionResult::Exited(7)
+        ));
+        assert_eq!(processor.pc, 8);
+        assert_eq!(processor.registers[Register::A0], 7);
+    }
+
+    #[test]
+    fn ecall_traps_to_handler() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(
+            0,
+            instructions![Instruction::LI(Register::A7, 93), Instruction::ECALL,],
+        );
+        processor.csrs.read_write(MTVEC, 64);
+
+        processor.step::<Instruction>();
+        assert!(matches!(
+            processor.step::<Instruction>(),
+            ExecutionResult::Continue
+        ));
+
+        assert_eq!(processor.pc, 64);
+        assert_eq!(processor.csrs.read(MEPC), 4);
+        assert_eq!(processor.csrs.read(MCAUSE), 11);
+    }
+
+    #[test]
+    fn patch_instruction() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        let addi = Instruction::ADDI {
+            rd: Register::A0,
+            rs1: Register::A0,
+            imm: 1,
+        };
+        processor.store_instructions(0, [addi, addi, addi]);
+        processor.memory.protect(0..12, Protection::ReadExecute);
+
+        // Set a breakpoint on the second instruction.
+        assert_eq!(
+            processor.patch_instruction(4, Instruction::EBREAK),
+            Ok(addi)
+        );
+        assert!(matches!(
+            processor.run::<Instruction>(),
+            ExecutionResult::Halt
+        ));
+        assert_eq!(processor.pc, 4);
+        assert_eq!(processor.registers[Register::A0], 1);
+
+        // Restore it and continue.
+        assert_eq!(
+            processor.patch_instruction(4, addi),
+            Ok(Instruction::EBREAK)
+        );
+        processor.step::<Instruction>();
+        processor.step::<Instruction>();
+        assert_eq!(processor.pc, 12);
+        assert_eq!(processor.registers[Register::A0], 3);
+
+        processor.memory.write(16, &[0xff; 4]);
+        assert_eq!(
+            processor.patch_instruction(16, Instruction::EBREAK),
+            Err(Exception::UnimplementedInstruction(0xffff_ffff))
+        );
+        assert_eq!(processor.memory.load_word(16), -1);
+    }
+
+    #[test]
+    fn patch_instruction_of_disabled_extension() {
+        let mut processor = Processor::<i32, CSR32>::with_isa("rv32i").unwrap();
+        let mul = Instruction::MUL {
+            rd: Register::A0,
+            rs1: Register::A0,
+            rs2: Register::A1,
+        };
+        processor.store_instructions(0, [mul]);
+
+        assert_eq!(
+            processor.patch_instruction(0, Instruction::EBREAK),
+            Err(Exception::UnimplementedInstruction(mul.encode()))
+        );
+        assert_eq!(processor.memory.load_word(0), mul.encode() as i32);
+    }
+
+    #[test]
+    fn run_with_timeout() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(
+            0,
+            [Instruction::JAL {
+                rd: Register::ZERO,
+                offset: 0,
+            }],
+        );
+
+        assert_eq!(
+            processor.run_with_timeout::<Instruction>(Duration::from_millis(1)),
+            ExecutionResult::TimedOut
+        );
+        assert_eq!(processor.pc, 0);
+
+        // A programme which halts in time is unaffected.
+        processor.store_instructions(0, [Instruction::EBREAK]);
+        assert_eq!(
+            processor.run_with_timeout::<Instruction>(Duration::from_secs(60)),
+            ExecutionResult::Halt
+        );
+    }
+
+    /// A programme which recurses 32 calls deep, using 16 bytes of stack for
+    /// each call.
+    fn recursion_programme() -> Processor<i32, CSR32> {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(
+            0,
+            instructions![
+                Instruction::LI(Register::A0, 32),
+                [Instruction::JAL {
+                    rd: Register::RA,
+                    offset: 8
+                }],
+                [Instruction::EBREAK],
+                // The recursive function at address 12.
+                [
+                    Instruction::ADDI {
+                        rd: Register::SP,
+                        rs1: Register::SP,
+                        imm: -16
+                    },
+                    Instruction::SW {
+                        rs1: Register::SP,
+                        rs2: Register::RA,
+                        offset: 12
+                    },
+                    Instruction::ADDI {
+                        rd: Register::A0,
+                        rs1: Register::A0,
+                        imm: -1
+                    },
+                    Instruction::BEQ {
+                        rs1: Register::A0,
+                        rs2: Register::ZERO,
+                        offset: 8
+                    },
+                    Instruction::JAL {
+                        rd: Register::RA,
+                        offset: -16
+                    },
+                    Instruction::LW {
+                        rd: Register::RA,
+                        rs1: Register::SP,
+                        offset: 12
+                    },
+                    Instruction::ADDI {
+                        rd: Register::SP,
+                        rs1: Register::SP,
+                        imm: 16
+                    },
+                    Instruction::JALR {
+                        rd: Register::ZERO,
+                        rs1: Register::RA,
+                        offset: 0
+                    },
+                ],
+            ],
+        );
+        processor.set_stack_top(0x1000);
+        processor
+    }
+
+    #[test]
+    fn stack_guard() {
+        let mut processor = recursion_programme();
+        processor.set_stack_guard(Some(0xF00));
+
+        assert_eq!(
+            processor.run::<Instruction>(),
+            ExecutionResult::StackOverflow
+        );
+        // The prologue of the 17th call moved the stack pointer past the guard.
+        assert_eq!(processor.registers[Register::SP], 0xEF0);
+        assert_eq!(processor.registers[Register::A0], 16);
+        assert_eq!(processor.pc, 16);
+
+        // Recursion which stays within the guard is unaffected.
+        let mut processor = recursion_programme();
+        processor.set_stack_guard(Some(0xE00));
+
+        assert_eq!(processor.run::<Instruction>(), ExecutionResult::Halt);
+        assert_eq!(processor.registers[Register::SP], 0x1000);
+        assert_eq!(processor.registers[Register::A0], 0);
+    }
+
+    #[test]
+    fn checkpoint_and_restore() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(
+            0,
+            [
+                Instruction::ADDI {
+                    rd: Register::A0,
+                    rs1: Register::ZERO,
+                    imm: 1,
+                },
+                Instruction::SW {
+                    rs1: Register::ZERO,
+                    rs2: Register::A0,
+                    offset: 64,
+                },
+                Instruction::CSRRW {
+                    rd: Register::ZERO,
+                    rs1: Register::A0,
+                    csr: SSCRATCH,
+                },
+                Instruction::ADDI {
+                    rd: Register::A0,
+                    rs1: Register::A0,
+                    imm: 1,
+                },
+                Instruction::SW {
+                    rs1: Register::ZERO,
+                    rs2: Register::A0,
+                    offset: 64,
+                },
+            ],
+        );
+        processor.step::<Instruction>();
+        processor.step::<Instruction>();
+        let checkpoint = processor.checkpoint();
+        let expected = Processor::from_parts(
+            processor.registers.clone(),
+            processor.pc,
+            processor.csrs.clone(),
+            processor.memory.clone(),
+        );
+
+        processor.run_for::<Instruction>(3);
+        assert!(!processor.diff(&expected).is_empty());
+
+        processor.restore(checkpoint);
+        assert_eq!(processor, expected);
+        assert_eq!(processor.pc, 8);
+        assert_eq!(processor.memory.load_word(64), 1);
+    }
+
+    #[test]
+    fn summary() {
+        let mut processor = Processor::<i32, CSR32> {
+            pc: 0x1004,
+            ..Default::default()
+        };
+        processor.registers[Register::A0] = 42;
+        processor.registers[Register::A1] = 21;
+        processor.registers[Register::SP] = 0x7ff0;
+        processor.registers[Register::RA] = 0x100;
+        processor.registers[Register::T0] = -1;
+
+        assert_eq!(processor.to_string(), "pc=0x1004 a0=42 a1=21 sp=0x7ff0");
+        assert_eq!(
+            processor.summary(&[Register::T0, Register::RA]),
+            "pc=0x1004 t0=-1 ra=0x100"
+        );
+    }
+
+    #[test]
+    fn return_value() {
+        // The sum of 1 to 10.
+        let processor = Processor::<i32, CSR32>::run_program(instructions![
+            Instruction::LI(Register::T0, 10),
+            Instruction::ADD {
+                rd: Register::A0,
+                rs1: Register::A0,
+                rs2: Register::T0
+            },
+            Instruction::ADDI {
+                rd: Register::T0,
+                rs1: Register::T0,
+                imm: -1
+            },
+            Instruction::BNE {
+                rs1: Register::T0,
+                rs2: Register::ZERO,
+                offset: -8
+            },
+        ]);
+
+        assert_eq!(processor.return_value(), 55);
+    }
+
+    #[test]
+    fn diff() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(
+            0,
+            [Instruction::ADDI {
+                rd: Register::A0,
+                rs1: Register::ZERO,
+                imm: 1,
+            }],
+        );
+        let mut other = Processor::<i32, CSR32>::default();
+        other.store_instructions(
+            0,
+            [Instruction::ADDI {
+                rd: Register::A0,
+                rs1: Register::ZERO,
+                imm: 1,
+            }],
+        );
+        assert!(processor.diff(&other).is_empty());
+
+        processor.step::<Instruction>();
+        other.registers[Register::A0] = 2;
+        other.pc = 4;
+        other.csrs.read_write(SSCRATCH, 7);
+        other.memory.store_byte(64, 3).unwrap();
+
+        let diff = processor.diff(&other);
+        assert_eq!(
+            diff,
+            StateDiff {
+                pc: None,
+                privilege: None,
+                registers: vec![(Register::A0, 1, 2)],
+                csrs: vec![(SSCRATCH, 0, 7)],
+                memory: vec![(64, 0, 3)],
+            }
+        );
+        assert_eq!(
+            diff.to_string(),
+            "a0: 1 != 2\nsscratch: 0 != 7\n[0x40]: 0x00 != 0x03"
+        );
+    }
+
+    #[test]
+    fn to_json() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(
+            0,
+            [
+                Instruction::ADDI {
+                    rd: Register::A0,
+                    rs1: Register::ZERO,
+                    imm: -42,
+                },
+                Instruction::SW {
+                    rs1: Register::ZERO,
+                    rs2: Register::A0,
+                    offset: 64,
+                },
+            ],
+        );
+        processor.csrs.read_write(SSCRATCH, 0x100);
+        processor.run::<Instruction>();
+
+        assert_eq!(
+            processor.to_json(),
+            concat!(
+                r#"{"pc":8,"privilege":"Machine","registers":{"a0":-42},"#,
+                r#""csrs":{"sscratch":256},"#,
+                r#""memory":{"0x0":"130560fd2320a004","0x40":"d6ffffff"}}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn run_with_tick() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_words(0, &[0x0000_0013; 10]);
+
+        let mut ticks = Vec::new();
+        let result = processor.run_with_tick::<Instruction>(3, |p| ticks.push(p.pc));
+
+        assert!(matches!(result, ExecutionResult::Halt));
+        assert_eq!(ticks, vec![12, 24, 36]);
+    }
+
+    #[test]
+    fn reserve_memory() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.reserve_memory(0x1_0000);
+        assert_eq!(processor.memory.len(), 0x1_0000);
+
+        processor.store_instructions(
+            0,
+            [Instruction::LW {
+                rd: Register::A0,
+                rs1: Register::ZERO,
+                offset: 0x7FC,
+            }],
+        );
+        assert_eq!(processor.memory.len(), 0x1_0000);
+        assert!(matches!(
+            processor.step::<Instruction>(),
+            ExecutionResult::Continue
+        ));
+        assert_eq!(processor.memory.load_word(0xFFFC), 0);
+    }
+
+    #[test]
+    fn instruction_address_trigger() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(
+            0,
+            instructions![
+                Instruction::LI(Register::A0, 1),
+                Instruction::LI(Register::A0, 2),
+                Instruction::LI(Register::A0, 3),
+            ],
+        );
+        processor.csrs.read_write(MTVEC, 64);
+        processor.csrs.read_write(MSTATUS, MStatus::<CSR32>::MIE);
+        // An mcontrol trigger on execution in machine mode at address 8.
+        processor.csrs.read_write(TSELECT, 0);
+        processor
+            .csrs
+            .read_write(TDATA1, (MCONTROL << 28 | 1 << 6 | MCONTROL_EXECUTE) as i32);
+        processor.csrs.read_write(TDATA2, 8);
+
+        processor.step::<Instruction>();
+        processor.step::<Instruction>();
+        processor.step::<Instruction>();
+
+        assert_eq!(processor.pc, 64);
+        assert_eq!(processor.registers[Register::A0], 2);
+        assert_eq!(processor.csrs.read(MEPC), 8);
+        assert_eq!(processor.csrs.read(MCAUSE), 3);
+    }
+
+    #[test]
+    fn trigger_handler_returns_past_trigger() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(
+            0,
+            instructions![
+                Instruction::LI(Register::A0, 1),
+                Instruction::LI(Register::A0, 2),
+                Instruction::LI(Register::A0, 3),
+            ],
+        );
+        // The handler returns with `MIE` clear, so the trigger does not fire
+        // again.
+        processor.store_instructions(
+            64,
+            instructions![
+                Instruction::LI(Register::T0, MStatus::<CSR32>::MPIE),
+                Instruction::CSRRC {
+                    rd: Register::ZERO,
+                    rs1: Register::T0,
+                    csr: MSTATUS,
+                },
+                Instruction::MRET,
+            ],
+        );
+        processor.csrs.read_write(MTVEC, 64);
+        processor.csrs.read_write(MSTATUS, MStatus::<CSR32>::MIE);
+        processor
+            .csrs
+            .read_write(TDATA1, (MCONTROL << 28 | 1 << 6 | MCONTROL_EXECUTE) as i32);
+        processor.csrs.read_write(TDATA2, 8);
+
+        // Two instructions, the trap, three in the handler, and the third.
+        for _ in 0..7 {
+            processor.step::<Instruction>();
+        }
+
+        assert_eq!(processor.pc, 12);
+        assert_eq!(processor.registers[Register::A0], 3);
+    }
+
+    #[test]
+    fn trigger_suppressed_in_machine_mode_without_mie() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(0, instructions![Instruction::LI(Register::A0, 1)]);
+        processor.csrs.read_write(MTVEC, 64);
+        processor
+            .csrs
+            .read_write(TDATA1, (MCONTROL << 28 | 1 << 6 | MCONTROL_EXECUTE) as i32);
+
+        processor.step::<Instruction>();
+
+        assert_eq!(processor.pc, 4);
+        assert_eq!(processor.registers[Register::A0], 1);
+    }
+
+    #[test]
+    fn tselect_only_selects_trigger_zero() {
+        let processor = Processor::<i32, CSR32>::default();
+        assert_eq!(processor.csrs.read_write(TSELECT, 1), 0);
+        assert_eq!(processor.csrs.read(TSELECT), 0);
+    }
+
+    #[test]
+    fn ecall_without_exit_request_halts() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(0, [Instruction::ECALL]);
+
+        assert!(matches!(
+            processor.run::<Instruction>(),
+            ExecutionResult::Halt
+        ));
+    }
+
+    /// A programme which increments `a0` either side of an `ebreak`.
+    fn ebreak_programme(policy: EbreakPolicy) -> Processor<i32, CSR32> {
+        let increment = Instruction::ADDI {
+            rd: Register::A0,
+            rs1: Register::A0,
+            imm: 1,
+        };
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.set_ebreak_policy(policy);
+        processor.store_instructions(0, [increment, Instruction::EBREAK, increment]);
+        processor
+    }
+
+    #[test]
+    fn ebreak_trap() {
+        let mut processor = ebreak_programme(EbreakPolicy::Trap);
+        processor.csrs.read_write(MTVEC, 64);
+
+        processor.step::<Instruction>();
+        processor.step::<Instruction>();
+
+        assert_eq!(processor.pc, 64);
+        assert_eq!(processor.csrs.read(MEPC), 4);
+        assert_eq!(processor.csrs.read(MCAUSE), 3);
+        assert_eq!(processor.registers[Register::A0], 1);
+    }
+
+    #[test]
+    fn ebreak_halt() {
+        let mut processor = ebreak_programme(EbreakPolicy::Halt);
+        processor.csrs.read_write(MTVEC, 64);
+
+        processor.run::<Instruction>();
+
+        assert_eq!(processor.pc, 4);
+        assert_eq!(processor.csrs.read(MCAUSE), 0);
+        assert_eq!(processor.registers[Register::A0], 1);
+    }
+
+    #[test]
+    fn ebreak_ignore() {
+        let mut processor = ebreak_programme(EbreakPolicy::Ignore);
+
+        processor.run::<Instruction>();
+
+        assert_eq!(processor.pc, 12);
+        assert_eq!(processor.registers[Register::A0], 2);
+    }
+
+    #[test]
+    fn halt_without_trap_handler() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.memory.store_word(0, -1).unwrap();
+
+        assert!(matches!(
+            processor.step::<Instruction>(),
+            ExecutionResult::Halt
+        ));
+        assert_eq!(processor.pc, 0);
+    }
+
+    #[test]
+    fn delegate_trap_to_supervisor() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.memory.store_word(8, -1).unwrap();
+        processor.csrs.read_write(MTVEC, 64);
+        processor.csrs.read_write(STVEC, 128);
+        processor.csrs.read_write(MEDELEG, 1 << 2);
+        processor.privilege = Privilege::User;
+        processor.pc = 8;
+
+        processor.step::<Instruction>();
+
+        assert_eq!(processor.pc, 128);
+        assert_eq!(processor.privilege, Privilege::Supervisor);
+        assert_eq!(processor.csrs.read(SEPC), 8);
+        assert_eq!(processor.csrs.read(SCAUSE), 2);
+        assert_eq!(processor.csrs.read(MEPC), 0);
+        assert_eq!(MStatus::new(&processor.csrs).spp(), Privilege::User as u8);
+    }
+
+    #[test]
+    fn machine_mode_traps_are_not_delegated() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.memory.store_word(8, -1).unwrap();
+        processor.csrs.read_write(MTVEC, 64);
+        processor.csrs.read_write(STVEC, 128);
+        processor.csrs.read_write(MEDELEG, 1 << 2);
+        processor.pc = 8;
+
+        processor.step::<Instruction>();
+
+        assert_eq!(processor.pc, 64);
+        assert_eq!(processor.privilege, Privilege::Machine);
+        assert_eq!(processor.csrs.read(MCAUSE), 2);
+    }
+
+    #[test]
+    fn trap_and_return() {
+        let mut processor = Processor::<i32, CSR32>::default();
+        processor.store_instructions(
+            0,
+            [Instruction::CSRRW {
+                rd: Register::ZERO,
+                rs1: Register::A0,
+                csr: MEPC,
+            }],
+        );
+        processor.store_instructions(4, [Instruction::MRET]);
+        processor.memory.store_word(64, -1).unwrap();
+        processor.registers[Register::A0] = 64;
+        processor.csrs.read_write(MTVEC, 128);
+        processor.store_instructions(128, [Instruction::MRET]);
+
+        // Return to user mode at 64, which then traps back to machine mode.
+        processor.step::<Instruction>();
+        processor.step::<Instruction>();
+        assert_eq!(processor.pc, 64);
+        assert_eq!(processor.privilege, Privilege::User);
+
+        processor.step::<Instruction>();
+        assert_eq!(processor.pc, 128);
+        assert_eq!(processor.privilege, Privilege::Machine);
+        assert_eq!(MStatus::new(&processor.csrs).mpp(), Privilege::User as u8);
+    }
 }
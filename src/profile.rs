@@ -0,0 +1,88 @@
+//! Profiling of the instructions executed by the processor.
+use std::collections::HashMap;
+use std::fmt::Display;
+
+/// A histogram of the number of times each instruction has been executed.
+///
+/// Instructions are grouped by their mnemonic, see
+/// [crate::instruction_set::InstructionSet::mnemonic].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct InstructionProfile {
+    /// The number of times each instruction has been executed.
+    counts: HashMap<&'static str, usize>,
+    /// The total number of instructions executed.
+    total: usize,
+}
+
+impl InstructionProfile {
+    /// Record a single execution of the instruction with the given `mnemonic`.
+    pub fn record(&mut self, mnemonic: &'static str) {
+        *self.counts.entry(mnemonic).or_default() += 1;
+        self.total += 1;
+    }
+
+    /// The number of times the instruction with the given `mnemonic` has been
+    /// executed.
+    pub fn count(&self, mnemonic: &str) -> usize {
+        self.counts.get(mnemonic).copied().unwrap_or_default()
+    }
+
+    /// The total number of instructions executed.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// The mnemonics and counts of the executed instructions, ordered from
+    /// most to least frequently executed.
+    pub fn by_frequency(&self) -> Vec<(&'static str, usize)> {
+        let mut counts: Vec<_> = self
+            .counts
+            .iter()
+            .map(|(mnemonic, count)| (*mnemonic, *count))
+            .collect();
+        counts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        counts
+    }
+}
+
+impl Display for InstructionProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (mnemonic, count) in self.by_frequency() {
+            writeln!(
+                f,
+                "{mnemonic:<8} {count:>8} {:>6.2}%",
+                100.0 * count as f64 / self.total as f64
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn display_sorted_by_frequency() {
+        let mut profile = InstructionProfile::default();
+        ["lw", "addi", "lw", "beq", "lw", "addi"]
+            .into_iter()
+            .for_each(|mnemonic| profile.record(mnemonic));
+
+        assert_eq!(
+            profile.to_string(),
+            "lw              3  50.00%\n\
+             addi            2  33.33%\n\
+             beq             1  16.67%\n"
+        );
+    }
+
+    #[test]
+    fn empty_profile() {
+        let profile = InstructionProfile::default();
+        assert_eq!(profile.total(), 0);
+        assert_eq!(profile.count("add"), 0);
+        assert_eq!(profile.to_string(), "");
+    }
+}
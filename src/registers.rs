@@ -45,231 +45,111 @@ use std::{
 /// | 29 | -   | x29      | t4       | temporary register 4                 | caller   |
 /// | 30 | -   | x30      | t5       | temporary register 5                 | caller   |
 /// | 31 | -   | x31      | t6       | temporary register 6                 | caller   |
-#[derive(Default, PartialEq, Eq)]
-pub(super) struct Registers<T> {
+#[derive(Default, Clone)]
+pub struct Registers<T> {
+    /// The values of the registers indexed by their number, for example
+    /// `values[10]` is `a0`.
+    ///
+    /// The value of the zero register, `x0`, is never written so it always
+    /// yields zero.
+    values: [T; 32],
+
     /// The zero register.
     ///
-    /// This register will always yield zero. Setting the destination register
-    /// of a specific instruction will discard the result.
+    /// Setting the destination register of a specific instruction to the zero
+    /// register writes to this, which discards the result.
     ///
     /// In particular instructions that usually trigger read read side effects
     /// should not be triggered if their destination register is the zero register.
-    ///
-    /// Also referred to as `x0`.
-    pub(super) zero: ZeroRegister<T>,
-
-    /// Return address register.
-    ///
-    /// Usually the value will be set by the caller.
-    ///
-    /// Also referred to as `x1`.
-    pub(super) ra: T,
-
-    /// Stack pointer register
-    ///
-    /// Usually the value will be set by the callee.
-    ///
-    /// Also referred to as `x2`.
-    pub(super) sp: T,
-
-    /// Global pointer register
-    ///
-    /// Also referred to as `x2`.
-    pub(super) gp: T,
-
-    /// Thread pointer register
-    ///
-    /// Also referred to as `x2`.
-    pub(super) tp: T,
-
-    /// Temporary register 0.
-    ///
-    /// Usually the value will be set by the caller.
-    ///
-    /// Also referred to as `x5`.
-    pub(super) t0: T,
-
-    /// Temporary register 1.
-    ///
-    /// Usually the value will be set by the caller.
-    ///
-    /// Also referred to as `x6`.
-    pub(super) t1: T,
-
-    /// Temporary register 2.
-    ///
-    /// Usually the value will be set by the caller.
-    ///
-    /// Also referred to as `x7`.
-    pub(super) t2: T,
-
-    /// Saved register 0.
-    ///
-    /// Usually the value will be set by the callee.
-    ///
-    /// Also referred to as `x8`.
-    pub(super) s0: T,
-
-    /// Saved register 1.
-    ///
-    /// Usually the value will be set by the callee.
-    ///
-    /// Also referred to as `x9`.
-    pub(super) s1: T,
-
-    /// Function argument register 0.
-    ///
-    /// Usually the value will be set by the caller.
-    ///
-    /// Also referred to as `x10`.
-    pub(super) a0: T,
-
-    /// Function argument register 1.
-    ///
-    /// Usually the value will be set by the caller.
-    ///
-    /// Also referred to as `x11`.
-    pub(super) a1: T,
-
-    /// Function argument register 2.
-    ///
-    /// Usually the value will be set by the caller.
-    ///
-    /// Also referred to as `x12`.
-    pub(super) a2: T,
-
-    /// Function argument register 3.
-    ///
-    /// Usually the value will be set by the caller.
-    ///
-    /// Also referred to as `x13`.
-    pub(super) a3: T,
-
-    /// Function argument register 4.
-    ///
-    /// Usually the value will be set by the caller.
-    ///
-    /// Also referred to as `x14`.
-    pub(super) a4: T,
-
-    /// Function argument register 5.
-    ///
-    /// Usually the value will be set by the caller.
-    ///
-    /// Also referred to as `x15`.
-    pub(super) a5: T,
-
-    /// Function argument register 6.
-    ///
-    /// Usually the value will be set by the caller.
-    ///
-    /// Also referred to as `x16`.
-    pub(super) a6: T,
-
-    /// Function argument register 7.
-    ///
-    /// Usually the value will be set by the caller.
-    ///
-    /// Also referred to as `x17`.
-    pub(super) a7: T,
-
-    /// Saved register 2.
-    ///
-    /// Usually the value will be set by the callee.
-    ///
-    /// Also referred to as `x18`.
-    pub(super) s2: T,
-
-    /// Saved register 3.
-    ///
-    /// Usually the value will be set by the callee.
-    ///
-    /// Also referred to as `x19`.
-    pub(super) s3: T,
-
-    /// Saved register 4.
-    ///
-    /// Usually the value will be set by the callee.
-    ///
-    /// Also referred to as `x20`.
-    pub(super) s4: T,
-
-    /// Saved register 5.
-    ///
-    /// Usually the value will be set by the callee.
-    ///
-    /// Also referred to as `x21`.
-    pub(super) s5: T,
-
-    /// Saved register 6.
-    ///
-    /// Usually the value will be set by the callee.
-    ///
-    /// Also referred to as `x22`.
-    pub(super) s6: T,
-
-    /// Saved register 7.
-    ///
-    /// Usually the value will be set by the callee.
-    ///
-    /// Also referred to as `x23`.
-    pub(super) s7: T,
-
-    /// Saved register 8.
-    ///
-    /// Usually the value will be set by the callee.
-    ///
-    /// Also referred to as `x24`.
-    pub(super) s8: T,
-
-    /// Saved register 9.
-    ///
-    /// Usually the value will be set by the callee.
-    ///
-    /// Also referred to as `x25`.
-    pub(super) s9: T,
+    zero: ZeroRegister<T>,
+}
 
-    /// Saved register 10.
-    ///
-    /// Usually the value will be set by the callee.
-    ///
-    /// Also referred to as `x26`.
-    pub(super) s10: T,
+impl<T> PartialEq for Registers<T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.values == other.values
+    }
+}
 
-    /// Saved register 11.
-    ///
-    /// Usually the value will be set by the callee.
-    ///
-    /// Also referred to as `x27`.
-    pub(super) s11: T,
+impl<T> Eq for Registers<T> where T: Eq {}
 
-    /// Temporary register 3.
-    ///
-    /// Usually the value will be set by the caller.
-    ///
-    /// Also referred to as `x28`.
-    pub(super) t3: T,
-
-    /// Temporary register 4.
-    ///
-    /// Usually the value will be set by the caller.
-    ///
-    /// Also referred to as `x29`.
-    pub(super) t4: T,
+impl<T> Registers<T>
+where
+    T: PartialEq + Copy,
+{
+    /// The registers whose values differ from `other`, each with its value in
+    /// `self` followed by its value in `other`.
+    ///
+    /// This is useful for reporting the changes made by a single step, such
+    /// as `a0: 5 -> 42`. The zero register is never reported.
+    pub fn diff(&self, other: &Registers<T>) -> Vec<(Register, T, T)> {
+        (1..32)
+            .filter(|&i| self[i] != other[i])
+            .map(|i| (Register::from(i), self[i], other[i]))
+            .collect()
+    }
+}
 
-    /// Temporary register 5.
-    ///
-    /// Usually the value will be set by the caller.
-    ///
-    /// Also referred to as `x30`.
-    pub(super) t5: T,
+/// Implements the named accessors of [Registers], a shared and a mutable
+/// reference to each register given by its ABI name and [Register].
+macro_rules! named_registers {
+    ($($name:ident, $name_mut:ident: $register:ident;)*) => {
+        impl<T> Registers<T> {
+            $(
+                #[doc = concat!("The `", stringify!($name), "` register, see [Register::", stringify!($register), "].")]
+                #[inline]
+                pub fn $name(&self) -> &T {
+                    &self[Register::$register]
+                }
+
+                #[doc = concat!("A mutable reference to the `", stringify!($name), "` register, see [Register::", stringify!($register), "].")]
+                #[inline]
+                pub fn $name_mut(&mut self) -> &mut T
+                where
+                    T: Default,
+                {
+                    &mut self[Register::$register]
+                }
+            )*
+        }
+    };
+}
 
-    /// Temporary register 6.
-    ///
-    /// Usually the value will be set by the caller.
-    ///
-    /// Also referred to as `x31`.
-    pub(super) t6: T,
+named_registers! {
+    zero, zero_mut: ZERO;
+    ra, ra_mut: RA;
+    sp, sp_mut: SP;
+    gp, gp_mut: GP;
+    tp, tp_mut: TP;
+    t0, t0_mut: T0;
+    t1, t1_mut: T1;
+    t2, t2_mut: T2;
+    s0, s0_mut: S0;
+    s1, s1_mut: S1;
+    a0, a0_mut: A0;
+    a1, a1_mut: A1;
+    a2, a2_mut: A2;
+    a3, a3_mut: A3;
+    a4, a4_mut: A4;
+    a5, a5_mut: A5;
+    a6, a6_mut: A6;
+    a7, a7_mut: A7;
+    s2, s2_mut: S2;
+    s3, s3_mut: S3;
+    s4, s4_mut: S4;
+    s5, s5_mut: S5;
+    s6, s6_mut: S6;
+    s7, s7_mut: S7;
+    s8, s8_mut: S8;
+    s9, s9_mut: S9;
+    s10, s10_mut: S10;
+    s11, s11_mut: S11;
+    t3, t3_mut: T3;
+    t4, t4_mut: T4;
+    t5, t5_mut: T5;
+    t6, t6_mut: T6;
 }
 
 impl<T> core::fmt::Debug for Registers<T>
@@ -291,7 +171,7 @@ where
 /// A struct encapsulated the behaviour of the zero register:
 /// - always yields zero when read
 /// - can be assigned to but this effectively discards the value
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub(super) struct ZeroRegister<T> {
     /// This value will always be set to zero. Shared references are given to
     /// this value.
@@ -339,9 +219,9 @@ where
 
 /// A single processor register.
 #[repr(u8)]
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 #[allow(clippy::upper_case_acronyms)]
-pub(super) enum Register {
+pub enum Register {
     /// The zero register.
     ///
     /// This register will always yield zero. Setting the destination register
@@ -571,7 +451,7 @@ impl Register {
     /// Frame pointer register.
     ///
     /// Actually an alias to register `S0`.
-    pub(super) const FP: Self = Self::S0;
+    pub const FP: Self = Self::S0;
     /// Converts the [u8] into a [Register].
     ///
     /// The [u8] will be masked to ensure it always returns a valid register.
@@ -619,6 +499,44 @@ impl Register {
             _ => panic!("Unreachable: Already masked the value"),
         }
     }
+
+    /// The ABI name of the register, for example `"a0"`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Register::ZERO => "zero",
+            Register::RA => "ra",
+            Register::SP => "sp",
+            Register::GP => "gp",
+            Register::TP => "tp",
+            Register::T0 => "t0",
+            Register::T1 => "t1",
+            Register::T2 => "t2",
+            Register::S0 => "s0",
+            Register::S1 => "s1",
+            Register::A0 => "a0",
+            Register::A1 => "a1",
+            Register::A2 => "a2",
+            Register::A3 => "a3",
+            Register::A4 => "a4",
+            Register::A5 => "a5",
+            Register::A6 => "a6",
+            Register::A7 => "a7",
+            Register::S2 => "s2",
+            Register::S3 => "s3",
+            Register::S4 => "s4",
+            Register::S5 => "s5",
+            Register::S6 => "s6",
+            Register::S7 => "s7",
+            Register::S8 => "s8",
+            Register::S9 => "s9",
+            Register::S10 => "s10",
+            Register::S11 => "s11",
+            Register::T3 => "t3",
+            Register::T4 => "t4",
+            Register::T5 => "t5",
+            Register::T6 => "t6",
+        }
+    }
 }
 
 impl From<u8> for Register {
@@ -630,83 +548,18 @@ impl From<u8> for Register {
 impl<T> std::ops::Index<u8> for Registers<T> {
     type Output = T;
 
+    #[inline]
     fn index(&self, index: u8) -> &Self::Output {
-        match index {
-            0 => &self.zero,
-            1 => &self.ra,
-            2 => &self.sp,
-            3 => &self.gp,
-            4 => &self.tp,
-            5 => &self.t0,
-            6 => &self.t1,
-            7 => &self.t2,
-            8 => &self.s0,
-            9 => &self.s1,
-            10 => &self.a0,
-            11 => &self.a1,
-            12 => &self.a2,
-            13 => &self.a3,
-            14 => &self.a4,
-            15 => &self.a5,
-            16 => &self.a6,
-            17 => &self.a7,
-            18 => &self.s2,
-            19 => &self.s3,
-            20 => &self.s4,
-            21 => &self.s5,
-            22 => &self.s6,
-            23 => &self.s7,
-            24 => &self.s8,
-            25 => &self.s9,
-            26 => &self.s10,
-            27 => &self.s11,
-            28 => &self.t3,
-            29 => &self.t4,
-            30 => &self.t5,
-            31 => &self.t6,
-            _ => panic!("Out of bounds"),
-        }
+        &self.values[index as usize]
     }
 }
 
 impl<T> std::ops::Index<Register> for Registers<T> {
     type Output = T;
 
+    #[inline]
     fn index(&self, index: Register) -> &Self::Output {
-        match index {
-            Register::ZERO => &self.zero,
-            Register::RA => &self.ra,
-            Register::SP => &self.sp,
-            Register::GP => &self.gp,
-            Register::TP => &self.tp,
-            Register::T0 => &self.t0,
-            Register::T1 => &self.t1,
-            Register::T2 => &self.t2,
-            Register::S0 => &self.s0,
-            Register::S1 => &self.s1,
-            Register::A0 => &self.a0,
-            Register::A1 => &self.a1,
-            Register::A2 => &self.a2,
-            Register::A3 => &self.a3,
-            Register::A4 => &self.a4,
-            Register::A5 => &self.a5,
-            Register::A6 => &self.a6,
-            Register::A7 => &self.a7,
-            Register::S2 => &self.s2,
-            Register::S3 => &self.s3,
-            Register::S4 => &self.s4,
-            Register::S5 => &self.s5,
-            Register::S6 => &self.s6,
-            Register::S7 => &self.s7,
-            Register::S8 => &self.s8,
-            Register::S9 => &self.s9,
-            Register::S10 => &self.s10,
-            Register::S11 => &self.s11,
-            Register::T3 => &self.t3,
-            Register::T4 => &self.t4,
-            Register::T5 => &self.t5,
-            Register::T6 => &self.t6,
-        }
+        &self[index as u8]
     }
 }
 
@@ -714,41 +567,11 @@ impl<T> std::ops::IndexMut<u8> for Registers<T>
 where
     T: Default,
 {
+    #[inline]
     fn index_mut(&mut self, index: u8) -> &mut Self::Output {
         match index {
             0 => &mut self.zero,
-            1 => &mut self.ra,
-            2 => &mut self.sp,
-            3 => &mut self.gp,
-            4 => &mut self.tp,
-            5 => &mut self.t0,
-            6 => &mut self.t1,
-            7 => &mut self.t2,
-            8 => &mut self.s0,
-            9 => &mut self.s1,
-            10 => &mut self.a0,
-            11 => &mut self.a1,
-            12 => &mut self.a2,
-            13 => &mut self.a3,
-            14 => &mut self.a4,
-            15 => &mut self.a5,
-            16 => &mut self.a6,
-            17 => &mut self.a7,
-            18 => &mut self.s2,
-            19 => &mut self.s3,
-            20 => &mut self.s4,
-            21 => &mut self.s5,
-            22 => &mut self.s6,
-            23 => &mut self.s7,
-            24 => &mut self.s8,
-            25 => &mut self.s9,
-            26 => &mut self.s10,
-            27 => &mut self.s11,
-            28 => &mut self.t3,
-            29 => &mut self.t4,
-            30 => &mut self.t5,
-            31 => &mut self.t6,
-            _ => panic!("Out of bounds"),
+            _ => &mut self.values[index as usize],
         }
     }
 }
@@ -757,41 +580,9 @@ impl<T> std::ops::IndexMut<Register> for Registers<T>
 where
     T: Default,
 {
+    #[inline]
     fn index_mut(&mut self, index: Register) -> &mut Self::Output {
-        match index {
-            Register::ZERO => &mut self.zero,
-            Register::RA => &mut self.ra,
-            Register::SP => &mut self.sp,
-            Register::GP => &mut self.gp,
-            Register::TP => &mut self.tp,
-            Register::T0 => &mut self.t0,
-            Register::T1 => &mut self.t1,
-            Register::T2 => &mut self.t2,
-            Register::S0 => &mut self.s0,
-            Register::S1 => &mut self.s1,
-            Register::A0 => &mut self.a0,
-            Register::A1 => &mut self.a1,
-            Register::A2 => &mut self.a2,
-            Register::A3 => &mut self.a3,
-            Register::A4 => &mut self.a4,
-            Register::A5 => &mut self.a5,
-            Register::A6 => &mut self.a6,
-            Register::A7 => &mut self.a7,
-            Register::S2 => &mut self.s2,
-            Register::S3 => &mut self.s3,
-            Register::S4 => &mut self.s4,
-            Register::S5 => &mut self.s5,
-            Register::S6 => &mut self.s6,
-            Register::S7 => &mut self.s7,
-            Register::S8 => &mut self.s8,
-            Register::S9 => &mut self.s9,
-            Register::S10 => &mut self.s10,
-            Register::S11 => &mut self.s11,
-            Register::T3 => &mut self.t3,
-            Register::T4 => &mut self.t4,
-            Register::T5 => &mut self.t5,
-            Register::T6 => &mut self.t6,
-        }
+        &mut self[index as u8]
     }
 }
 
@@ -803,11 +594,66 @@ mod test {
     #[test]
     fn debug_formatting_registers_ignores_zeros() {
         let mut regs = Registers::default();
-        regs.sp = 32;
-        regs.t0 = 33;
+        *regs.sp_mut() = 32;
+        *regs.t0_mut() = 33;
         assert_eq!(format!("{:?}", regs), "Registers { sp: 32, t0: 33 }");
     }
 
+    #[test]
+    fn named_accessors() {
+        let mut regs = Registers::default();
+        *regs.a0_mut() = 42;
+        *regs.zero_mut() = 1;
+        assert_eq!(*regs.a0(), 42);
+        assert_eq!(regs[Register::A0], 42);
+        assert_eq!(*regs.zero(), 0);
+    }
+
+    #[test]
+    fn diff() {
+        use crate::test::macros::*;
+
+        let before = register_state! {sp: 32, a0: 5};
+        let after = register_state! {sp: 32, a0: 42, t6: -1};
+        assert_eq!(
+            before.diff(&after),
+            vec![(Register::A0, 5, 42), (Register::T6, 0, -1)]
+        );
+        assert_eq!(after.diff(&after), vec![]);
+    }
+
+    #[test]
+    fn diff_after_addi() {
+        use crate::test::macros::*;
+        use crate::{
+            csr::CSR32, instruction_set::InstructionSet, instructions::Instruction,
+            processor::Processor,
+        };
+
+        let before: Processor<i32, CSR32> = processor_state!(registers: {a0: 5, a1: 37});
+        let mut after = processor_state!(registers: {a0: 5, a1: 37});
+        Instruction::ADDI {
+            rd: Register::A2,
+            rs1: Register::A1,
+            imm: 5,
+        }
+        .execute(&mut after)
+        .unwrap();
+
+        assert_eq!(
+            before.registers().diff(after.registers()),
+            vec![(Register::A2, 0, 42)]
+        );
+    }
+
+    #[test]
+    fn name() {
+        assert_eq!(Register::ZERO.name(), "zero");
+        assert_eq!(Register::FP.name(), "s0");
+        assert_eq!(Register::S11.name(), "s11");
+        assert_eq!(Register::T6.name(), "t6");
+    }
+
     #[test]
     fn from_u8() {
         assert_eq!(Register::from(31), Register::T6);
@@ -856,6 +702,14 @@ mod test {
         }
     }
 
+    #[test]
+    fn registers_zero_register_index_u8() {
+        let mut registers = Registers::default();
+        registers[0] = 42;
+        assert_eq!(registers[0], 0);
+        assert_eq!(registers, Registers::default());
+    }
+
     #[test]
     #[should_panic]
     fn index_out_of_bounds_mut() {
@@ -103,6 +103,28 @@ pub(crate) fn dbg_inst(raw_instruction: u32) -> u32 {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::instructions::RawInstruction;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn raw_instruction_fields() {
+        for value in [
+            0b_0110110_10101_10110_011_10111_0010011,
+            0x00c5_8533,
+            0x0000_0013,
+            0xffff_ffff,
+            0x8000_0001,
+        ] {
+            let raw = RawInstruction(value);
+            let bin = BinInstruction(value);
+            assert_eq!(raw.opcode() as u32, bin.opcode());
+            assert_eq!(raw.rd() as u32, bin.rd());
+            assert_eq!(raw.funct3() as u32, bin.func3());
+            assert_eq!(raw.rs1() as u32, bin.rs1());
+            assert_eq!(raw.rs2() as u32, bin.rs2());
+            assert_eq!(raw.funct7() as u32, bin.func7());
+        }
+    }
 
     #[test]
     fn debug_print() {
@@ -1,18 +1,4 @@
 //! Macros to help write clean and concise tests without lots of boiler plate.
-use crate::instructions::Instruction;
-
-/// Used by the test macros to make it possible to treat instructions and
-/// pseudoinstructions together as if of the same type for example in a vec or
-/// in the `test_execute` macro.
-impl IntoIterator for Instruction {
-    type Item = Self;
-
-    type IntoIter = std::iter::Once<Self::Item>;
-
-    fn into_iter(self) -> Self::IntoIter {
-        std::iter::once(self)
-    }
-}
 
 /// A helper macro for creating an instance of [crate::registers::Registers].
 ///
@@ -25,17 +11,120 @@ impl IntoIterator for Instruction {
 /// };
 /// ```
 macro_rules! register_state {
-    ($($register:ident: $value:expr),* $(,)?) => {
-        crate::registers::Registers {
-            $($register: $value,)*
-            ..Default::default()
-        }
-    };
+    ($($register:ident: $value:expr),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut registers = crate::registers::Registers::default();
+        $(
+            registers[crate::test::macros::register!($register)] = $value;
+        )*
+        registers
+    }};
     ({$($register:ident: $value:expr),* $(,)?}) => {
         register_state!($($register: $value,)*)
     };
 }
 
+/// Maps the ABI name of a register to its [crate::registers::Register], so an
+/// unknown name fails to compile.
+macro_rules! register {
+    (zero) => {
+        crate::registers::Register::ZERO
+    };
+    (ra) => {
+        crate::registers::Register::RA
+    };
+    (sp) => {
+        crate::registers::Register::SP
+    };
+    (gp) => {
+        crate::registers::Register::GP
+    };
+    (tp) => {
+        crate::registers::Register::TP
+    };
+    (t0) => {
+        crate::registers::Register::T0
+    };
+    (t1) => {
+        crate::registers::Register::T1
+    };
+    (t2) => {
+        crate::registers::Register::T2
+    };
+    (s0) => {
+        crate::registers::Register::S0
+    };
+    (s1) => {
+        crate::registers::Register::S1
+    };
+    (a0) => {
+        crate::registers::Register::A0
+    };
+    (a1) => {
+        crate::registers::Register::A1
+    };
+    (a2) => {
+        crate::registers::Register::A2
+    };
+    (a3) => {
+        crate::registers::Register::A3
+    };
+    (a4) => {
+        crate::registers::Register::A4
+    };
+    (a5) => {
+        crate::registers::Register::A5
+    };
+    (a6) => {
+        crate::registers::Register::A6
+    };
+    (a7) => {
+        crate::registers::Register::A7
+    };
+    (s2) => {
+        crate::registers::Register::S2
+    };
+    (s3) => {
+        crate::registers::Register::S3
+    };
+    (s4) => {
+        crate::registers::Register::S4
+    };
+    (s5) => {
+        crate::registers::Register::S5
+    };
+    (s6) => {
+        crate::registers::Register::S6
+    };
+    (s7) => {
+        crate::registers::Register::S7
+    };
+    (s8) => {
+        crate::registers::Register::S8
+    };
+    (s9) => {
+        crate::registers::Register::S9
+    };
+    (s10) => {
+        crate::registers::Register::S10
+    };
+    (s11) => {
+        crate::registers::Register::S11
+    };
+    (t3) => {
+        crate::registers::Register::T3
+    };
+    (t4) => {
+        crate::registers::Register::T4
+    };
+    (t5) => {
+        crate::registers::Register::T5
+    };
+    (t6) => {
+        crate::registers::Register::T6
+    };
+}
+
 /// A helper macro for creating an instance of [crate::memory::Memory].
 ///
 /// # Example usage
@@ -51,7 +140,7 @@ macro_rules! memory_state {
         {
             let mut mem = crate::memory::Memory::default();
             $(
-                mem.store_word($location, $value);
+                mem.store_word($location, $value).unwrap();
             )*
             mem
         }
@@ -73,13 +162,7 @@ macro_rules! memory_state {
 /// ```
 macro_rules! csr_state {
     ($($index:literal: $value:expr),* $(,)?) => {
-        {
-            let csr = crate::csr::CSR32::default();
-            $(
-                csr.read_write($index, $value);
-            )*
-            csr
-        }
+        crate::csr::CSR32::from_values([$(($index, $value)),*])
     };
     ({$($location:literal: $value:expr),* $(,)?}) => {
         csr_state!($($location: $value,)*)
@@ -96,6 +179,7 @@ macro_rules! csr_state {
 ///   memory: {4: 50},
 ///   csr: {0: 10},
 ///   pc: 4,
+///   privilege: Privilege::Supervisor,
 /// };
 /// ```
 macro_rules! processor_state {
@@ -104,22 +188,26 @@ macro_rules! processor_state {
         $(, memory: $memory_state:tt)?
         $(, csr: $csr:tt)?
         $(, pc: $program_counter1:expr)?
+        $(, privilege: $privilege1:expr)?
         $(,)?
-    ) => {
-        crate::processor::Processor {
+    ) => {{
+        let processor: crate::processor::Processor<_, _> = crate::processor::Processor {
             registers: register_state!($register_state)
             $(, memory: memory_state!($memory_state))?
             $(, csrs: csr_state!($csr))?
             $(, pc: $program_counter1)?
+            $(, privilege: $privilege1)?
             , ..Default::default()
-        }
-    };
+        };
+        processor
+    }};
     (
         {
             registers: $register_state:tt
             $(, memory: $memory_state:tt)?
             $(, csr: $csr:tt)?
             $(, pc: $program_counter2:expr)?
+            $(, privilege: $privilege2:expr)?
             $(,)?
         }
     ) => {
@@ -128,6 +216,7 @@ macro_rules! processor_state {
             $(, memory: $memory_state)?
             $(, csr: $csr)?
             $(, pc: $program_counter2)?
+            $(, privilege: $privilege2)?
         )
     };
 }
@@ -197,30 +286,6 @@ macro_rules! test_execute {
     }};
 }
 
-/// Macro for creating a vec of instructions from a mixture of instructions and
-/// pseudoinstructions.
-///
-/// # Example usage
-///
-/// ```ignore
-/// let instructions = instructions![
-///     Instruction::LI(Register::A0, 21),
-///     Instruction::LI(Register::A1, 21),
-///     Instruction::ADD {
-///         rd: Register::A2,
-///         rs1: Register::A1,
-///         rs2: Register::A0,
-///     },
-/// ];
-/// ```
-macro_rules! instructions {
-    ($($instruction:expr),* $(,)*) => {{
-        let mut vec = Vec::new();
-        $(vec.extend($instruction);)*
-        vec
-    }};
-}
-
 /// A helper macro for testing the execution of a small programme on the
 /// [crate::processor::Processor]
 ///
@@ -258,10 +323,11 @@ macro_rules! processor_test {
     };
 }
 
+pub(crate) use crate::instructions;
 pub(crate) use csr_state;
-pub(crate) use instructions;
 pub(crate) use memory_state;
 pub(crate) use processor_state;
 pub(crate) use processor_test;
+pub(crate) use register;
 pub(crate) use register_state;
 pub(crate) use test_execute;